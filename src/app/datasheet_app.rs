@@ -1,11 +1,12 @@
-use std::{fs::{self, create_dir, remove_dir_all, remove_file, File}, path::PathBuf};
+use std::{collections::BTreeMap, fs::{self, create_dir, remove_dir_all, remove_file, File}, path::{Path, PathBuf}};
 
-use crate::{data::{Unit, UnitEditData}, export::{export_unit, load_export_templates, ExportTemplates, ExportType}};
+use crate::{data::{apply_battle, apply_requisition, Ability, Agendas, BattleRecord, BattleResult, BattleWizard, CrusadeForce, CrusadeRank, CrusadeUnitData, CrusadeUpgrade, Detachments, Glossary, OutOfAction, PendingScar, RankUp, Requisition, RequisitionWizard, RollRecord, RollTable, RollTableKind, RollTables, Stratagems, Unit, UnitEditData, UnitOverrides, Weapon, WeaponEditData, WeaponLibrary, WeaponReference}, export::{export_folder, export_to_tts, export_unit, load_export_templates, print_units, ExportTemplates, ExportType, FolderFile}, import::{read_catalogue, read_roster, read_units, sanitise_filename, CsvImport}};
 
-use super::{edit_settings::settings_panel, edit_unit::edit_unit, pop_up_menus, read_unit::read_unit, shortcuts::*};
+use super::{edit_agendas::agendas_panel, edit_detachments::detachments_panel, edit_glossary::glossary_panel, edit_roll_tables::roll_tables_panel, edit_settings::settings_panel, edit_stratagems::stratagems_panel, edit_unit::edit_unit, edit_weapon_library::weapon_library_panel, order_of_battle::order_of_battle_panel, pop_up_menus, problems::problems_panel, read_unit::read_unit, shortcuts::*};
 use eframe::App;
 use egui::{CollapsingHeader, Color32, Context, Layout, RichText, ThemePreference};
 use egui_keybind::{Bind, Shortcut};
+use serde::de::DeserializeOwned;
 use ron::{
     de::from_reader,
     ser::{to_string_pretty, PrettyConfig}
@@ -19,7 +20,15 @@ const CURRENT_VERSION: &str = "v1.7";
 #[derive(PartialEq)]
 pub enum OpenFile {
     Index((usize, usize)),
-    Settings
+    Settings,
+    WeaponLibrary,
+    Glossary,
+    RollTables,
+    Agendas,
+    Detachments,
+    Stratagems,
+    Problems,
+    OrderOfBattle
 }
 
 
@@ -55,19 +64,44 @@ pub struct DatasheetApp {
 
     pub export_templates: ExportTemplates,
     pub export_unit: Option<((usize, usize), ExportType)>,
+    pub print_folder: Option<usize>,
+    pub export_folder: Option<(usize, ExportType)>,
+    pub csv_import: Option<CsvImport>,
+    pub battle_wizard: Option<BattleWizard>,
+    pub requisition_wizard: Option<RequisitionWizard>,
+    pub rank_ups: Vec<RankUp>,
+    pub pending_scars: Vec<PendingScar>,
     pub last_export_type: ExportType,
     pub last_export_dir: PathBuf,
 
     pub show_confirmation_dialog: bool,
     pub allowed_to_close: bool,
+    pub report: Option<(String, Vec<String>)>,
 
 
 
     pub settings_menu_open: bool,
     pub settings: DatasheetAppSettings,
-    pub folder_settings: Option<DatasheetAppSettings>
+    pub folder_settings: Option<DatasheetAppSettings>,
+    pub weapon_library: WeaponLibrary,
+    pub weapon_library_edit: Vec<(String, String, WeaponEditData)>, // saved id, id, weapon
+    pub glossary: Glossary,
+    pub glossary_edit: Glossary,
+    pub roll_tables: RollTables,
+    pub roll_tables_edit: RollTables,
+    pub agendas: Agendas,
+    pub agendas_edit: Agendas,
+    pub detachments: Detachments,
+    pub detachments_edit: Detachments,
+    pub stratagems: Stratagems,
+    pub stratagems_edit: Stratagems,
+    pub crusade_force: CrusadeForce,
+    pub crusade_force_edit: CrusadeForce,
 }
 
+/// Files of a working folder that aren't units, without their .ron extension
+pub const FOLDER_FILES: [&str; 8] = ["SETTINGS", "WEAPONS", "GLOSSARY", "ROLLTABLES", "AGENDAS", "DETACHMENTS", "STRATAGEMS", "CRUSADE"];
+
 impl DatasheetApp {
     fn open_folder(&mut self, path: PathBuf) {
         self.folder_path = path.as_path().to_str().unwrap().to_string();
@@ -75,19 +109,51 @@ impl DatasheetApp {
         self.working_dir = Vec::new();
         self.open_files = Vec::new();
         self.selected_file = 0;
-        
-        
+        self.weapon_library = WeaponLibrary::default();
+        self.glossary = Glossary::default();
+        self.roll_tables = RollTables::default();
+        self.agendas = Agendas::default();
+        self.detachments = Detachments::default();
+        self.stratagems = Stratagems::default();
+        self.crusade_force = CrusadeForce::default();
+        let mut errors = Vec::new();
+
         for path in dir {
             let path = path.unwrap();
             if path.file_type().unwrap().is_dir() {
                 self.read_dir(path.path());
                 continue;
-            } else if path.file_name() == "SETTINGS.ron" {
-                let f = File::open(path.path().clone()).unwrap();
-                let settings: DatasheetAppSettings = from_reader(f).unwrap();
-                self.folder_settings = Some(settings);
             }
+            let name = path.file_name().to_str().unwrap_or_default().to_string();
+            match name.as_str() {
+                "SETTINGS.ron" => self.folder_settings = read_folder_file(&path.path(), &mut errors),
+                "WEAPONS.ron" => self.weapon_library = read_folder_file(&path.path(), &mut errors).unwrap_or_default(),
+                "GLOSSARY.ron" => self.glossary = read_folder_file(&path.path(), &mut errors).unwrap_or_default(),
+                "ROLLTABLES.ron" => self.roll_tables = read_folder_file(&path.path(), &mut errors).unwrap_or_default(),
+                "AGENDAS.ron" => self.agendas = read_folder_file(&path.path(), &mut errors).unwrap_or_default(),
+                "DETACHMENTS.ron" => self.detachments = read_folder_file(&path.path(), &mut errors).unwrap_or_default(),
+                "STRATAGEMS.ron" => self.stratagems = read_folder_file(&path.path(), &mut errors).unwrap_or_default(),
+                "CRUSADE.ron" => self.crusade_force = read_folder_file(&path.path(), &mut errors).unwrap_or_default(),
+                _ => ()
+            }
+        }
+        if !errors.is_empty() {
+            errors.push("Defaults are used instead, saving one of them will overwrite its file".to_string());
+            self.report = Some(("Folder Files Not Read".to_string(), errors));
         }
+
+        self.glossary_edit = self.glossary.clone();
+        self.roll_tables_edit = self.roll_tables.clone();
+        self.agendas_edit = self.agendas.clone();
+        self.detachments_edit = self.detachments.clone();
+        self.stratagems_edit = self.stratagems.clone();
+        self.crusade_force_edit = self.crusade_force.clone();
+
+        self.weapon_library_edit = self.weapon_library.weapons.iter().map(|(id, weapon)| (id.clone(), id.clone(), WeaponEditData::from(weapon))).collect();
+        self.resolve_library_weapons(&BTreeMap::new());
+        self.resolve_enhancements();
+        self.resolve_bases();
+        self.rerank_units();
     }
 
     fn read_dir(&mut self, path: PathBuf) {
@@ -102,8 +168,18 @@ impl DatasheetApp {
             if let Some(extension) = path.extension() {
                 if extension.to_str() == Some("ron") {
                     let f = File::open(path.clone()).unwrap();
-                    let unit: Unit = from_reader(f).unwrap();
-                    unit_edit_data.push(UnitEditData::from((&unit, path.file_name().unwrap().to_str().unwrap().replace(".ron", "").to_string())));
+                    let mut unit: Unit = from_reader(f).unwrap();
+                    let filename = path.file_name().unwrap().to_str().unwrap().replace(".ron", "").to_string();
+                    // files from before weapons had ids are migrated on load
+                    if unit.assign_weapon_uids() {
+                        unit = UnitEditData::from((&unit, filename.clone())).into();
+                        let config = PrettyConfig::new()
+                            .depth_limit(2)
+                            .separate_tuple_members(true)
+                            .enumerate_arrays(true);
+                        let _ = fs::write(path.clone(), to_string_pretty(&unit, config).expect("Failed to serialize"));
+                    }
+                    unit_edit_data.push(UnitEditData::from((&unit, filename)));
                     units.push(unit);
                 }
             }
@@ -119,18 +195,58 @@ impl DatasheetApp {
     fn display_current(&mut self, ctx: &Context) {
         match self.open_files[self.selected_file] {
             OpenFile::Settings => settings_panel(self, ctx),
+            OpenFile::WeaponLibrary => weapon_library_panel(self, ctx),
+            OpenFile::Glossary => glossary_panel(self, ctx),
+            OpenFile::RollTables => roll_tables_panel(self, ctx),
+            OpenFile::Agendas => agendas_panel(self, ctx),
+            OpenFile::Detachments => detachments_panel(self, ctx),
+            OpenFile::Stratagems => stratagems_panel(self, ctx),
+            OpenFile::Problems => problems_panel(self, ctx),
+            OpenFile::OrderOfBattle => order_of_battle_panel(self, ctx),
             OpenFile::Index(index) => {
                 match self.mode {
-                    DatasheetAppMode::Edit => edit_unit(ctx,  &mut self.working_dir[index.0].unit_edit_data[index.1]),
-                    DatasheetAppMode::Read => read_unit(self.get_settings(), self.settings.dark_mode, ctx, &self.working_dir[index.0].units[index.1]),
+                    DatasheetAppMode::Edit => self.edit_current_unit(ctx, index),
+                    DatasheetAppMode::Read => read_unit(self.get_settings(), &self.glossary, &self.crusade_force.log, &self.unit_reference(index.0, index.1), self.settings.dark_mode, ctx, &self.working_dir[index.0].units[index.1]),
                 }
             },
         };
     }
 
+    fn edit_current_unit(&mut self, ctx: &Context, (folder, index): (usize, usize)) {
+        let bases: Vec<String> = (0..self.working_dir.len())
+            .flat_map(|i| (0..self.working_dir[i].units.len()).map(move |j| (i, j)))
+            .filter(|other| *other != (folder, index))
+            .map(|(i, j)| self.unit_reference(i, j))
+            .collect();
+        let previous = self.working_dir[folder].unit_edit_data[index].base.clone();
+        let base = previous.as_ref().and_then(|base| self.find_unit(&base.unit)).map(|(i, j)| self.working_dir[i].units[j].clone());
+
+        edit_unit(ctx, &mut self.working_dir[folder].unit_edit_data[index], &self.weapon_library, &self.roll_tables, &self.detachments, &bases, base.as_ref());
+
+        // picking a new base starts the variant as an exact copy of it
+        let edit_data = &self.working_dir[folder].unit_edit_data[index];
+        if edit_data.base.as_ref().map(|base| &base.unit) != previous.as_ref().map(|base| &base.unit) {
+            let Some(base) = edit_data.base.clone() else {return};
+            let Some((i, j)) = self.find_unit(&base.unit) else {return};
+            let current: Unit = edit_data.clone().into();
+            let mut resolved = base.overrides.apply(&self.working_dir[i].units[j], &current);
+            resolved.assign_weapon_uids();
+            let mut new_edit_data = UnitEditData::from((&resolved, edit_data.prev_filename.clone()));
+            new_edit_data.filename = edit_data.filename.clone();
+            self.working_dir[folder].unit_edit_data[index] = new_edit_data;
+        }
+    }
+
     pub fn save_current(&mut self) {
         let (extra_dir, intra_dir) = match self.open_files[self.selected_file] {
             OpenFile::Index(index) => index,
+            OpenFile::WeaponLibrary => return self.save_weapon_library(),
+            OpenFile::Glossary => return self.save_glossary(),
+            OpenFile::RollTables => return self.save_roll_tables(),
+            OpenFile::Agendas => return self.save_agendas(),
+            OpenFile::Detachments => return self.save_detachments(),
+            OpenFile::Stratagems => return self.save_stratagems(),
+            OpenFile::OrderOfBattle => return self.save_crusade_force(),
             _ => return
         };
 
@@ -143,23 +259,79 @@ impl DatasheetApp {
         let mut data = self.working_dir[extra_dir].unit_edit_data[intra_dir].clone();
         
         
-        let new_unit: Unit = data.clone().into();
+        let mut new_unit: Unit = data.clone().into();
+        self.weapon_library.relink_unit(&mut new_unit);
+        let base_index = new_unit.base.as_ref()
+            .and_then(|base| self.find_unit(&base.unit))
+            .filter(|index| *index != (extra_dir, intra_dir));
+        if let Some((i, j)) = base_index {
+            let base_unit = &self.working_dir[i].units[j];
+            let overrides = UnitOverrides::diff(&new_unit, base_unit);
+            if let Some(base) = &mut new_unit.base {
+                base.overrides = overrides;
+            }
+            // resolved straight away so weapons moved to the variant's uids are saved with them
+            new_unit = self.resolve_variant(&new_unit, base_unit);
+        }
+        self.rank_unit(&mut new_unit);
+        // the saved history is the one to add to, the edit data's copy may be out of date
+        let previous = &self.working_dir[extra_dir].units[intra_dir].crusade_data;
+        new_unit.crusade_data.history = previous.history.clone();
+        if new_unit.crusade_unit {
+            new_unit.crusade_data.record_history(previous, &chrono::Local::now().format("%Y-%m-%d").to_string());
+        }
+        let previous_rank = previous.rank;
+        // the edit data is rebuilt so it matches what was saved, otherwise it would count as unsaved edits
+        self.working_dir[extra_dir].unit_edit_data[intra_dir] = UnitEditData::from((&new_unit, data.prev_filename.clone()));
         self.working_dir[extra_dir].units[intra_dir] = new_unit;
         let s = to_string_pretty(&self.working_dir[extra_dir].units[intra_dir], config).expect("Failed to serialize");
+        // queued before the rename, which repoints them at the new reference
+        self.queue_rank_ups((extra_dir, intra_dir), previous_rank);
 
 
         if data.prev_filename != data.filename {
+            let folder = &self.working_dir[extra_dir].name;
+            self.rename_references(&format!("{}/{}", folder, data.prev_filename), &format!("{}/{}", folder, data.filename));
             self.delete_unit(extra_dir, intra_dir);
             data.prev_filename = data.filename.clone(); // update filename
         }
 
         let path = format!("{}/{}.ron", self.working_dir[extra_dir].path, data.filename.clone());
         let _ = fs::write(path, s);
+        self.resolve_bases();
     }
 
     fn reset_current(&mut self) {
         let (extra_dir, intra_dir) = match self.open_files[self.selected_file] {
             OpenFile::Index(index) => index,
+            OpenFile::WeaponLibrary => {
+                self.weapon_library_edit = self.weapon_library.weapons.iter().map(|(id, weapon)| (id.clone(), id.clone(), WeaponEditData::from(weapon))).collect();
+                return;
+            },
+            OpenFile::Glossary => {
+                self.glossary_edit = self.glossary.clone();
+                return;
+            },
+            OpenFile::RollTables => {
+                self.roll_tables_edit = self.roll_tables.clone();
+                return;
+            },
+            OpenFile::Agendas => {
+                self.agendas_edit = self.agendas.clone();
+                return;
+            },
+            OpenFile::Detachments => {
+                self.detachments_edit = self.detachments.clone();
+                return;
+            },
+            OpenFile::Stratagems => {
+                self.stratagems_edit = self.stratagems.clone();
+                return;
+            },
+            OpenFile::OrderOfBattle => {
+                self.crusade_force_edit = self.crusade_force.clone();
+                return;
+            },
             _ => return
         };
 
@@ -260,6 +432,13 @@ impl DatasheetApp {
         return &self.settings;
     }
 
+    pub fn get_settings_mut(&mut self) -> &mut DatasheetAppSettings {
+        if let Some(settings) = &mut self.folder_settings {
+            return settings;
+        }
+        return &mut self.settings;
+    }
+
     fn save_folder_settings(&self) {
         if let Some(settings) = &self.folder_settings {
             let config = PrettyConfig::new()
@@ -272,20 +451,600 @@ impl DatasheetApp {
         }
     }
 
+    pub fn open_file(&mut self, file: OpenFile) {
+        match self.open_files.iter().position(|open| open == &file) {
+            Some(i) => self.selected_file = i,
+            None => {
+                self.selected_file = self.open_files.len();
+                self.open_files.push(file);
+            }
+        }
+    }
+
+    fn save_weapon_library(&mut self) {
+        let mut weapons = BTreeMap::new();
+        let mut renames = BTreeMap::new();
+        for (saved_id, id, weapon) in self.weapon_library_edit.iter_mut() {
+            let start = if id.trim().is_empty() {weapon.name.to_lowercase().replace(' ', "_")} else {id.trim().to_string()};
+            let mut new_id = start.clone();
+            let mut k = 1;
+            while weapons.contains_key(&new_id) {
+                new_id = format!("{}_{}", start, k);
+                k += 1;
+            }
+            if !saved_id.is_empty() && *saved_id != new_id {
+                renames.insert(saved_id.clone(), new_id.clone());
+            }
+            *id = new_id.clone();
+            *saved_id = new_id.clone();
+
+            let mut weapon: Weapon = weapon.clone().into();
+            weapon.library = None;
+            weapons.insert(new_id, weapon);
+        }
+        self.weapon_library = WeaponLibrary {weapons};
+
+        let config = PrettyConfig::new()
+            .depth_limit(3)
+            .separate_tuple_members(true)
+            .enumerate_arrays(true);
+        let s = to_string_pretty(&self.weapon_library, config).expect("Failed to serialize");
+        let _ = fs::write(format!("{}/WEAPONS.ron", self.folder_path), s);
+
+        self.resolve_library_weapons(&renames);
+    }
+
+    fn save_glossary(&mut self) {
+        self.glossary_edit.entries.retain(|entry| !entry.name.trim().is_empty());
+        self.glossary = self.glossary_edit.clone();
+
+        let config = PrettyConfig::new()
+            .depth_limit(3)
+            .separate_tuple_members(true)
+            .enumerate_arrays(true);
+        let s = to_string_pretty(&self.glossary, config).expect("Failed to serialize");
+        let _ = fs::write(format!("{}/GLOSSARY.ron", self.folder_path), s);
+    }
+
+    fn save_roll_tables(&mut self) {
+        for table in self.roll_tables_edit.tables.iter_mut() {
+            table.tidy();
+        }
+        self.roll_tables = self.roll_tables_edit.clone();
+
+        let config = PrettyConfig::new()
+            .depth_limit(4)
+            .separate_tuple_members(true)
+            .enumerate_arrays(true);
+        let s = to_string_pretty(&self.roll_tables, config).expect("Failed to serialize");
+        let _ = fs::write(format!("{}/ROLLTABLES.ron", self.folder_path), s);
+    }
+
+    fn save_agendas(&mut self) {
+        self.agendas = self.agendas_edit.clone();
+
+        let config = PrettyConfig::new()
+            .depth_limit(3)
+            .separate_tuple_members(true)
+            .enumerate_arrays(true);
+        let s = to_string_pretty(&self.agendas, config).expect("Failed to serialize");
+        let _ = fs::write(format!("{}/AGENDAS.ron", self.folder_path), s);
+    }
+
+    fn save_detachments(&mut self) {
+        for enhancement in self.detachments_edit.detachments.iter_mut().flat_map(|detachment| detachment.enhancements.iter_mut()) {
+            enhancement.keywords = enhancement.keywords.iter().map(|keyword| keyword.trim().to_string()).filter(|keyword| !keyword.is_empty()).collect();
+        }
+        self.detachments = self.detachments_edit.clone();
+        self.write_detachments();
+        self.resolve_enhancements();
+    }
+
+    fn write_detachments(&self) {
+        let config = PrettyConfig::new()
+            .depth_limit(4)
+            .separate_tuple_members(true)
+            .enumerate_arrays(true);
+        let s = to_string_pretty(&self.detachments, config).expect("Failed to serialize");
+        let _ = fs::write(format!("{}/DETACHMENTS.ron", self.folder_path), s);
+    }
+
+    fn save_stratagems(&mut self) {
+        for stratagem in self.stratagems_edit.stratagems.iter_mut() {
+            stratagem.keywords = stratagem.keywords.iter().map(|keyword| keyword.trim().to_string()).filter(|keyword| !keyword.is_empty()).collect();
+        }
+        self.stratagems = self.stratagems_edit.clone();
+        self.write_stratagems();
+    }
+
+    fn write_stratagems(&self) {
+        let config = PrettyConfig::new()
+            .depth_limit(3)
+            .separate_tuple_members(true)
+            .enumerate_arrays(true);
+        let s = to_string_pretty(&self.stratagems, config).expect("Failed to serialize");
+        let _ = fs::write(format!("{}/STRATAGEMS.ron", self.folder_path), s);
+    }
+
+    /// Updates every unit's copy of its enhancement from the detachments, rewriting the ones that changed.
+    /// Units whose enhancement no longer exists keep their copy
+    fn resolve_enhancements(&mut self) {
+        for i in 0..self.working_dir.len() {
+            for j in 0..self.working_dir[i].units.len() {
+                let Some(assigned) = &self.working_dir[i].units[j].enhancement else {continue};
+                let Some(enhancement) = self.detachments.find_enhancement(&assigned.detachment, &assigned.enhancement.name) else {continue};
+                if *enhancement == assigned.enhancement {
+                    continue;
+                }
+                let enhancement = enhancement.clone();
+                let folder = &mut self.working_dir[i];
+                for assigned in [&mut folder.units[j].enhancement, &mut folder.unit_edit_data[j].enhancement].into_iter().flatten() {
+                    assigned.enhancement = enhancement.clone();
+                }
+                self.write_unit(i, j);
+            }
+        }
+    }
+
+    /// Updates every unit with weapons linked to the library, rewriting the ones that changed
+    fn resolve_library_weapons(&mut self, renames: &BTreeMap<String, String>) {
+        for i in 0..self.working_dir.len() {
+            for j in 0..self.working_dir[i].units.len() {
+                let mut unit = self.working_dir[i].units[j].clone();
+                if self.weapon_library.resolve_unit(&mut unit, renames) {
+                    let filename = self.working_dir[i].unit_edit_data[j].prev_filename.clone();
+                    // round trip through the edit data so crusade weapons are rebuilt from the new profiles
+                    let mut unit: Unit = UnitEditData::from((&unit, filename.clone())).into();
+                    self.rank_unit(&mut unit);
+                    // unsaved edits are kept, saving relinks their weapons anyway
+                    if !self.has_pending_edits(i, j) {
+                        self.working_dir[i].unit_edit_data[j] = UnitEditData::from((&unit, filename));
+                    }
+                    self.working_dir[i].units[j] = unit;
+                    self.write_unit(i, j);
+                }
+            }
+        }
+    }
+
+    fn save_crusade_force(&mut self) {
+        self.crusade_force = self.crusade_force_edit.clone();
+        self.write_crusade_force();
+    }
+
+    fn write_crusade_force(&self) {
+        let config = PrettyConfig::new()
+            .depth_limit(3)
+            .separate_tuple_members(true)
+            .enumerate_arrays(true);
+        let s = to_string_pretty(&self.crusade_force, config).expect("Failed to serialize");
+        let _ = fs::write(format!("{}/CRUSADE.ron", self.folder_path), s);
+    }
+
+    /// The crusade units of the working folder, with their references
+    pub fn crusade_units(&self) -> Vec<(String, &Unit)> {
+        let mut units = Vec::new();
+        for (i, folder) in self.working_dir.iter().enumerate() {
+            for (j, unit) in folder.units.iter().enumerate() {
+                if unit.crusade_unit {
+                    units.push((self.unit_reference(i, j), unit));
+                }
+            }
+        }
+        units
+    }
+
+    /// Awards each unit in the battle its experience and out of action results, and adds the battle to the log
+    fn record_battle(&mut self, record: BattleRecord) {
+        let mut lines = Vec::new();
+        for battle_unit in record.units.iter() {
+            let Some((i, j)) = self.find_unit(&battle_unit.unit) else {continue};
+            let mut unit = self.working_dir[i].units[j].clone();
+            let previous = unit.crusade_data.clone();
+            let previous_rank = unit.crusade_data.rank;
+            apply_battle(&mut unit, battle_unit, &record);
+            let filename = self.working_dir[i].unit_edit_data[j].prev_filename.clone();
+            // round trip through the edit data so the crusade weapons are rebuilt
+            let mut unit: Unit = UnitEditData::from((&unit, filename.clone())).into();
+            self.rank_unit(&mut unit);
+            unit.crusade_data.record_history(&previous, &record.date);
+            self.store_crusade_change((i, j), unit);
+            self.queue_rank_ups((i, j), previous_rank);
+            if let Some((_, OutOfAction::BattleScar)) = &battle_unit.out_of_action {
+                self.pending_scars.push(PendingScar::new(battle_unit.unit.clone(), battle_unit.name.clone()));
+            }
+
+            let mut line = format!("{}: +{} XP", battle_unit.name, battle_unit.exp_gained);
+            if let Some((roll, outcome)) = &battle_unit.out_of_action {
+                line += &format!(", out of action roll {} - {}", roll, outcome.to_string());
+            }
+            lines.push(line);
+        }
+
+        for force in [&mut self.crusade_force, &mut self.crusade_force_edit] {
+            force.battles += 1;
+            if record.result == BattleResult::Victory {
+                force.victories += 1;
+            }
+            force.log.push(record.clone());
+        }
+        self.write_crusade_force();
+
+        self.report = Some(("Battle Recorded".to_string(), lines));
+    }
+
+    /// Spends the force's requisition points on a requisition, applies it to its unit and logs it
+    fn buy_requisition(&mut self, wizard: RequisitionWizard) {
+        if let Some(problem) = wizard.problem(&self.crusade_force, &self.crusade_units(), &self.weapon_library) {
+            self.report = Some(("Requisition Not Bought".to_string(), vec![problem]));
+            return;
+        }
+        let mut record = wizard.record();
+        if let Some((i, j)) = wizard.selected_unit().and_then(|unit| self.find_unit(&unit.reference)) {
+            let mut unit = self.working_dir[i].units[j].clone();
+            record.details = apply_requisition(&mut unit, &wizard, &self.weapon_library);
+            let filename = self.working_dir[i].unit_edit_data[j].prev_filename.clone();
+            // round trip through the edit data so the crusade weapons are rebuilt
+            let mut unit: Unit = UnitEditData::from((&unit, filename)).into();
+            self.rank_unit(&mut unit);
+            unit.crusade_data.record_history(&self.working_dir[i].units[j].crusade_data, &record.date);
+            self.store_crusade_change((i, j), unit);
+            self.resolve_bases();
+        } else if wizard.requisition.targets_unit() {
+            return;
+        }
+
+        for force in [&mut self.crusade_force, &mut self.crusade_force_edit] {
+            force.requisition_points = force.requisition_points.saturating_sub(record.cost);
+            if wizard.requisition == Requisition::IncreaseSupplyLimit {
+                force.supply_limit += wizard.supply;
+            }
+        }
+        if wizard.requisition == Requisition::IncreaseSupplyLimit {
+            record.details = format!("+{} supply, now {}", wizard.supply, self.crusade_force.supply_limit);
+        }
+        for force in [&mut self.crusade_force, &mut self.crusade_force_edit] {
+            force.requisitions.push(record.clone());
+        }
+        self.write_crusade_force();
+
+        self.report = Some(("Requisition Bought".to_string(), vec![record.to_string()]));
+    }
+
+    /// Whether a unit's edit data differs from the saved unit, so rebuilding it would lose the user's edits
+    fn has_pending_edits(&self, i: usize, j: usize) -> bool {
+        let edit_data = &self.working_dir[i].unit_edit_data[j];
+        let edited: Unit = edit_data.clone().into();
+        let saved: Unit = UnitEditData::from((&self.working_dir[i].units[j], edit_data.prev_filename.clone())).into();
+        edit_data.filename != edit_data.prev_filename || ron::to_string(&edited).ok() != ron::to_string(&saved).ok()
+    }
+
+    /// Sets the rank of a crusade unit from its experience, using the rank thresholds of the folder
+    fn rank_unit(&self, unit: &mut Unit) {
+        if unit.crusade_unit {
+            unit.crusade_data.rank = self.get_settings().rank_thresholds.rank(unit.crusade_data.exp, unit.is_character());
+        }
+    }
+
+    /// Reranks every crusade unit, rewriting the ones whose rank changed
+    pub fn rerank_units(&mut self) {
+        let thresholds = self.get_settings().rank_thresholds.clone();
+        for i in 0..self.working_dir.len() {
+            for j in 0..self.working_dir[i].units.len() {
+                let unit = &mut self.working_dir[i].units[j];
+                if !unit.crusade_unit {
+                    continue;
+                }
+                let rank = thresholds.rank(unit.crusade_data.exp, unit.is_character());
+                if rank != unit.crusade_data.rank {
+                    unit.crusade_data.rank = rank;
+                    self.working_dir[i].unit_edit_data[j].crusade_data.rank = rank;
+                    self.write_unit(i, j);
+                }
+            }
+        }
+    }
+
+    /// Asks for a battle honour for every rank a unit gained since `previous_rank`
+    fn queue_rank_ups(&mut self, (i, j): (usize, usize), previous_rank: CrusadeRank) {
+        let unit = &self.working_dir[i].units[j];
+        if !unit.crusade_unit {
+            return;
+        }
+        let weapons: Vec<WeaponReference> = unit.ranged_weapons.iter().map(|(weapon, _)| WeaponReference::new(weapon.name.clone(), true, weapon.uid))
+            .chain(unit.melee_weapons.iter().map(|(weapon, _)| WeaponReference::new(weapon.name.clone(), false, weapon.uid)))
+            .collect();
+        let rank_ups: Vec<RankUp> = CrusadeRank::ALL.iter()
+            .filter(|rank| rank.level() > previous_rank.level() && rank.level() <= unit.crusade_data.rank.level())
+            .map(|rank| RankUp {
+                unit: self.unit_reference(i, j),
+                name: unit.name.clone(),
+                rank: *rank,
+                honour: CrusadeUpgrade::BattleTrait(Ability::default()),
+                weapons: weapons.clone(),
+            })
+            .collect();
+        self.rank_ups.extend(rank_ups);
+    }
+
+    /// The battle honours a unit has and how many it may have at the rank it is being promoted to
+    fn honour_count(&self, rank_up: &RankUp) -> (u32, u32) {
+        let honours = self.find_unit(&rank_up.unit).map(|(i, j)| self.working_dir[i].units[j].crusade_data.honours()).unwrap_or(0);
+        (honours, self.get_settings().rank_thresholds.honour_limit(rank_up.rank))
+    }
+
+    /// Adds an upgrade chosen after a battle to a unit, with the roll it came from
+    fn add_upgrade(&mut self, reference: &str, upgrade: CrusadeUpgrade, roll: Option<RollRecord>) {
+        let Some((i, j)) = self.find_unit(reference) else {return};
+        let mut unit = self.working_dir[i].units[j].clone();
+        unit.crusade_data.upgrades.push(upgrade);
+        unit.crusade_data.rolls.extend(roll);
+        let filename = self.working_dir[i].unit_edit_data[j].prev_filename.clone();
+        let mut unit: Unit = UnitEditData::from((&unit, filename.clone())).into();
+        self.rank_unit(&mut unit);
+        unit.crusade_data.record_history(&self.working_dir[i].units[j].crusade_data, &chrono::Local::now().format("%Y-%m-%d").to_string());
+        self.store_crusade_change((i, j), unit);
+        self.resolve_bases();
+    }
+
+    /// Stores a unit whose crusade data changed outside its tab and writes it.
+    /// A tab with unsaved edits keeps them and only takes the new crusade data, so saving it doesn't undo the change
+    fn store_crusade_change(&mut self, (i, j): (usize, usize), unit: Unit) {
+        if self.has_pending_edits(i, j) {
+            self.working_dir[i].unit_edit_data[j].crusade_data = unit.crusade_data.clone();
+        } else {
+            let filename = self.working_dir[i].unit_edit_data[j].prev_filename.clone();
+            self.working_dir[i].unit_edit_data[j] = UnitEditData::from((&unit, filename));
+        }
+        self.working_dir[i].units[j] = unit;
+        self.write_unit(i, j);
+    }
+
+    /// "folder/filename", how a variant refers to its base unit
+    pub fn unit_reference(&self, folder: usize, index: usize) -> String {
+        format!("{}/{}", self.working_dir[folder].name, self.working_dir[folder].unit_edit_data[index].prev_filename)
+    }
+
+    pub fn find_unit(&self, reference: &str) -> Option<(usize, usize)> {
+        let (folder, filename) = reference.split_once('/')?;
+        let i = self.working_dir.iter().position(|dir| dir.name == folder)?;
+        let j = self.working_dir[i].unit_edit_data.iter().position(|edit_data| edit_data.prev_filename == filename)?;
+        Some((i, j))
+    }
+
+    /// Points every variant, battle log entry, requisition and pending rank up of a renamed unit at its new reference
+    fn rename_references(&mut self, old: &str, new: &str) {
+        for rank_up in self.rank_ups.iter_mut().filter(|rank_up| rank_up.unit == old) {
+            rank_up.unit = new.to_string();
+        }
+
+        let mut logged = false;
+        for force in [&mut self.crusade_force, &mut self.crusade_force_edit] {
+            for battle_unit in force.log.iter_mut().flat_map(|record| record.units.iter_mut()) {
+                if battle_unit.unit == old {
+                    battle_unit.unit = new.to_string();
+                    logged = true;
+                }
+            }
+            for record in force.requisitions.iter_mut() {
+                if record.unit == old {
+                    record.unit = new.to_string();
+                    logged = true;
+                }
+            }
+        }
+        if logged {
+            self.write_crusade_force();
+        }
+
+        for i in 0..self.working_dir.len() {
+            for j in 0..self.working_dir[i].units.len() {
+                if self.working_dir[i].units[j].base.as_ref().is_some_and(|base| base.unit == old) {
+                    let folder = &mut self.working_dir[i];
+                    for base in [&mut folder.units[j].base, &mut folder.unit_edit_data[j].base].into_iter().flatten() {
+                        base.unit = new.to_string();
+                    }
+                    self.write_unit(i, j);
+                }
+            }
+        }
+    }
+
+    /// Rebuilds every variant from its base unit and overrides, rewriting the ones that changed.
+    /// Repeats so variants of variants pick up changes, stopping early on a cycle
+    fn resolve_bases(&mut self) {
+        for _ in 0..8 {
+            let mut changed = false;
+            for i in 0..self.working_dir.len() {
+                for j in 0..self.working_dir[i].units.len() {
+                    let unit = &self.working_dir[i].units[j];
+                    let Some(base) = &unit.base else {continue};
+                    let Some((k, l)) = self.find_unit(&base.unit) else {continue};
+                    if (k, l) == (i, j) {
+                        continue;
+                    }
+                    let filename = self.working_dir[i].unit_edit_data[j].prev_filename.clone();
+                    let resolved = self.resolve_variant(unit, &self.working_dir[k].units[l]);
+                    if ron::to_string(&resolved).ok() != ron::to_string(unit).ok() {
+                        // a tab with unsaved edits is left alone so the edits aren't lost
+                        if !self.has_pending_edits(i, j) {
+                            self.working_dir[i].unit_edit_data[j] = UnitEditData::from((&resolved, filename));
+                        }
+                        self.working_dir[i].units[j] = resolved;
+                        self.write_unit(i, j);
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    /// A variant rebuilt from its base unit and overrides, ranked with the folder's thresholds
+    fn resolve_variant(&self, unit: &Unit, base_unit: &Unit) -> Unit {
+        let mut resolved = unit.resolve_variant(base_unit);
+        self.rank_unit(&mut resolved);
+        resolved
+    }
+
     fn copy_unit(&mut self, unit: &Unit, folder_index: usize, filename: String) {
         self.working_dir[folder_index].units.push(unit.clone());
         self.working_dir[folder_index].unit_edit_data.push(UnitEditData::from((unit, filename)));
     }
 
-    fn create_folder(&mut self) {
-        let path = format!("{}/{}", self.folder_path, self.new_folder.1);
-        let _ = create_dir(path.clone());
+    fn free_filename(&self, folder: usize, start: &str) -> String {
+        let taken = |name: &str| self.working_dir[folder].unit_edit_data.iter().any(|edit_data| edit_data.filename == name);
+        if !taken(start) {
+            return start.to_string();
+        }
+        let mut k = 1;
+        while taken(&format!("{}_{}", start, k)) {
+            k += 1;
+        }
+        format!("{}_{}", start, k)
+    }
+
+    fn write_unit(&self, folder: usize, index: usize) {
+        let _ = self.try_write_unit(folder, index);
+    }
+
+    fn try_write_unit(&self, folder: usize, index: usize) -> std::io::Result<()> {
+        let config = PrettyConfig::new()
+            .depth_limit(2)
+            .separate_tuple_members(true)
+            .enumerate_arrays(true);
+
+        let s = to_string_pretty(&self.working_dir[folder].units[index], config).expect("Failed to serialize");
+        fs::write(format!("{}/{}.ron", self.working_dir[folder].path, self.working_dir[folder].unit_edit_data[index].prev_filename), s)
+    }
+
+    /// Adds imported units to a folder and writes them, reporting each one imported or failed.
+    /// Filenames come from the imported data, so they are sanitised first
+    fn add_units(&mut self, folder: usize, units: Vec<(String, Unit)>) -> Vec<String> {
+        let mut imported = Vec::new();
+        for (filename, mut unit) in units {
+            let filename = self.free_filename(folder, &sanitise_filename(&filename));
+            if unit.assign_weapon_uids() {
+                unit = UnitEditData::from((&unit, filename.clone())).into();
+            }
+            self.rank_unit(&mut unit);
+            self.working_dir[folder].unit_edit_data.push(UnitEditData::from((&unit, filename.clone())));
+            self.working_dir[folder].units.push(unit);
+            match self.try_write_unit(folder, self.working_dir[folder].units.len() - 1) {
+                Ok(()) => imported.push(format!("Imported {}", filename)),
+                Err(e) => {
+                    self.working_dir[folder].units.pop();
+                    self.working_dir[folder].unit_edit_data.pop();
+                    imported.push(format!("Failed to write {}: {}", filename, e));
+                }
+            }
+        }
+        imported
+    }
+
+    fn import_units(&mut self, folder: usize, path: PathBuf) {
+        match read_units(&path) {
+            Ok(units) => {
+                let imported = self.add_units(folder, units);
+                self.report = Some(("Import Complete".to_string(), imported));
+            },
+            Err(e) => {
+                self.report = Some(("Import Failed".to_string(), vec![e.to_string()]));
+            }
+        }
+    }
+
+    fn free_folder_name(&self, start: &str) -> String {
+        let mut name = start.to_string();
+        let mut k = 1;
+        while self.working_dir.iter().any(|folder| folder.name == name) {
+            name = format!("{}_{}", start, k);
+            k += 1;
+        }
+        name
+    }
+
+    fn import_catalogue(&mut self, path: PathBuf) {
+        match read_catalogue(&path) {
+            Ok((name, units, warnings)) => {
+                if let Err(e) = self.create_folder(self.free_folder_name(&sanitise_filename(&name))) {
+                    self.report = Some(("Import Failed".to_string(), vec![format!("Could not create a folder for {}: {}", name, e)]));
+                    return;
+                }
+                let mut lines = self.add_units(self.working_dir.len() - 1, units);
+                lines.extend(warnings);
+                self.report = Some((format!("Imported {}", name), lines));
+            },
+            Err(e) => {
+                self.report = Some(("Import Failed".to_string(), vec![e.to_string()]));
+            }
+        }
+    }
+
+    /// Creates a subfolder of crusade copies of every unit in a roster that matches a unit already in the working directory
+    fn import_roster(&mut self, path: PathBuf) {
+        match read_roster(&path) {
+            Ok((name, selections)) => {
+                let mut units = Vec::new();
+                let mut unmatched = Vec::new();
+                for selection in selections {
+                    let found = self.working_dir.iter()
+                        .flat_map(|folder| folder.units.iter())
+                        .find(|unit| !unit.crusade_unit && unit.name.eq_ignore_ascii_case(&selection));
+                    match found {
+                        Some(unit) => {
+                            let mut unit = unit.clone();
+                            unit.crusade_unit = true;
+                            unit.crusade_data = CrusadeUnitData::default();
+                            unit.crusade_weapons = (unit.ranged_weapons.clone(), unit.melee_weapons.clone());
+                            // the copy is already resolved, keeping the base would overwrite its crusade edits with the template
+                            unit.base = None;
+                            units.push((unit.name.clone(), unit));
+                        },
+                        None => unmatched.push(format!("No unit matches '{}'", selection))
+                    }
+                }
+
+                if let Err(e) = self.create_folder(self.free_folder_name(&sanitise_filename(&name))) {
+                    self.report = Some(("Import Failed".to_string(), vec![format!("Could not create a folder for {}: {}", name, e)]));
+                    return;
+                }
+                let mut lines = self.add_units(self.working_dir.len() - 1, units);
+                lines.extend(unmatched);
+                self.report = Some((format!("Imported {}", name), lines));
+            },
+            Err(e) => {
+                self.report = Some(("Import Failed".to_string(), vec![e.to_string()]));
+            }
+        }
+    }
+
+    fn create_folder(&mut self, name: String) -> std::io::Result<()> {
+        let path = format!("{}/{}", self.folder_path, name);
+        create_dir(path.clone())?;
         self.working_dir.push(DatasheetFolder {
-            name: self.new_folder.1.clone(),
+            name,
             units: Vec::new(),
             unit_edit_data: Vec::new(),
             path
         });
+        Ok(())
+    }
+}
+
+
+/// Reads one of the folder's files, adding the error to `errors` if it can't be opened or parsed
+fn read_folder_file<T: DeserializeOwned>(path: &Path, errors: &mut Vec<String>) -> Option<T> {
+    let result = File::open(path).map_err(|e| e.to_string())
+        .and_then(|f| from_reader(f).map_err(|e| e.to_string()));
+    match result {
+        Ok(value) => Some(value),
+        Err(e) => {
+            errors.push(format!("{}: {}", path.file_name().unwrap_or_default().to_string_lossy(), e));
+            None
+        }
     }
 }
 
@@ -306,14 +1065,36 @@ impl Default for DatasheetApp {
 
             export_templates: load_export_templates(),
             export_unit: None,
+            print_folder: None,
+            export_folder: None,
+            csv_import: None,
+            battle_wizard: None,
+            requisition_wizard: None,
+            rank_ups: Vec::new(),
+            pending_scars: Vec::new(),
             last_export_type: ExportType::PDF,
             last_export_dir: PathBuf::new(),
 
             show_confirmation_dialog: false,
             allowed_to_close: false,
+            report: None,
             settings_menu_open: false,
             settings: DatasheetAppSettings::default(),
-            folder_settings: None
+            folder_settings: None,
+            weapon_library: WeaponLibrary::default(),
+            weapon_library_edit: Vec::new(),
+            glossary: Glossary::default(),
+            glossary_edit: Glossary::default(),
+            roll_tables: RollTables::default(),
+            roll_tables_edit: RollTables::default(),
+            agendas: Agendas::default(),
+            agendas_edit: Agendas::default(),
+            detachments: Detachments::default(),
+            detachments_edit: Detachments::default(),
+            stratagems: Stratagems::default(),
+            stratagems_edit: Stratagems::default(),
+            crusade_force: CrusadeForce::default(),
+            crusade_force_edit: CrusadeForce::default(),
         }
     }
 }
@@ -350,6 +1131,9 @@ impl App for DatasheetApp {
 
         
         let mut copy_data = None;
+        let mut import_folder = None;
+        let mut import_catalogue = false;
+        let mut import_roster = false;
         egui::SidePanel::left("LeftPanel").min_width(150.0).resizable(false).show(ctx, |ui| {
 
             if ui.button(RichText::new(&self.working_dir_name).size(15.0)).clicked() {
@@ -373,6 +1157,14 @@ impl App for DatasheetApp {
                         self.new_unit.2 = "".to_string();
                     }
 
+                    if ui.button("Import Catalogue").clicked() {
+                        import_catalogue = true;
+                    }
+
+                    if ui.button("Import Roster").clicked() {
+                        import_roster = true;
+                    }
+
                     ui.reset_style();
                 });
             }
@@ -388,6 +1180,32 @@ impl App for DatasheetApp {
                         }
                     }
                 }
+                if self.working_dir_name != "No Folder Open".to_string() {
+                    if ui.selectable_label(false, "Weapon Library").clicked() {
+                        self.open_file(OpenFile::WeaponLibrary);
+                    }
+                    if ui.selectable_label(false, "Glossary").clicked() {
+                        self.open_file(OpenFile::Glossary);
+                    }
+                    if ui.selectable_label(false, "Roll Tables").clicked() {
+                        self.open_file(OpenFile::RollTables);
+                    }
+                    if ui.selectable_label(false, "Agendas").clicked() {
+                        self.open_file(OpenFile::Agendas);
+                    }
+                    if ui.selectable_label(false, "Detachments").clicked() {
+                        self.open_file(OpenFile::Detachments);
+                    }
+                    if ui.selectable_label(false, "Stratagems").clicked() {
+                        self.open_file(OpenFile::Stratagems);
+                    }
+                    if ui.selectable_label(false, "Problems").clicked() {
+                        self.open_file(OpenFile::Problems);
+                    }
+                    if ui.selectable_label(false, "Order of Battle").clicked() {
+                        self.open_file(OpenFile::OrderOfBattle);
+                    }
+                }
 
                 // Draw units sidebar
                 for (i, folder) in self.working_dir.iter_mut().enumerate() {
@@ -457,6 +1275,18 @@ impl App for DatasheetApp {
                             self.new_unit.1 = i;
                             self.new_unit.2 = "".to_string();
                         }
+                        if ui.selectable_label(false, "Print Subfolder").clicked() {
+                            self.print_folder = Some(i);
+                        }
+                        if ui.selectable_label(false, "Export Subfolder").clicked() {
+                            self.export_folder = Some((i, ExportType::JSON));
+                        }
+                        if ui.selectable_label(false, "Import Units").clicked() {
+                            import_folder = Some(i);
+                        }
+                        if ui.selectable_label(false, "Import CSV").clicked() {
+                            self.csv_import = Some(CsvImport::new(i));
+                        }
                         if ui.selectable_label(false, "Delete Subfolder").clicked() {
                             self.deleting = Some(((i, 0), true));
                         }
@@ -472,6 +1302,24 @@ impl App for DatasheetApp {
             self.copy_unit(&unit, index, filename);
         }
 
+        if import_catalogue {
+            if let Some(file) = rfd::FileDialog::new().add_filter("BattleScribe Catalogue", &["cat", "catz"]).pick_file() {
+                self.import_catalogue(file);
+            }
+        }
+
+        if import_roster {
+            if let Some(file) = rfd::FileDialog::new().add_filter("Roster", &["ros", "rosz"]).pick_file() {
+                self.import_roster(file);
+            }
+        }
+
+        if let Some(folder) = import_folder {
+            if let Some(file) = rfd::FileDialog::new().add_filter("Units", &["json", "yaml", "yml", "ron"]).set_directory(self.last_export_dir.clone()).pick_file() {
+                self.import_units(folder, file);
+            }
+        }
+
             
         egui::TopBottomPanel::top("TopPanel").min_height(25.0).show(ctx, |ui| {
             // DOES NOT WORK WITHOUT ABILITY TO HORIZONTAL SCROLL
@@ -498,6 +1346,46 @@ impl App for DatasheetApp {
                                 if ui.selectable_label(false, "Settings").clicked() {
                                     self.selected_file = i;
                                 };
+                            },
+                            OpenFile::WeaponLibrary => {
+                                if ui.selectable_label(false, "Weapon Library").clicked() {
+                                    self.selected_file = i;
+                                };
+                            },
+                            OpenFile::Glossary => {
+                                if ui.selectable_label(false, "Glossary").clicked() {
+                                    self.selected_file = i;
+                                };
+                            },
+                            OpenFile::RollTables => {
+                                if ui.selectable_label(false, "Roll Tables").clicked() {
+                                    self.selected_file = i;
+                                };
+                            },
+                            OpenFile::Agendas => {
+                                if ui.selectable_label(false, "Agendas").clicked() {
+                                    self.selected_file = i;
+                                };
+                            },
+                            OpenFile::Detachments => {
+                                if ui.selectable_label(false, "Detachments").clicked() {
+                                    self.selected_file = i;
+                                };
+                            },
+                            OpenFile::Stratagems => {
+                                if ui.selectable_label(false, "Stratagems").clicked() {
+                                    self.selected_file = i;
+                                };
+                            },
+                            OpenFile::Problems => {
+                                if ui.selectable_label(false, "Problems").clicked() {
+                                    self.selected_file = i;
+                                };
+                            },
+                            OpenFile::OrderOfBattle => {
+                                if ui.selectable_label(false, "Order of Battle").clicked() {
+                                    self.selected_file = i;
+                                };
                             }
                         }
                         
@@ -602,7 +1490,9 @@ impl App for DatasheetApp {
             pop_up_menus::folder_creation_window(ctx, &mut result, &mut self.new_folder.1);
             if let Some(create) = result {
                 if create {
-                    self.create_folder();
+                    if let Err(e) = self.create_folder(sanitise_filename(&self.new_folder.1)) {
+                        self.report = Some(("Folder Not Created".to_string(), vec![e.to_string()]));
+                    }
                 }
                 self.new_folder.0 = false;
             }
@@ -641,21 +1531,152 @@ impl App for DatasheetApp {
             }
         }
 
-        if let Some(((i, j), export_type)) = &mut self.export_unit {
+        if let Some(((i, j), mut export_type)) = self.export_unit {
             let mut result = None;
-            pop_up_menus::export_window(&ctx, &mut result, export_type);
+            pop_up_menus::export_window(&ctx, &mut result, &mut export_type, self.get_settings_mut());
+            self.export_unit = Some(((i, j), export_type));
             if let Some(export) = result {
                 if export {
-                    if let Some(file) = rfd::FileDialog::new().add_filter(export_type.to_string(), export_type.get_extensions()).set_directory(self.last_export_dir.clone()).set_file_name(&self.working_dir[*i].units[*j].name).save_file() {
+                    if let Some(file) = rfd::FileDialog::new().add_filter(export_type.to_string(), export_type.get_extensions()).set_directory(self.last_export_dir.clone()).set_file_name(&self.working_dir[i].units[j].name).save_file() {
                         self.last_export_dir = file.parent().unwrap().to_path_buf();
-                        export_unit(&self.working_dir[*i].units[*j], *export_type,file, &self.export_templates);
+                        if let Err(e) = export_unit(&self.working_dir[i].units[j], export_type, file, &self.export_templates, self.get_settings(), &self.glossary) {
+                            self.report = Some(("Export Failed".to_string(), vec![e.to_string()]));
+                        }
                     }
                 }
-                self.last_export_type = *export_type;
+                // print profiles and export options belong to the folder when it has its own settings
+                self.save_folder_settings();
+                self.last_export_type = export_type;
                 self.export_unit = None;
             }
         }
 
+        if let Some((i, mut export_type)) = self.export_folder {
+            let mut result = None;
+            let folder_name = self.working_dir[i].name.clone();
+            pop_up_menus::export_folder_window(&ctx, &mut result, &folder_name, &mut export_type);
+            self.export_folder = Some((i, export_type));
+            if let Some(export) = result {
+                if export {
+                    if let Some(file) = rfd::FileDialog::new().add_filter(export_type.to_string(), export_type.get_extensions()).set_directory(self.last_export_dir.clone()).set_file_name(&folder_name).save_file() {
+                        self.last_export_dir = file.parent().unwrap().to_path_buf();
+                        if export_type == ExportType::TTS {
+                            let units: Vec<&Unit> = self.working_dir[i].units.iter().collect();
+                            if let Err(e) = export_to_tts(&units, &folder_name, file) {
+                                self.report = Some(("Export Failed".to_string(), vec![e.to_string()]));
+                            }
+                        } else {
+                            let folder = FolderFile {
+                                name: folder_name,
+                                units: self.working_dir[i].unit_edit_data.iter().map(|edit_data| edit_data.prev_filename.clone()).zip(self.working_dir[i].units.iter().cloned()).collect()
+                            };
+                            if let Err(e) = export_folder(&folder, export_type == ExportType::YAML, file) {
+                                self.report = Some(("Export Failed".to_string(), vec![e.to_string()]));
+                            }
+                        }
+                    }
+                }
+                self.export_folder = None;
+            }
+        }
+
+        if let Some(import) = &mut self.csv_import {
+            let mut result = None;
+            let mut error = None;
+            pop_up_menus::csv_import_window(&ctx, &mut result, import, &mut error);
+            if let Some(e) = error {
+                self.report = Some(("Could not read CSV".to_string(), vec![e]));
+            }
+            if let Some(create) = result {
+                if create {
+                    let (units, errors) = import.build_units();
+                    let folder = import.folder;
+                    let units = units.into_iter().map(|unit| (unit.name.clone(), unit)).collect();
+                    let mut lines = self.add_units(folder, units);
+                    lines.extend(errors);
+                    self.report = Some(("CSV Import".to_string(), lines));
+                }
+                self.csv_import = None;
+            }
+        }
+
+        if let Some(wizard) = &mut self.battle_wizard {
+            let mut result = None;
+            pop_up_menus::record_battle_window(&ctx, &mut result, wizard);
+            if let Some(record) = result {
+                if record {
+                    let record = wizard.finish();
+                    self.record_battle(record);
+                }
+                self.battle_wizard = None;
+            }
+        }
+
+        let problem = self.requisition_wizard.as_ref().and_then(|wizard| wizard.problem(&self.crusade_force, &self.crusade_units(), &self.weapon_library));
+        if let Some(wizard) = &mut self.requisition_wizard {
+            let mut result = None;
+            pop_up_menus::requisition_window(&ctx, &mut result, wizard, &self.weapon_library, self.crusade_force.requisition_points, problem);
+            if let Some(buy) = result {
+                let wizard = self.requisition_wizard.take().unwrap();
+                if buy {
+                    self.buy_requisition(wizard);
+                }
+            }
+        }
+
+        if !self.rank_ups.is_empty() {
+            let mut result = None;
+            let (honours, limit) = self.honour_count(&self.rank_ups[0]);
+            pop_up_menus::rank_up_window(&ctx, &mut result, &mut self.rank_ups[0], honours, limit);
+            if let Some(add) = result {
+                let rank_up = self.rank_ups.remove(0);
+                if add {
+                    self.add_upgrade(&rank_up.unit, rank_up.honour, None);
+                }
+            }
+        }
+
+        if !self.pending_scars.is_empty() {
+            let mut result = None;
+            let tables: Vec<&RollTable> = self.roll_tables.tables.iter().filter(|table| table.kind == RollTableKind::BattleScar).collect();
+            pop_up_menus::battle_scar_window(&ctx, &mut result, &mut self.pending_scars[0], &tables);
+            if let Some(add) = result {
+                let pending = self.pending_scars.remove(0);
+                if add {
+                    self.add_upgrade(&pending.unit, CrusadeUpgrade::BattleScar(pending.scar, pending.effects), pending.roll);
+                }
+            }
+        }
+
+        if let Some((title, lines)) = &self.report {
+            let mut result = None;
+            pop_up_menus::report_window(&ctx, &mut result, title, lines);
+            if result.is_some() {
+                self.report = None;
+            }
+        }
+
+        if let Some(i) = self.print_folder {
+            let mut result = None;
+            let folder_name = self.working_dir[i].name.clone();
+            pop_up_menus::print_folder_window(&ctx, &mut result, &folder_name, self.get_settings_mut());
+            if let Some(print) = result {
+                if print && self.working_dir[i].units.is_empty() {
+                    self.report = Some(("Print Failed".to_string(), vec![format!("{} has no datasheets to print", folder_name)]));
+                } else if print {
+                    if let Some(file) = rfd::FileDialog::new().add_filter("PDF", &["pdf"]).set_directory(self.last_export_dir.clone()).set_file_name(&folder_name).save_file() {
+                        self.last_export_dir = file.parent().unwrap().to_path_buf();
+                        let units: Vec<&Unit> = self.working_dir[i].units.iter().collect();
+                        if let Err(e) = print_units(&units, file, &self.export_templates, self.get_settings(), &self.glossary) {
+                            self.report = Some(("Print Failed".to_string(), vec![e.to_string()]));
+                        }
+                    }
+                }
+                self.save_folder_settings();
+                self.print_folder = None;
+            }
+        }
+
 
         self.settings.dark_mode = ctx.options(|opt| opt.theme_preference == ThemePreference::Dark);
     }
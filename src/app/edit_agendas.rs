@@ -0,0 +1,78 @@
+use egui::{Context, RichText};
+use egui_extras::{Column, TableBuilder};
+
+use crate::{data::{Agenda, Unit}, export::export_agenda_tracker, helper_funcs::{select_drag_value_with_range_on_tab, select_text_on_tab}};
+
+use super::{datasheet_app::DatasheetAppMode, DatasheetApp};
+
+
+
+pub fn agendas_panel(app: &mut DatasheetApp, ctx: &Context) {
+    egui::CentralPanel::default().show(ctx, |ui| {
+        egui::ScrollArea::both().show(ui, |ui| {
+            ui.heading(RichText::new("Agendas").size(30.0));
+            ui.label("Agendas chosen when recording a battle. Each tally a unit makes earns it experience, up to the agenda's limit.");
+
+            ui.add_enabled_ui(app.mode == DatasheetAppMode::Edit, |ui| {
+                TableBuilder::new(ui)
+                    .id_salt(80)
+                    .striped(true)
+                    .resizable(false)
+                    .column(Column::auto().at_least(200.0))
+                    .column(Column::auto().at_least(80.0))
+                    .column(Column::auto().at_least(80.0))
+                    .column(Column::auto().at_least(500.0))
+                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                    .header(20.0, |mut header| {
+                        for col_header in ["Name", "XP per Tally", "Max XP", "Description"] {
+                            header.col(|ui| {
+                                ui.strong(RichText::new(col_header).size(15.0));
+                            });
+                        }
+                    })
+                    .body(|mut body| {
+                        let mut to_remove = Vec::new();
+                        for (i, agenda) in app.agendas_edit.agendas.iter_mut().enumerate() {
+                            body.row(60.0, |mut row| {
+                                row.col(|ui| {
+                                    ui.horizontal(|ui| {
+                                        if ui.button("X").on_hover_text("Delete").clicked() {
+                                            to_remove.push(i);
+                                        }
+                                        select_text_on_tab(agenda.name.len(), egui::TextEdit::singleline(&mut agenda.name), ui);
+                                    });
+                                });
+                                row.col(|ui| {
+                                    select_drag_value_with_range_on_tab(&mut agenda.exp_per_tally, 0..=10, ui);
+                                });
+                                row.col(|ui| {
+                                    select_drag_value_with_range_on_tab(&mut agenda.max_exp, 0..=99, ui).on_hover_text("0 for no limit");
+                                });
+                                row.col(|ui| {
+                                    select_text_on_tab(agenda.description.len(), egui::TextEdit::multiline(&mut agenda.description).desired_rows(2).desired_width(500.0), ui);
+                                });
+                            });
+                        }
+                        for (j, i) in to_remove.iter().enumerate() {
+                            app.agendas_edit.agendas.remove(i - j);
+                        }
+                    });
+
+                if ui.button("Add new agenda").clicked() {
+                    app.agendas_edit.agendas.push(Agenda::default());
+                }
+            });
+
+            ui.separator();
+            if ui.button("Export Tracker").on_hover_text("Export the saved agendas as a printable tally sheet for the crusade units").clicked() {
+                if let Some(file) = rfd::FileDialog::new().add_filter("HTML", &["html"]).add_filter("PDF", &["pdf"]).set_directory(app.last_export_dir.clone()).set_file_name("agenda_tracker").save_file() {
+                    let units: Vec<&Unit> = app.crusade_units().into_iter().map(|(_, unit)| unit).collect();
+                    let result = export_agenda_tracker(&app.agendas, &units, file);
+                    if let Err(e) = result {
+                        app.report = Some(("Export Failed".to_string(), vec![e.to_string()]));
+                    }
+                }
+            }
+        });
+    });
+}
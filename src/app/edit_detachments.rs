@@ -0,0 +1,103 @@
+use egui::{CollapsingHeader, Context, RichText};
+use egui_extras::{Column, TableBuilder};
+
+use crate::{data::{Detachment, Enhancement}, helper_funcs::{select_drag_value_with_range_on_tab, select_text_on_tab}};
+
+use super::{datasheet_app::DatasheetAppMode, DatasheetApp};
+
+
+
+pub fn detachments_panel(app: &mut DatasheetApp, ctx: &Context) {
+    egui::CentralPanel::default().show(ctx, |ui| {
+        egui::ScrollArea::both().show(ui, |ui| {
+            ui.heading(RichText::new("Detachments").size(30.0));
+            ui.label("Detachment rules and the enhancements characters can take. Enhancements list the keywords a unit needs, as well as CHARACTER.");
+
+            ui.add_enabled_ui(app.mode == DatasheetAppMode::Edit, |ui| {
+                let mut to_remove = None;
+                for (i, detachment) in app.detachments_edit.detachments.iter_mut().enumerate() {
+                    let title = if detachment.name.is_empty() {"Unnamed Detachment"} else {&detachment.name};
+                    CollapsingHeader::new(title)
+                        .id_salt(("detachment", i))
+                        .default_open(false)
+                        .show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                if ui.button("X").on_hover_text("Delete detachment").clicked() {
+                                    to_remove = Some(i);
+                                }
+                                ui.label("Name:");
+                                select_text_on_tab(detachment.name.len(), egui::TextEdit::singleline(&mut detachment.name), ui);
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Detachment Rule:");
+                                select_text_on_tab(detachment.rule.name.len(), egui::TextEdit::singleline(&mut detachment.rule.name), ui);
+                            });
+                            select_text_on_tab(detachment.rule.description.len(), egui::TextEdit::multiline(&mut detachment.rule.description).desired_rows(2).desired_width(700.0), ui);
+
+                            ui.strong("Enhancements");
+                            TableBuilder::new(ui)
+                                .id_salt(90 + i * 2)
+                                .striped(true)
+                                .resizable(false)
+                                .column(Column::auto().at_least(200.0))
+                                .column(Column::auto().at_least(60.0))
+                                .column(Column::auto().at_least(200.0))
+                                .column(Column::auto().at_least(400.0))
+                                .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                                .header(20.0, |mut header| {
+                                    for col_header in ["Name", "Points", "Keywords", "Description"] {
+                                        header.col(|ui| {
+                                            ui.strong(RichText::new(col_header).size(15.0));
+                                        });
+                                    }
+                                })
+                                .body(|mut body| {
+                                    let mut to_remove = Vec::new();
+                                    for (j, enhancement) in detachment.enhancements.iter_mut().enumerate() {
+                                        body.row(60.0, |mut row| {
+                                            row.col(|ui| {
+                                                ui.horizontal(|ui| {
+                                                    if ui.button("X").on_hover_text("Delete").clicked() {
+                                                        to_remove.push(j);
+                                                    }
+                                                    select_text_on_tab(enhancement.name.len(), egui::TextEdit::singleline(&mut enhancement.name), ui);
+                                                });
+                                            });
+                                            row.col(|ui| {
+                                                select_drag_value_with_range_on_tab(&mut enhancement.points, 0..=500, ui);
+                                            });
+                                            row.col(|ui| {
+                                                // edited as a comma separated list, empty keywords are removed on save
+                                                let mut keywords = enhancement.keywords.join(",");
+                                                if select_text_on_tab(keywords.len(), egui::TextEdit::singleline(&mut keywords), ui).changed() {
+                                                    enhancement.keywords = keywords.split(',').map(|keyword| keyword.to_uppercase()).collect();
+                                                }
+                                            });
+                                            row.col(|ui| {
+                                                select_text_on_tab(enhancement.description.len(), egui::TextEdit::multiline(&mut enhancement.description).desired_rows(2).desired_width(400.0), ui);
+                                            });
+                                        });
+                                    }
+                                    for (k, j) in to_remove.iter().enumerate() {
+                                        detachment.enhancements.remove(j - k);
+                                    }
+                                });
+                            if ui.button("Add new enhancement").clicked() {
+                                detachment.enhancements.push(Enhancement::default());
+                            }
+
+                            let stratagems = app.stratagems_edit.stratagems.iter().filter(|stratagem| !detachment.name.is_empty() && stratagem.detachment == detachment.name).count();
+                            ui.label(format!("{} stratagems, edited in the Stratagems tab", stratagems));
+                        });
+                }
+                if let Some(i) = to_remove {
+                    app.detachments_edit.detachments.remove(i);
+                }
+
+                if ui.button("Add new detachment").clicked() {
+                    app.detachments_edit.detachments.push(Detachment::default());
+                }
+            });
+        });
+    });
+}
@@ -0,0 +1,69 @@
+use egui::{Context, RichText};
+use egui_extras::{Column, TableBuilder};
+
+use crate::{data::{GlossaryEntry, Unit}, helper_funcs::select_text_on_tab};
+
+use super::{datasheet_app::DatasheetAppMode, DatasheetApp};
+
+
+
+pub fn glossary_panel(app: &mut DatasheetApp, ctx: &Context) {
+    egui::CentralPanel::default().show(ctx, |ui| {
+        egui::ScrollArea::both().show(ui, |ui| {
+            ui.heading(RichText::new("Glossary").size(30.0));
+            ui.label("Rules text shown when hovering abilities in read mode, and optionally printed as a rules reference.");
+
+            ui.add_enabled_ui(app.mode == DatasheetAppMode::Edit, |ui| {
+                TableBuilder::new(ui)
+                    .id_salt(30)
+                    .striped(true)
+                    .resizable(false)
+                    .column(Column::auto().at_least(200.0))
+                    .column(Column::auto().at_least(80.0))
+                    .column(Column::auto().at_least(500.0))
+                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                    .header(20.0, |mut header| {
+                        for col_header in ["Name", "Type", "Description"] {
+                            header.col(|ui| {
+                                ui.strong(RichText::new(col_header).size(15.0));
+                            });
+                        }
+                    })
+                    .body(|mut body| {
+                        let mut to_remove = Vec::new();
+                        for (i, entry) in app.glossary_edit.entries.iter_mut().enumerate() {
+                            body.row(80.0, |mut row| {
+                                row.col(|ui| {
+                                    ui.horizontal(|ui| {
+                                        if ui.button("X").on_hover_text("Delete").clicked() {
+                                            to_remove.push(i);
+                                        }
+                                        select_text_on_tab(entry.name.len(), egui::TextEdit::singleline(&mut entry.name), ui);
+                                    });
+                                });
+                                row.col(|ui| {
+                                    entry.kind.combo_box(ui, i * 7 + 30000000);
+                                });
+                                row.col(|ui| {
+                                    select_text_on_tab(entry.description.len(), egui::TextEdit::multiline(&mut entry.description).desired_width(500.0), ui);
+                                });
+                            });
+                        }
+                        for (j, i) in to_remove.iter().enumerate() {
+                            app.glossary_edit.entries.remove(i - j);
+                        }
+                    });
+
+                ui.horizontal(|ui| {
+                    if ui.button("Add new entry").clicked() {
+                        app.glossary_edit.entries.push(GlossaryEntry::default());
+                    }
+                    if ui.button("Add missing entries").on_hover_text("Add an entry for every ability used in the working folder").clicked() {
+                        let units: Vec<&Unit> = app.working_dir.iter().flat_map(|folder| folder.units.iter()).collect();
+                        app.glossary_edit.add_missing(&units);
+                    }
+                });
+            });
+        });
+    });
+}
@@ -0,0 +1,107 @@
+use egui::{CollapsingHeader, Context, RichText};
+use egui_extras::{Column, TableBuilder};
+
+use crate::{data::{CrusadeUpgrade, RollTable, RollTableKind, ScarEffect}, helper_funcs::select_text_on_tab};
+
+use super::{datasheet_app::DatasheetAppMode, DatasheetApp};
+
+
+
+pub fn roll_tables_panel(app: &mut DatasheetApp, ctx: &Context) {
+    egui::CentralPanel::default().show(ctx, |ui| {
+        egui::ScrollArea::both().show(ui, |ui| {
+            ui.heading(RichText::new("Roll Tables").size(30.0));
+            ui.label("Battle traits, weapon mods and battle scars that can be rolled from the crusade section of a unit.");
+
+            ui.add_enabled_ui(app.mode == DatasheetAppMode::Edit, |ui| {
+                let mut to_remove = None;
+                for (i, table) in app.roll_tables_edit.tables.iter_mut().enumerate() {
+                    let title = if table.name.is_empty() {"Unnamed Table"} else {&table.name};
+                    CollapsingHeader::new(format!("{} ({})", title, table.dice.to_string()))
+                        .id_salt(("roll_table", i))
+                        .default_open(false)
+                        .show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                if ui.button("X").on_hover_text("Delete table").clicked() {
+                                    to_remove = Some(i);
+                                }
+                                ui.label("Name:");
+                                select_text_on_tab(table.name.len(), egui::TextEdit::singleline(&mut table.name), ui);
+                                let (kind, dice) = (table.kind, table.dice);
+                                table.kind.combo_box(ui, i * 5 + 70000000);
+                                table.dice.combo_box(ui, i * 5 + 70000001);
+                                if kind != table.kind || dice != table.dice {
+                                    table.tidy();
+                                }
+                            });
+
+                            let weapon_mods = table.kind == RollTableKind::WeaponMod;
+                            let first = *table.dice.totals().start();
+                            TableBuilder::new(ui)
+                                .id_salt(70 + i)
+                                .striped(true)
+                                .resizable(false)
+                                .column(Column::auto().at_least(40.0))
+                                .column(Column::auto().at_least(200.0))
+                                .column(Column::auto().at_least(400.0))
+                                .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                                .header(20.0, |mut header| {
+                                    for col_header in ["Roll", "Name", if weapon_mods {"Changes"} else {"Description"}] {
+                                        header.col(|ui| {
+                                            ui.strong(RichText::new(col_header).size(15.0));
+                                        });
+                                    }
+                                })
+                                .body(|mut body| {
+                                    for (j, entry) in table.entries.iter_mut().enumerate() {
+                                        body.row(if weapon_mods {30.0} else {60.0}, |mut row| {
+                                            row.col(|ui| {
+                                                ui.label((first + j as u32).to_string());
+                                            });
+                                            match entry {
+                                                CrusadeUpgrade::WeaponMod(weapon_mod) => {
+                                                    row.col(|ui| {
+                                                        select_text_on_tab(weapon_mod.name.len(), egui::TextEdit::singleline(&mut weapon_mod.name), ui);
+                                                    });
+                                                    row.col(|ui| {
+                                                        weapon_mod.combo_boxes(ui, 71000000 + i * 100 + j * 2);
+                                                    });
+                                                },
+                                                CrusadeUpgrade::BattleScar(ability, effects) => {
+                                                    row.col(|ui| {
+                                                        select_text_on_tab(ability.name.len(), egui::TextEdit::singleline(&mut ability.name), ui);
+                                                    });
+                                                    row.col(|ui| {
+                                                        ui.vertical(|ui| {
+                                                            ScarEffect::checkboxes(effects, ui);
+                                                            select_text_on_tab(ability.description.len(), egui::TextEdit::multiline(&mut ability.description).desired_rows(1).desired_width(400.0), ui);
+                                                        });
+                                                    });
+                                                },
+                                                CrusadeUpgrade::Relic(ability, _) | CrusadeUpgrade::BattleTrait(ability) | CrusadeUpgrade::Enhancement(ability, _) => {
+                                                    row.col(|ui| {
+                                                        select_text_on_tab(ability.name.len(), egui::TextEdit::singleline(&mut ability.name), ui);
+                                                    });
+                                                    row.col(|ui| {
+                                                        select_text_on_tab(ability.description.len(), egui::TextEdit::multiline(&mut ability.description).desired_rows(2).desired_width(400.0), ui);
+                                                    });
+                                                },
+                                            }
+                                        });
+                                    }
+                                });
+                        });
+                }
+                if let Some(i) = to_remove {
+                    app.roll_tables_edit.tables.remove(i);
+                }
+
+                if ui.button("Add new table").clicked() {
+                    let mut table = RollTable::default();
+                    table.tidy();
+                    app.roll_tables_edit.tables.push(table);
+                }
+            });
+        });
+    });
+}
@@ -1,5 +1,7 @@
 use egui::Context;
 
+use crate::data::{CrusadeRank, RankThresholds};
+
 use super::DatasheetApp;
 
 
@@ -25,5 +27,38 @@ pub fn settings_panel(app: &mut DatasheetApp, ctx: &Context) {
             ui.label("Default Faction Keyword:");
             ui.text_edit_singleline(&mut settings.default_faction_keyword);
         });
+
+        ui.separator();
+        ui.heading("Crusade Ranks");
+        let mut changed = false;
+        let thresholds = &mut settings.rank_thresholds;
+        egui::Grid::new("rank_thresholds").show(ui, |ui| {
+            ui.label("");
+            for rank in CrusadeRank::ALL.iter() {
+                ui.label(rank.to_string());
+            }
+            ui.end_row();
+            for (label, experience) in [("Character XP", &mut thresholds.character), ("Non-Character XP", &mut thresholds.non_character)] {
+                ui.label(label);
+                ui.label("0");
+                for i in 0..experience.len() {
+                    let min = if i == 0 {1} else {experience[i - 1] + 1};
+                    changed |= ui.add(egui::DragValue::new(&mut experience[i]).range(min..=999)).changed();
+                }
+                ui.end_row();
+            }
+            ui.label("Battle Honours");
+            for limit in thresholds.honour_limits.iter_mut() {
+                ui.add(egui::DragValue::new(limit).range(0..=20));
+            }
+            ui.end_row();
+        });
+        if ui.button("Reset to Default").clicked() {
+            *thresholds = RankThresholds::default();
+            changed = true;
+        }
+        if changed {
+            app.rerank_units();
+        }
     });
 }
\ No newline at end of file
@@ -0,0 +1,135 @@
+use egui::{CollapsingHeader, ComboBox, Context, Id, RichText};
+
+use crate::{data::{Stratagem, Unit}, export::export_stratagem_cards, helper_funcs::{select_drag_value_with_range_on_tab, select_text_on_tab}};
+
+use super::{datasheet_app::DatasheetAppMode, DatasheetApp};
+
+
+
+pub fn stratagems_panel(app: &mut DatasheetApp, ctx: &Context) {
+    egui::CentralPanel::default().show(ctx, |ui| {
+        egui::ScrollArea::both().show(ui, |ui| {
+            ui.heading(RichText::new("Stratagems").size(30.0));
+            ui.label("Core and detachment stratagems. A unit can be targeted by a stratagem if it has all of the stratagem's keywords.");
+
+            // None shows every stratagem, an empty detachment shows the core stratagems
+            let detachment_id = Id::new("stratagem_detachment_filter");
+            let unit_id = Id::new("stratagem_unit_filter");
+            let mut detachment_filter = ui.data(|data| data.get_temp::<Option<String>>(detachment_id)).flatten();
+            let mut unit_filter = ui.data(|data| data.get_temp::<Option<String>>(unit_id)).flatten();
+
+            let units: Vec<(String, String)> = (0..app.working_dir.len())
+                .flat_map(|i| (0..app.working_dir[i].units.len()).map(move |j| (i, j)))
+                .map(|(i, j)| (app.unit_reference(i, j), app.working_dir[i].units[j].name.clone()))
+                .collect();
+            let filter_unit: Option<Unit> = unit_filter.as_ref().and_then(|reference| app.find_unit(reference)).map(|(i, j)| app.working_dir[i].units[j].clone());
+
+            ui.horizontal(|ui| {
+                ui.label("Detachment:");
+                let detachment_text = match &detachment_filter {
+                    None => "All",
+                    Some(detachment) if detachment.is_empty() => "Core",
+                    Some(detachment) => detachment,
+                }.to_string();
+                ComboBox::from_id_salt("stratagem_detachment")
+                    .selected_text(detachment_text)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut detachment_filter, None, "All");
+                        ui.selectable_value(&mut detachment_filter, Some(String::new()), "Core");
+                        for detachment in app.detachments.detachments.iter().filter(|detachment| !detachment.name.is_empty()) {
+                            ui.selectable_value(&mut detachment_filter, Some(detachment.name.clone()), &detachment.name);
+                        }
+                    });
+
+                ui.label("Can Target:");
+                let unit_text = filter_unit.as_ref().map(|unit| unit.name.clone()).unwrap_or("Any Unit".to_string());
+                ComboBox::from_id_salt("stratagem_unit")
+                    .selected_text(unit_text)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut unit_filter, None, "Any Unit");
+                        for (reference, name) in units.iter() {
+                            ui.selectable_value(&mut unit_filter, Some(reference.clone()), name).on_hover_text(reference);
+                        }
+                    });
+            });
+            ui.data_mut(|data| {
+                data.insert_temp(detachment_id, detachment_filter.clone());
+                data.insert_temp(unit_id, unit_filter.clone());
+            });
+
+            let shown = |stratagem: &Stratagem| {
+                detachment_filter.as_ref().is_none_or(|detachment| stratagem.detachment == *detachment)
+                    && filter_unit.as_ref().is_none_or(|unit| stratagem.can_target(unit))
+            };
+
+            ui.add_enabled_ui(app.mode == DatasheetAppMode::Edit, |ui| {
+                let detachments: Vec<String> = app.detachments.detachments.iter().map(|detachment| detachment.name.clone()).filter(|name| !name.is_empty()).collect();
+                let mut to_remove = None;
+                for (i, stratagem) in app.stratagems_edit.stratagems.iter_mut().enumerate() {
+                    if !shown(stratagem) {
+                        continue;
+                    }
+                    let title = format!("{} ({}CP)", if stratagem.name.is_empty() {"Unnamed Stratagem"} else {&stratagem.name}, stratagem.cost);
+                    CollapsingHeader::new(title)
+                        .id_salt(("stratagem", i))
+                        .default_open(false)
+                        .show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                if ui.button("X").on_hover_text("Delete stratagem").clicked() {
+                                    to_remove = Some(i);
+                                }
+                                ui.label("Name:");
+                                select_text_on_tab(stratagem.name.len(), egui::TextEdit::singleline(&mut stratagem.name), ui);
+                                ui.label("CP:");
+                                select_drag_value_with_range_on_tab(&mut stratagem.cost, 0..=5, ui);
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Phase:");
+                                stratagem.phase.combo_box(ui, 100 + i);
+                                ui.label("Detachment:");
+                                ComboBox::from_id_salt(("stratagem_detachment", i))
+                                    .selected_text(if stratagem.detachment.is_empty() {"Core"} else {&stratagem.detachment})
+                                    .show_ui(ui, |ui| {
+                                        ui.selectable_value(&mut stratagem.detachment, String::new(), "Core");
+                                        for detachment in detachments.iter() {
+                                            ui.selectable_value(&mut stratagem.detachment, detachment.clone(), detachment);
+                                        }
+                                    });
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Keywords:");
+                                // edited as a comma separated list, empty keywords are removed on save
+                                let mut keywords = stratagem.keywords.join(",");
+                                if select_text_on_tab(keywords.len(), egui::TextEdit::singleline(&mut keywords), ui).changed() {
+                                    stratagem.keywords = keywords.split(',').map(|keyword| keyword.to_uppercase()).collect();
+                                }
+                            });
+                            for (label, text) in [("When:", &mut stratagem.when), ("Target:", &mut stratagem.target), ("Effect:", &mut stratagem.effect), ("Restrictions:", &mut stratagem.restrictions)] {
+                                ui.label(label);
+                                select_text_on_tab(text.len(), egui::TextEdit::multiline(text).desired_rows(2).desired_width(700.0), ui);
+                            }
+                        });
+                }
+                if let Some(i) = to_remove {
+                    app.stratagems_edit.stratagems.remove(i);
+                }
+
+                if ui.button("Add new stratagem").clicked() {
+                    // new stratagems start in the filtered detachment so they stay visible
+                    app.stratagems_edit.stratagems.push(Stratagem {detachment: detachment_filter.clone().unwrap_or_default(), ..Default::default()});
+                }
+            });
+
+            ui.separator();
+            if ui.button("Export Cards").on_hover_text("Export the saved stratagems matching the filters as printable cards").clicked() {
+                if let Some(file) = rfd::FileDialog::new().add_filter("HTML", &["html"]).add_filter("PDF", &["pdf"]).set_directory(app.last_export_dir.clone()).set_file_name("stratagems").save_file() {
+                    let stratagems: Vec<&Stratagem> = app.stratagems.stratagems.iter().filter(|stratagem| shown(stratagem)).collect();
+                    let result = export_stratagem_cards(&stratagems, &app.export_templates, &app.get_settings().print_profile(), file);
+                    if let Err(e) = result {
+                        app.report = Some(("Export Failed".to_string(), vec![e.to_string()]));
+                    }
+                }
+            }
+        });
+    });
+}
@@ -1,6 +1,6 @@
-use egui::{Color32, Context, RichText};
+use egui::{CollapsingHeader, Color32, ComboBox, Context, RichText, Ui};
 use egui_extras::{Column, TableBuilder};
-use crate::{data::{Ability, CoreAbility, CrusadeUpgrade, UnitEditData, VariableValue, WeaponAbility, WeaponEditData, WeaponMod, WeaponReference}, helper_funcs::{select_drag_value_with_range_on_tab, select_text_on_tab}};
+use crate::{app::helper, data::{Ability, AssignedEnhancement, CoreAbility, CrusadeUpgrade, Detachments, Problem, RollTableKind, RollTables, ScarEffect, Unit, UnitBase, UnitEditData, UnitOverrides, VariableValue, Weapon, WeaponEditData, WeaponLibrary, WeaponMod, WeaponReference}, helper_funcs::{select_drag_value_with_range_on_tab, select_text_on_tab}};
 
 
 
@@ -8,7 +8,36 @@ use crate::{data::{Ability, CoreAbility, CrusadeUpgrade, UnitEditData, VariableV
 
 
 
-pub fn edit_unit(ctx: &Context, unit: &mut UnitEditData) {
+/// Shows whether a section of a variant still matches its base unit
+fn inheritance_label(ui: &mut Ui, overridden: Option<bool>) {
+    match overridden {
+        Some(true) => {ui.label(RichText::new("overridden").color(Color32::ORANGE));},
+        Some(false) => {ui.label(RichText::new("inherited").color(Color32::GRAY));},
+        None => (),
+    }
+}
+
+
+/// Lists the problems found in one section of the datasheet, under its heading
+fn section_problems(ui: &mut Ui, problems: &[Problem], sections: &[&str]) {
+    for problem in problems.iter().filter(|problem| sections.iter().any(|section| problem.location.starts_with(section))) {
+        ui.label(RichText::new(problem.to_string()).color(problem.severity.colour()));
+    }
+}
+
+
+/// `bases` are the references of every unit this one can be based on, `base` the unit it is currently based on
+pub fn edit_unit(ctx: &Context, unit: &mut UnitEditData, library: &WeaponLibrary, roll_tables: &RollTables, detachments: &Detachments, bases: &[String], base: Option<&Unit>) {
+
+    let overrides = base.map(|base| {
+        let mut current: Unit = unit.clone().into();
+        library.relink_unit(&mut current);
+        (UnitOverrides::diff(&current, base), base)
+    });
+    let section = |overridden: fn(&UnitOverrides, &Unit) -> bool| overrides.as_ref().map(|(overrides, base)| overridden(overrides, base));
+    let removes_weapon = |overrides: &UnitOverrides, weapons: &Vec<(Weapon, u32)>| weapons.iter().any(|(weapon, _)| overrides.removes_weapon(weapon));
+    let current: Unit = unit.clone().into();
+    let problems = current.validate();
 
     egui::CentralPanel::default().show(ctx, |ui| {
         egui::ScrollArea::both().show(ui, |ui| {
@@ -16,29 +45,68 @@ pub fn edit_unit(ctx: &Context, unit: &mut UnitEditData) {
             ui.horizontal(|ui| {
                 ui.label("Name:");
                 select_text_on_tab( unit.name.len(), egui::TextEdit::singleline(&mut unit.name), ui);
+                inheritance_label(ui, section(|overrides, _| overrides.name.is_some()));
             });
             ui.horizontal(|ui| {
                 ui.label("Filename:");
                 select_text_on_tab(unit.filename.len(), egui::TextEdit::singleline(&mut unit.filename), ui);
                 // ui.text_edit_singleline(&mut unit.filename);
             });
+            ui.horizontal(|ui| {
+                ui.label("Base Unit:");
+                let selected = unit.base.as_ref().map(|base| base.unit.clone()).unwrap_or("None".to_string());
+                ComboBox::from_id_salt("base_unit")
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        if ui.selectable_label(unit.base.is_none(), "None").clicked() {
+                            unit.base = None;
+                        }
+                        for reference in bases {
+                            let current = unit.base.as_ref().is_some_and(|base| &base.unit == reference);
+                            if ui.selectable_label(current, reference).clicked() && !current {
+                                unit.base = Some(UnitBase {unit: reference.clone(), ..Default::default()});
+                            }
+                        }
+                    }).response.on_hover_text("A variant inherits everything it does not override from its base unit");
+            });
             ui.horizontal(|ui| {
                 ui.label("Is a Crusade Unit:");
                 ui.checkbox( &mut unit.crusader, "");
             });
+            ui.horizontal(|ui| {
+                ui.label("Points:");
+                let mut has_points = unit.unit_comp.points.is_some();
+                if ui.checkbox(&mut has_points, "").changed() {
+                    unit.unit_comp.points = if has_points {Some(0)} else {None};
+                }
+                if let Some(points) = &mut unit.unit_comp.points {
+                    select_drag_value_with_range_on_tab(points, 0..=5000, ui);
+                }
+            });
+            if overrides.is_some() {
+                ui.horizontal(|ui| {
+                    ui.label("Stats:");
+                    inheritance_label(ui, section(|overrides, _| overrides.stats.is_some() || overrides.extra_statlines.is_some()));
+                });
+            }
             unit.stats.render(ui);
+            section_problems(ui, &problems, &["Name", "Stats", "Damaged"]);
             
 
             ui.separator();
-            ui.heading("Ranged Weapons");
+            ui.horizontal(|ui| {
+                ui.heading("Ranged Weapons");
+                inheritance_label(ui, overrides.as_ref().map(|(overrides, base)| !overrides.added_ranged_weapons.is_empty() || removes_weapon(overrides, &base.ranged_weapons)));
+            });
+            section_problems(ui, &problems, &["Ranged Weapons"]);
 
             let weapons_list = {
                 let mut list: Vec<WeaponReference> = Vec::new();
-                for (id, weapon) in unit.ranged_weapons.iter().enumerate() {
-                    list.push(WeaponReference::new(weapon.0.name.clone(), true, id));
+                for weapon in unit.ranged_weapons.iter() {
+                    list.push(WeaponReference::new(weapon.0.name.clone(), true, weapon.0.uid));
                 }
-                for (id, weapon) in unit.melee_weapons.iter().enumerate() {
-                    list.push(WeaponReference::new(weapon.0.name.clone(), false, id));
+                for weapon in unit.melee_weapons.iter() {
+                    list.push(WeaponReference::new(weapon.0.name.clone(), false, weapon.0.uid));
                 }
                 list
             };
@@ -68,14 +136,17 @@ pub fn edit_unit(ctx: &Context, unit: &mut UnitEditData) {
                 })
                 .body(|mut body| {
                     let mut to_remove = Vec::new();
+                    let mut moved = None;
                     for (i, (weapon, count)) in unit.ranged_weapons.iter_mut().enumerate() {
                         body.row(20.0, |mut row| {
                             row.col(|ui| {
                                 ui.horizontal(|ui| {
+                                    helper::drag_handle(ui, true, i);
                                     if ui.button("X").on_hover_text("Delete").clicked() {
                                         to_remove.push(i);
                                     }
                                     select_text_on_tab(weapon.name.len(), egui::TextEdit::singleline(&mut weapon.name), ui);
+                                    weapon.library_edit_section(ui, library, i * 50 + 10005030912);
                                 });
                             });
                             row.col(|ui| {
@@ -110,64 +181,36 @@ pub fn edit_unit(ctx: &Context, unit: &mut UnitEditData) {
                                 // ui.text_edit_singleline(&mut weapon.damage);
                             });
                             row.col(|ui| {
-                                weapon.charge_edit_section(ui, i, &weapons_list, i * 50 + 10005010912);
+                                weapon.charge_edit_section(ui, &weapons_list, i * 50 + 10005010912);
                             });
                             row.col(|ui| {
-                                ui.horizontal(|ui| {
-                                    if ui.button("+").on_hover_text("Add keyword").clicked() {
-                                        weapon.keywords.push(WeaponAbility::None);
-                                    }
-                                    let mut to_remove = Vec::new();
-                                    for (j, keyword) in weapon.keywords.iter_mut().enumerate() {
-                                        if ui.button("-").on_hover_text("Remove keyword").clicked() {
-                                            to_remove.push(j);
-                                        }
-                                        keyword.combo_box_ranged(ui, i * 50 + j + 10000000);
-                                        match keyword {
-                                            WeaponAbility::Sustained(_, x) => {
-                                                if !VariableValue::is_valid_variable_val(&x) {
-                                                    ui.style_mut().visuals.extreme_bg_color = Color32::RED;
-                                                }
-                                                select_text_on_tab(x.len(), egui::TextEdit::singleline(x), ui);
-                                            },
-                                            WeaponAbility::RapidFire(_, x) => {
-                                                if !VariableValue::is_valid_variable_val(&x) {
-                                                    ui.style_mut().visuals.extreme_bg_color = Color32::RED;
-                                                }
-                                                select_text_on_tab(x.len(), egui::TextEdit::singleline(x), ui);
-                                            },
-                                            WeaponAbility::AntiX(keyword, x) => {
-                                                select_text_on_tab(keyword.len(), egui::TextEdit::singleline(keyword), ui);
-                                                select_drag_value_with_range_on_tab(x, 1..=99, ui);
-                                            },
-                                            WeaponAbility::Melta(x) => {
-                                                select_drag_value_with_range_on_tab( x, 1..=99, ui);
-                                            },
-                                            WeaponAbility::Custom(x) => {
-                                                select_text_on_tab(x.len(), egui::TextEdit::singleline(x), ui);
-                                            }
-                                            _ => {}
-                                        }
-                                    
-                                    }
-                                    for (j, i) in to_remove.iter().enumerate() {
-                                        weapon.keywords.remove(i - j);
-                                    }
-                                });
-                                
+                                helper::edit_weapon_keywords(ui, &mut weapon.keywords, true, i * 50 + 10000000);
                             });
+                            moved = moved.or(helper::drag_target(&row.response(), true, i));
                         });
                     }
                     for (j, i) in to_remove.iter().enumerate() {
                         unit.ranged_weapons.remove(i - j);
                     }
+                    if let (Some((from, to)), true) = (moved, to_remove.is_empty()) {
+                        let weapon = unit.ranged_weapons.remove(from);
+                        unit.ranged_weapons.insert(to, weapon);
+                    }
                 });
 
             if ui.button("Add new weapon").clicked() {
-                unit.ranged_weapons.push((WeaponEditData::default(), 1));
+                let uid = unit.next_weapon_uid();
+                unit.ranged_weapons.push((WeaponEditData {
+                    uid,
+                    ..Default::default()
+                }, 1));
             }
             ui.separator();
-            ui.heading("Melee Weapons");
+            ui.horizontal(|ui| {
+                ui.heading("Melee Weapons");
+                inheritance_label(ui, overrides.as_ref().map(|(overrides, base)| !overrides.added_melee_weapons.is_empty() || removes_weapon(overrides, &base.melee_weapons)));
+            });
+            section_problems(ui, &problems, &["Melee Weapons"]);
 
             // melee weapons
             TableBuilder::new(ui)
@@ -194,14 +237,17 @@ pub fn edit_unit(ctx: &Context, unit: &mut UnitEditData) {
                 })
                 .body(|mut body| {
                     let mut to_remove = Vec::new();
+                    let mut moved = None;
                     for (i, (weapon, count)) in unit.melee_weapons.iter_mut().enumerate() {
                         body.row(20.0, |mut row| {
                             row.col(|ui| {
                                 ui.horizontal(|ui| {
+                                    helper::drag_handle(ui, false, i);
                                     if ui.button("X").on_hover_text("Delete").clicked() {
                                         to_remove.push(i);
                                     }
                                     select_text_on_tab(weapon.name.len(), egui::TextEdit::singleline(&mut weapon.name), ui);
+                                    weapon.library_edit_section(ui, library, i * 51 + 10005020912);
                                 });
                             });
                             
@@ -237,67 +283,38 @@ pub fn edit_unit(ctx: &Context, unit: &mut UnitEditData) {
                                 // ui.text_edit_singleline(&mut weapon.damage);
                             });
                             row.col(|ui| {
-                                weapon.charge_edit_section(ui, i + unit.ranged_weapons.len(), &weapons_list, i * 51 + 10005000912);
+                                weapon.charge_edit_section(ui, &weapons_list, i * 51 + 10005000912);
                             });
                             row.col(|ui| {
-                                ui.horizontal(|ui| {
-                                    if ui.button("+").on_hover_text("Add keyword").clicked() {
-                                        weapon.keywords.push(WeaponAbility::None);
-                                    }
-                                    let mut to_remove = Vec::new();
-                                    for (j, keyword) in weapon.keywords.iter_mut().enumerate() {
-                                        if ui.button("-").on_hover_text("Remove keyword").clicked() {
-                                            to_remove.push(j);
-                                        }
-                                        keyword.combo_box_melee(ui, i * 50 + j + 900000);
-                                        match keyword {
-                                            WeaponAbility::Sustained(_, x) => {
-                                                if !VariableValue::is_valid_variable_val(&x) {
-                                                    ui.style_mut().visuals.extreme_bg_color = Color32::RED;
-                                                }
-                                                select_text_on_tab(x.len(), egui::TextEdit::singleline(x), ui);
-                                            },
-                                            WeaponAbility::RapidFire(_, x) => {
-                                                if !VariableValue::is_valid_variable_val(&x) {
-                                                    ui.style_mut().visuals.extreme_bg_color = Color32::RED;
-                                                }
-                                                select_text_on_tab(x.len(), egui::TextEdit::singleline(x), ui);
-                                            },
-                                            WeaponAbility::AntiX(keyword, x) => {
-                                                select_text_on_tab(keyword.len(), egui::TextEdit::singleline(keyword), ui);
-                                                select_drag_value_with_range_on_tab(x, 1..=99, ui);
-                                            },
-                                            WeaponAbility::Melta(x) => {
-                                                select_drag_value_with_range_on_tab(x, 1..=99, ui);
-                                            },
-                                            WeaponAbility::Custom(x) => {
-                                                select_text_on_tab(x.len(), egui::TextEdit::singleline(x), ui);
-                                            }
-                                            _ => {}
-                                        }
-                                    
-                                    }
-                                    for (j, i) in to_remove.iter().enumerate() {
-                                        weapon.keywords.remove(i - j);
-                                    }
-                                });
+                                helper::edit_weapon_keywords(ui, &mut weapon.keywords, false, i * 50 + 900000);
                             });
+                            moved = moved.or(helper::drag_target(&row.response(), false, i));
                         });
                     }
                     for (j, i) in to_remove.iter().enumerate() {
                         unit.melee_weapons.remove(i - j);
                     }
+                    if let (Some((from, to)), true) = (moved, to_remove.is_empty()) {
+                        let weapon = unit.melee_weapons.remove(from);
+                        unit.melee_weapons.insert(to, weapon);
+                    }
                 });
 
 
             if ui.button("Add new weapon").clicked() {
+                let uid = unit.next_weapon_uid();
                 unit.melee_weapons.push((WeaponEditData {
                     range: 0,
+                    uid,
                     ..Default::default()
                 }, 1));
             }
             ui.separator();
-            ui.heading("Abilities");
+            ui.horizontal(|ui| {
+                ui.heading("Abilities");
+                inheritance_label(ui, section(|overrides, _| overrides.faction_ability.is_some() || overrides.core_abilities.is_some() || !overrides.added_abilities.is_empty() || !overrides.removed_abilities.is_empty()));
+            });
+            section_problems(ui, &problems, &["Core Abilities", "Abilities", "Leader"]);
 
             ui.horizontal(|ui| {
                 ui.label("Has Faction Ability:");
@@ -403,7 +420,11 @@ pub fn edit_unit(ctx: &Context, unit: &mut UnitEditData) {
             }
 
             ui.separator();
-            ui.heading("Keywords");
+            ui.horizontal(|ui| {
+                ui.heading("Keywords");
+                inheritance_label(ui, section(|overrides, _| overrides.faction_keyword.is_some() || !overrides.added_keywords.is_empty() || !overrides.removed_keywords.is_empty()));
+            });
+            section_problems(ui, &problems, &["Faction Keyword", "Keywords"]);
 
             ui.horizontal(|ui| {
                 ui.label("Faction Keyword:");
@@ -441,6 +462,37 @@ pub fn edit_unit(ctx: &Context, unit: &mut UnitEditData) {
             }
             ui.separator();
 
+            if !detachments.detachments.is_empty() || unit.enhancement.is_some() {
+                ui.heading("Enhancement");
+                section_problems(ui, &problems, &["Enhancement"]);
+                ui.horizontal(|ui| {
+                    ui.label("Enhancement:");
+                    let selected = unit.enhancement.as_ref().map(|assigned| format!("{}: {}", assigned.detachment, assigned.enhancement.name)).unwrap_or("None".to_string());
+                    ComboBox::from_id_salt("enhancement")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            if ui.selectable_label(unit.enhancement.is_none(), "None").clicked() {
+                                unit.enhancement = None;
+                            }
+                            for detachment in detachments.detachments.iter() {
+                                for enhancement in detachment.enhancements.iter() {
+                                    let is_selected = unit.enhancement.as_ref().is_some_and(|assigned| assigned.detachment == detachment.name && assigned.enhancement.name == enhancement.name);
+                                    let missing = enhancement.missing_keywords(&current);
+                                    let label = ui.add_enabled(missing.is_empty(), egui::SelectableLabel::new(is_selected, format!("{}: {} ({} pts)", detachment.name, enhancement.name, enhancement.points)))
+                                        .on_disabled_hover_text(format!("Needs {}", missing.join(", ")));
+                                    if label.clicked() && !is_selected {
+                                        unit.enhancement = Some(AssignedEnhancement {detachment: detachment.name.clone(), enhancement: enhancement.clone()});
+                                    }
+                                }
+                            }
+                        });
+                });
+                if let Some(assigned) = &unit.enhancement {
+                    ui.label(format!("{} pts: {}", assigned.enhancement.points, assigned.enhancement.description));
+                }
+                ui.separator();
+            }
+
 
             if unit.crusader {
                 ui.heading("Crusade Information");
@@ -453,9 +505,11 @@ pub fn edit_unit(ctx: &Context, unit: &mut UnitEditData) {
                     ui.label("Kills:");
                     select_drag_value_with_range_on_tab(&mut unit.crusade_data.kills, 0..=500, ui);
                 });
+                ui.label(format!("Crusade Points: {}", unit.crusade_data.crusade_points()));
 
 
                 ui.heading("Crusade Upgrades");
+                section_problems(ui, &problems, &["Crusade Upgrades"]);
                 TableBuilder::new(ui)
                     .id_salt(6)
                     .striped(true)
@@ -494,9 +548,10 @@ pub fn edit_unit(ctx: &Context, unit: &mut UnitEditData) {
                                                 select_text_on_tab(ability.name.len(), egui::TextEdit::singleline(&mut ability.name), ui);
                                             });
                                         },
-                                        CrusadeUpgrade::Relic(ability) => {
+                                        CrusadeUpgrade::Relic(ability, tier) => {
                                             ui.horizontal(|ui| {
                                                 select_text_on_tab(ability.name.len(), egui::TextEdit::singleline(&mut ability.name), ui);
+                                                tier.combo_box(ui, i * 4 + 90000000);
                                             });
                                         },
                                         CrusadeUpgrade::WeaponMod(weapon_mod) => {
@@ -504,12 +559,14 @@ pub fn edit_unit(ctx: &Context, unit: &mut UnitEditData) {
                                                 select_text_on_tab(weapon_mod.name.len(), egui::TextEdit::singleline(&mut weapon_mod.name), ui);
                                             });
                                         },
-                                        CrusadeUpgrade::Enhancement(ability) => {
+                                        CrusadeUpgrade::Enhancement(ability, points) => {
                                             ui.horizontal(|ui| {
                                                 select_text_on_tab(ability.name.len(), egui::TextEdit::singleline(&mut ability.name), ui);
+                                                ui.label("Points:");
+                                                select_drag_value_with_range_on_tab(points, 0..=500, ui);
                                             });
                                         },
-                                        CrusadeUpgrade::BattleScar(ability) => {
+                                        CrusadeUpgrade::BattleScar(ability, _) => {
                                             ui.horizontal(|ui| {
                                                 select_text_on_tab(ability.name.len(), egui::TextEdit::singleline(&mut ability.name), ui);
                                             });
@@ -521,7 +578,7 @@ pub fn edit_unit(ctx: &Context, unit: &mut UnitEditData) {
                                         CrusadeUpgrade::BattleTrait(ability) => {
                                             select_text_on_tab(ability.description.len(), egui::TextEdit::multiline(&mut ability.description), ui);
                                         },
-                                        CrusadeUpgrade::Relic(ability) => {
+                                        CrusadeUpgrade::Relic(ability, _) => {
                                             select_text_on_tab(ability.description.len(), egui::TextEdit::multiline(&mut ability.description), ui);
                                         },
                                         CrusadeUpgrade::WeaponMod(weapon_mod) => {
@@ -530,11 +587,14 @@ pub fn edit_unit(ctx: &Context, unit: &mut UnitEditData) {
                                                 weapon_mod.target_select(ui, 10 + i * 4, &weapons_list);
                                             });
                                         }
-                                        CrusadeUpgrade::Enhancement(ability) => {
+                                        CrusadeUpgrade::Enhancement(ability, _) => {
                                             select_text_on_tab(ability.description.len(), egui::TextEdit::multiline(&mut ability.description), ui);
                                         },
-                                        CrusadeUpgrade::BattleScar(ability) => {
-                                            select_text_on_tab(ability.description.len(), egui::TextEdit::multiline(&mut ability.description), ui);
+                                        CrusadeUpgrade::BattleScar(ability, effects) => {
+                                            ui.vertical(|ui| {
+                                                ScarEffect::checkboxes(effects, ui);
+                                                select_text_on_tab(ability.description.len(), egui::TextEdit::multiline(&mut ability.description), ui);
+                                            });
                                         },
                                     }
                                 });
@@ -549,6 +609,59 @@ pub fn edit_unit(ctx: &Context, unit: &mut UnitEditData) {
                     unit.crusade_data.upgrades.push(CrusadeUpgrade::WeaponMod(WeaponMod::default()));
                 }
 
+                if !roll_tables.tables.is_empty() {
+                    ui.horizontal(|ui| {
+                        let id = ui.id().with("roll_table");
+                        let mut selected = ui.data(|data| data.get_temp::<usize>(id)).unwrap_or(0).min(roll_tables.tables.len() - 1);
+                        ui.label("Roll Table:");
+                        ComboBox::from_id_salt(id)
+                            .selected_text(&roll_tables.tables[selected].name)
+                            .show_ui(ui, |ui| {
+                                for (i, table) in roll_tables.tables.iter().enumerate() {
+                                    ui.selectable_value(&mut selected, i, &table.name);
+                                }
+                            });
+                        ui.data_mut(|data| data.insert_temp(id, selected));
+
+                        // a weapon mod needs a weapon, so it is chosen before rolling
+                        let needs_target = roll_tables.tables[selected].kind == RollTableKind::WeaponMod;
+                        let target_id = ui.id().with("roll_target");
+                        let mut target = ui.data(|data| data.get_temp::<Option<WeaponReference>>(target_id)).flatten()
+                            .filter(|target| weapons_list.contains(target));
+                        if needs_target {
+                            ui.label("Weapon:");
+                            ComboBox::from_id_salt(target_id)
+                                .selected_text(target.as_ref().map(|target| target.name.clone()).unwrap_or("None".to_string()))
+                                .show_ui(ui, |ui| {
+                                    for weapon in weapons_list.iter() {
+                                        ui.selectable_value(&mut target, Some(weapon.clone()), &weapon.name);
+                                    }
+                                });
+                            ui.data_mut(|data| data.insert_temp(target_id, target.clone()));
+                        }
+                        let roll = ui.add_enabled(!needs_target || target.is_some(), egui::Button::new("Roll"))
+                            .on_hover_text("Roll on the table and add the result as an upgrade");
+                        if roll.clicked() {
+                            let (record, mut upgrade) = roll_tables.tables[selected].roll(rand::random());
+                            if let CrusadeUpgrade::WeaponMod(weapon_mod) = &mut upgrade {
+                                weapon_mod.target = target;
+                            }
+                            unit.crusade_data.upgrades.push(upgrade);
+                            unit.crusade_data.rolls.push(record);
+                        }
+                    });
+                }
+                if let Some(record) = unit.crusade_data.rolls.last() {
+                    ui.label(record.to_string());
+                    CollapsingHeader::new(format!("Roll History ({})", unit.crusade_data.rolls.len()))
+                        .default_open(false)
+                        .show(ui, |ui| {
+                            for record in unit.crusade_data.rolls.iter().rev() {
+                                ui.label(record.to_string());
+                            }
+                        });
+                }
+
                 ui.separator();
             }
 
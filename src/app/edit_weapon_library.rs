@@ -0,0 +1,131 @@
+use egui::{CollapsingHeader, Color32, Context, RichText};
+use egui_extras::{Column, TableBuilder};
+
+use crate::{app::helper, data::{VariableValue, WeaponEditData}, helper_funcs::{select_drag_value_with_range_on_tab, select_text_on_tab}};
+
+use super::{datasheet_app::{DatasheetAppMode, OpenFile}, DatasheetApp};
+
+
+
+pub fn weapon_library_panel(app: &mut DatasheetApp, ctx: &Context) {
+    let mut open = None;
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        egui::ScrollArea::both().show(ui, |ui| {
+            ui.heading(RichText::new("Weapon Library").size(30.0));
+            ui.label("Unit weapons linked to an entry are updated whenever the library is saved.");
+
+            ui.add_enabled_ui(app.mode == DatasheetAppMode::Edit, |ui| {
+                TableBuilder::new(ui)
+                    .id_salt(20)
+                    .striped(true)
+                    .resizable(false)
+                    .column(Column::auto().at_least(150.0))
+                    .column(Column::auto().at_least(250.0))
+                    .column(Column::auto().at_least(40.0))
+                    .column(Column::auto().at_least(30.0))
+                    .column(Column::auto().at_least(40.0))
+                    .column(Column::auto().at_least(40.0))
+                    .column(Column::auto().at_least(40.0))
+                    .column(Column::auto().at_least(40.0))
+                    .column(Column::auto())
+                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                    .header(20.0, |mut header| {
+                        for col_header in ["Id", "Name", "Range", "A", "Skill", "S", "AP", "D", "Keywords"] {
+                            header.col(|ui| {
+                                ui.strong(RichText::new(col_header).size(15.0));
+                            });
+                        }
+                    })
+                    .body(|mut body| {
+                        let mut to_remove = Vec::new();
+                        for (i, (_, id, weapon)) in app.weapon_library_edit.iter_mut().enumerate() {
+                            body.row(20.0, |mut row| {
+                                row.col(|ui| {
+                                    ui.horizontal(|ui| {
+                                        if ui.button("X").on_hover_text("Delete").clicked() {
+                                            to_remove.push(i);
+                                        }
+                                        select_text_on_tab(id.len(), egui::TextEdit::singleline(id), ui);
+                                    });
+                                });
+                                row.col(|ui| {
+                                    select_text_on_tab(weapon.name.len(), egui::TextEdit::singleline(&mut weapon.name), ui);
+                                });
+                                row.col(|ui| {
+                                    select_drag_value_with_range_on_tab(&mut weapon.range, 0..=300, ui).on_hover_text("0 for melee");
+                                });
+                                row.col(|ui| {
+                                    if !VariableValue::is_valid_variable_val(&weapon.attacks) {
+                                        ui.style_mut().visuals.extreme_bg_color = Color32::RED;
+                                    }
+                                    select_text_on_tab(weapon.attacks.len(), egui::TextEdit::singleline(&mut weapon.attacks), ui);
+                                });
+                                row.col(|ui| {
+                                    select_drag_value_with_range_on_tab(&mut weapon.skill, 1..=6, ui);
+                                });
+                                row.col(|ui| {
+                                    select_drag_value_with_range_on_tab(&mut weapon.strength, 1..=99, ui);
+                                });
+                                row.col(|ui| {
+                                    select_drag_value_with_range_on_tab(&mut weapon.ap, 0..=10, ui);
+                                });
+                                row.col(|ui| {
+                                    if !VariableValue::is_valid_variable_val(&weapon.damage) {
+                                        ui.style_mut().visuals.extreme_bg_color = Color32::RED;
+                                    }
+                                    select_text_on_tab(weapon.damage.len(), egui::TextEdit::singleline(&mut weapon.damage), ui);
+                                });
+                                row.col(|ui| {
+                                    helper::edit_weapon_keywords(ui, &mut weapon.keywords, weapon.range != 0, i * 50 + 20000000);
+                                });
+                            });
+                        }
+                        for (j, i) in to_remove.iter().enumerate() {
+                            app.weapon_library_edit.remove(i - j);
+                        }
+                    });
+
+                ui.horizontal(|ui| {
+                    if ui.button("Add ranged weapon").clicked() {
+                        app.weapon_library_edit.push(("".to_string(), "".to_string(), WeaponEditData::default()));
+                    }
+                    if ui.button("Add melee weapon").clicked() {
+                        app.weapon_library_edit.push(("".to_string(), "".to_string(), WeaponEditData {
+                            range: 0,
+                            ..Default::default()
+                        }));
+                    }
+                });
+            });
+
+            ui.separator();
+            ui.heading("Where Used");
+            for (id, weapon) in app.weapon_library.weapons.iter() {
+                CollapsingHeader::new(format!("{} ({})", weapon.name, id))
+                    .id_salt(id)
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        let mut used = false;
+                        for (i, folder) in app.working_dir.iter().enumerate() {
+                            for (j, unit) in folder.units.iter().enumerate() {
+                                if unit.uses_library_weapon(id) {
+                                    used = true;
+                                    if ui.selectable_label(false, format!("{} / {}", folder.name, unit.name)).clicked() {
+                                        open = Some((i, j));
+                                    }
+                                }
+                            }
+                        }
+                        if !used {
+                            ui.label("Not used by any unit");
+                        }
+                    });
+            }
+        });
+    });
+
+    if let Some(index) = open {
+        app.open_file(OpenFile::Index(index));
+    }
+}
@@ -1,51 +1,102 @@
-use egui::{Color32, RichText};
+use egui::{Color32, Id, Response, RichText, Ui};
 use egui_extras::TableBody;
 
-use crate::data::{ChargeLevels, Weapon};
+use crate::{data::{Glossary, VariableValue, Weapon, WeaponAbility}, helper_funcs::{select_drag_value_with_range_on_tab, select_text_on_tab}};
 
 
 
-pub fn draw_weapon_row(weapon: &Weapon, count: u32, body: &mut TableBody, keyword_colour: Color32) {
+/// `original` is the weapon a crusade weapon was made from, any columns that differ from it are shown struck through
+pub fn draw_weapon_row(weapon: &Weapon, original: Option<&Weapon>, count: u32, body: &mut TableBody, keyword_colour: Color32, glossary: &Glossary) {
     let data = weapon.get_render_data();
     let has_keywords = data.7 != "[]";
     let height = if has_keywords{32.0} else {22.0};
 
     body.row(height, |mut row| {
         row.col(|ui| {
-            let (weapon_name, level_name) = match &weapon.charge {
-                ChargeLevels::None => (weapon.name.clone(), "".to_string()),
-                ChargeLevels::Parent(level_name) => (weapon.name.clone(), level_name.clone()),
-                ChargeLevels::Child(parent_ref, level_name) => (parent_ref.name.clone(), level_name.clone())
-            };
-            let name = if level_name == "" {weapon_name} else {format!("{} - {}", weapon_name, level_name)};
-            let title = if count == 1 {name} else {format!("{}x {}", count, name)};
+            let title = weapon.get_title(count);
 
             if has_keywords {
                 ui.vertical(|ui| {
                     ui.label(RichText::new(title).size(14.0));
-                    ui.label(RichText::new(data.7).color(keyword_colour).size(10.5))
+                    let keywords = ui.label(RichText::new(data.7).color(keyword_colour).size(10.5));
+                    if let Some(tooltip) = glossary.weapon_tooltip(&weapon.keywords) {
+                        keywords.on_hover_text(tooltip);
+                    }
                 });
             } else {
                 ui.label(RichText::new(title).size(14.0));
             }
         });
-        row.col(|ui| {
-            ui.label(data.1);
-        });
-        row.col(|ui| {
-            ui.label(data.2);
-        });
-        row.col(|ui| {
-            ui.label(data.3);
-        });
-        row.col(|ui| {
-            ui.label(data.4.to_string());
-        });
-        row.col(|ui| {
-            ui.label(data.5);
-        });
-        row.col(|ui| {
-            ui.label(data.6);
-        });
+        for (original, shown) in weapon.column_values(original) {
+            row.col(|ui| {
+                if original != shown {
+                    ui.label(RichText::new(original).strikethrough().weak());
+                }
+                ui.label(shown);
+            });
+        }
     });
-}
\ No newline at end of file
+}
+
+
+pub fn edit_weapon_keywords(ui: &mut Ui, keywords: &mut Vec<WeaponAbility>, ranged: bool, id: usize) {
+    ui.horizontal(|ui| {
+        if ui.button("+").on_hover_text("Add keyword").clicked() {
+            keywords.push(WeaponAbility::None);
+        }
+        let mut to_remove = Vec::new();
+        for (j, keyword) in keywords.iter_mut().enumerate() {
+            if ui.button("-").on_hover_text("Remove keyword").clicked() {
+                to_remove.push(j);
+            }
+            if ranged {
+                keyword.combo_box_ranged(ui, id + j);
+            } else {
+                keyword.combo_box_melee(ui, id + j);
+            }
+            match keyword {
+                WeaponAbility::Sustained(_, x) => {
+                    if !VariableValue::is_valid_variable_val(&x) {
+                        ui.style_mut().visuals.extreme_bg_color = Color32::RED;
+                    }
+                    select_text_on_tab(x.len(), egui::TextEdit::singleline(x), ui);
+                },
+                WeaponAbility::RapidFire(_, x) => {
+                    if !VariableValue::is_valid_variable_val(&x) {
+                        ui.style_mut().visuals.extreme_bg_color = Color32::RED;
+                    }
+                    select_text_on_tab(x.len(), egui::TextEdit::singleline(x), ui);
+                },
+                WeaponAbility::AntiX(keyword, x) => {
+                    select_text_on_tab(keyword.len(), egui::TextEdit::singleline(keyword), ui);
+                    select_drag_value_with_range_on_tab(x, 1..=99, ui);
+                },
+                WeaponAbility::Melta(x) => {
+                    select_drag_value_with_range_on_tab(x, 1..=99, ui);
+                },
+                WeaponAbility::Custom(x) => {
+                    select_text_on_tab(x.len(), egui::TextEdit::singleline(x), ui);
+                }
+                _ => {}
+            }
+        }
+        for (j, i) in to_remove.iter().enumerate() {
+            keywords.remove(i - j);
+        }
+    });
+}
+
+
+/// A handle for dragging a weapon row onto another in the same table
+pub fn drag_handle(ui: &mut Ui, ranged: bool, index: usize) {
+    ui.dnd_drag_source(Id::new(("weapon_row", ranged, index)), (ranged, index), |ui| {
+        ui.label("☰");
+    }).response.on_hover_text("Drag to reorder");
+}
+
+/// The (from, to) indices if a weapon row from the same table was dropped on this one
+pub fn drag_target(row: &Response, ranged: bool, index: usize) -> Option<(usize, usize)> {
+    let payload = row.dnd_release_payload::<(bool, usize)>()?;
+    let (from_ranged, from) = *payload;
+    if from_ranged == ranged && from != index {Some((from, index))} else {None}
+}
@@ -3,10 +3,18 @@ mod edit_unit;
 mod settings;
 mod datasheet_app;
 mod edit_settings;
+mod edit_glossary;
+mod edit_roll_tables;
+mod edit_agendas;
+mod edit_detachments;
+mod edit_stratagems;
+mod edit_weapon_library;
+mod problems;
+mod order_of_battle;
 mod shortcuts;
 mod pop_up_menus;
 mod helper;
 mod layout;
 
-pub use datasheet_app::DatasheetApp;
+pub use datasheet_app::{DatasheetApp, FOLDER_FILES};
 pub use settings::{string_to_color32, DatasheetAppSettings};
\ No newline at end of file
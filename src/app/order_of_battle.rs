@@ -0,0 +1,196 @@
+use egui::{CollapsingHeader, Color32, Context, RichText};
+use egui_extras::{Column, TableBuilder};
+
+use crate::{data::{BattleUnit, BattleWizard, RequisitionWizard, Unit}, export::export_order_of_battle, helper_funcs::{select_drag_value_with_range_on_tab, select_text_on_tab}};
+
+use super::{datasheet_app::{DatasheetAppMode, OpenFile}, DatasheetApp};
+
+
+
+pub fn order_of_battle_panel(app: &mut DatasheetApp, ctx: &Context) {
+    let mut open = None;
+    let mut record_battle = false;
+    let mut requisition = false;
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        egui::ScrollArea::both().show(ui, |ui| {
+            ui.heading(RichText::new("Order of Battle").size(30.0));
+
+            let force = &mut app.crusade_force_edit;
+            ui.add_enabled_ui(app.mode == DatasheetAppMode::Edit, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    select_text_on_tab(force.name.len(), egui::TextEdit::singleline(&mut force.name), ui);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Faction:");
+                    select_text_on_tab(force.faction.len(), egui::TextEdit::singleline(&mut force.faction), ui);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Supply Limit:");
+                    select_drag_value_with_range_on_tab(&mut force.supply_limit, 0..=10000, ui);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Requisition Points:");
+                    select_drag_value_with_range_on_tab(&mut force.requisition_points, 0..=99, ui);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Battles:");
+                    select_drag_value_with_range_on_tab(&mut force.battles, 0..=999, ui);
+                    ui.label("Victories:");
+                    select_drag_value_with_range_on_tab(&mut force.victories, 0..=999, ui);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Enhancement Limit:");
+                    select_drag_value_with_range_on_tab(&mut force.enhancement_limit, 0..=99, ui);
+                    ui.label("Relic Limit:");
+                    select_drag_value_with_range_on_tab(&mut force.relic_limit, 0..=99, ui);
+                });
+                ui.label("Notes:");
+                select_text_on_tab(force.notes.len(), egui::TextEdit::multiline(&mut force.notes), ui);
+            });
+
+            let mut members = Vec::new();
+            for (i, folder) in app.working_dir.iter().enumerate() {
+                for (j, unit) in folder.units.iter().enumerate() {
+                    if unit.crusade_unit {
+                        members.push(((i, j), unit));
+                    }
+                }
+            }
+            let units: Vec<&Unit> = members.iter().map(|(_, unit)| *unit).collect();
+
+            ui.separator();
+            let supply_used = force.supply_used(&units);
+            let supply_colour = if supply_used > force.supply_limit {Color32::RED} else {ui.visuals().text_color()};
+            ui.label(RichText::new(format!("Supply Used: {} / {}", supply_used, force.supply_limit)).color(supply_colour));
+            ui.label(format!("Crusade Points: {}", force.total_crusade_points(&units)));
+            for problem in force.upgrade_problems(&units) {
+                ui.label(RichText::new(problem).color(Color32::RED));
+            }
+
+            ui.heading("Units");
+            TableBuilder::new(ui)
+                .id_salt(40)
+                .striped(true)
+                .resizable(false)
+                .column(Column::auto().at_least(200.0))
+                .column(Column::auto().at_least(60.0))
+                .column(Column::auto().at_least(120.0))
+                .column(Column::auto().at_least(40.0))
+                .column(Column::auto().at_least(100.0))
+                .column(Column::auto().at_least(40.0))
+                .column(Column::auto().at_least(60.0))
+                .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                .header(20.0, |mut header| {
+                    for col_header in ["Unit", "Points", "Rank", "XP", "Crusade Points", "Kills", "Honours"] {
+                        header.col(|ui| {
+                            ui.strong(RichText::new(col_header).size(15.0));
+                        });
+                    }
+                })
+                .body(|mut body| {
+                    for (index, unit) in members.iter() {
+                        body.row(20.0, |mut row| {
+                            row.col(|ui| {
+                                if ui.selectable_label(false, &unit.name).clicked() {
+                                    open = Some(*index);
+                                }
+                            });
+                            row.col(|ui| {
+                                ui.label(unit.unit_comp.points.map(|points| points.to_string()).unwrap_or("-".to_string()));
+                            });
+                            row.col(|ui| {
+                                ui.label(unit.crusade_data.rank.to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(unit.crusade_data.exp.to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(unit.crusade_data.crusade_points().to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(unit.crusade_data.kills.to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(unit.crusade_data.upgrades.len().to_string());
+                            });
+                        });
+                    }
+                });
+            if members.is_empty() {
+                ui.label("No crusade units in the working folder");
+            }
+
+            ui.separator();
+            ui.heading("Battle Log");
+            if ui.button("Record Battle").on_hover_text("Award experience and roll out of action tests after a game").clicked() {
+                record_battle = true;
+            }
+            for (i, record) in app.crusade_force.log.iter().enumerate().rev() {
+                let title = format!("{} - {} vs {}", record.date, record.result.to_string(), if record.opponent.is_empty() {"Unknown"} else {&record.opponent});
+                CollapsingHeader::new(title)
+                    .id_salt(("battle", i))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        if !record.mission.is_empty() {
+                            ui.label(format!("Mission: {}", record.mission));
+                        }
+                        if !record.agendas.is_empty() {
+                            ui.label(format!("Agendas: {}", record.agendas.join(", ")));
+                        }
+                        for battle_unit in record.units.iter() {
+                            ui.label(battle_unit_summary(battle_unit));
+                        }
+                        if !record.notes.is_empty() {
+                            ui.label(&record.notes);
+                        }
+                    });
+            }
+
+            ui.separator();
+            ui.heading("Requisitions");
+            if ui.button("Spend Requisition Points").on_hover_text("Buy a requisition with the saved requisition points").clicked() {
+                requisition = true;
+            }
+            for record in app.crusade_force.requisitions.iter().rev() {
+                ui.label(record.to_string());
+            }
+
+            ui.separator();
+            if ui.button("Export Roster").on_hover_text("Export the saved order of battle as a printable roster").clicked() {
+                if let Some(file) = rfd::FileDialog::new().add_filter("HTML", &["html"]).add_filter("PDF", &["pdf"]).set_directory(app.last_export_dir.clone()).set_file_name("order_of_battle").save_file() {
+                    let result = export_order_of_battle(&app.crusade_force, &units, file);
+                    if let Err(e) = result {
+                        app.report = Some(("Export Failed".to_string(), vec![e.to_string()]));
+                    }
+                }
+            }
+        });
+    });
+
+    if let Some(index) = open {
+        app.open_file(OpenFile::Index(index));
+    }
+    if record_battle {
+        app.battle_wizard = Some(BattleWizard::new(app.crusade_units(), &app.agendas));
+    }
+    if requisition {
+        app.requisition_wizard = Some(RequisitionWizard::new(app.crusade_units()));
+    }
+}
+
+/// One line describing how a unit did in a battle
+pub fn battle_unit_summary(battle_unit: &BattleUnit) -> String {
+    let mut summary = format!("{}: {} kills, +{} XP", battle_unit.name, battle_unit.kills, battle_unit.exp_gained);
+    if battle_unit.marked_for_greatness {
+        summary += ", Marked for Greatness";
+    }
+    for tally in battle_unit.tallies.iter() {
+        summary += &format!(", {} x{} (+{} XP)", tally.agenda, tally.tally, tally.exp);
+    }
+    if let Some((roll, outcome)) = &battle_unit.out_of_action {
+        summary += &format!(", destroyed (out of action roll {}: {})", roll, outcome.to_string());
+    }
+    summary
+}
@@ -1,6 +1,7 @@
-use egui::{global_theme_preference_switch, CollapsingHeader, Context};
+use egui::{global_theme_preference_switch, CollapsingHeader, Color32, ComboBox, Context, RichText};
+use egui_extras::{Column, TableBuilder};
 
-use crate::export::ExportType;
+use crate::{data::{Ability, BattleWizard, CrusadeUpgrade, OutOfAction, PendingScar, RankUp, RelicTier, Requisition, RequisitionWizard, RollTable, ScarEffect, WeaponLibrary, WeaponMod}, export::{ExportType, PrintProfile}, helper_funcs::{select_drag_value_with_range_on_tab, select_text_on_tab}, import::{CsvImport, CsvSheet, UnitColumns, WeaponColumns}};
 
 use super::{datasheet_app::DatasheetFolder, DatasheetAppSettings};
 
@@ -139,7 +140,7 @@ pub fn settings_window(ctx: &Context, result: &mut Option<bool>, settings: &mut
 
 
 
-pub fn export_window(ctx: &Context, result: &mut Option<bool>, export_type: &mut ExportType) {
+pub fn export_window(ctx: &Context, result: &mut Option<bool>, export_type: &mut ExportType, settings: &mut DatasheetAppSettings) {
     egui::Window::new("Settings")
     .collapsible(false)
     .resizable(true)
@@ -153,9 +154,64 @@ pub fn export_window(ctx: &Context, result: &mut Option<bool>, export_type: &mut
                     ui.selectable_value(export_type, ExportType::PDF, "PDF");
                     // ui.selectable_value(export_type, ExportType::LATEX, "LaTeX");
                     ui.selectable_value(export_type, ExportType::HTML, "HTML");
+                    ui.selectable_value(export_type, ExportType::PNG, "PNG");
+                    ui.selectable_value(export_type, ExportType::WEBP, "WebP");
+                    ui.selectable_value(export_type, ExportType::SVG, "SVG");
+                    ui.selectable_value(export_type, ExportType::MARKDOWN, "Markdown");
+                    ui.selectable_value(export_type, ExportType::TEXT, "Plain Text");
+                    ui.selectable_value(export_type, ExportType::JSON, "JSON");
+                    ui.selectable_value(export_type, ExportType::YAML, "YAML");
+                    ui.selectable_value(export_type, ExportType::TTS, "Tabletop Simulator");
                 })
         });
 
+        if *export_type == ExportType::PDF {
+            print_profile_section(ui, settings);
+        }
+        if export_type.is_image() {
+            ui.horizontal(|ui| {
+                ui.label("DPI: ");
+                ui.add(egui::DragValue::new(&mut settings.image_dpi).range(72..=1200));
+            });
+        }
+        if *export_type == ExportType::SVG {
+            ui.horizontal(|ui| {
+                ui.label("Embed Fonts: ");
+                ui.checkbox(&mut settings.svg_embed_fonts, "");
+            });
+        }
+        if matches!(export_type, ExportType::PDF | ExportType::HTML | ExportType::MARKDOWN | ExportType::TEXT) {
+            ui.horizontal(|ui| {
+                ui.label("Rules Reference: ");
+                ui.checkbox(&mut settings.rules_reference, "").on_hover_text("Add the glossary text of every ability used");
+            });
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("Save").clicked() {
+                *result = Some(true);
+            }
+            if ui.button("Cancel").clicked() {
+                *result = Some(false);
+            }
+        });
+
+
+    });
+}
+
+
+pub fn print_folder_window(ctx: &Context, result: &mut Option<bool>, folder_name: &str, settings: &mut DatasheetAppSettings) {
+    egui::Window::new(format!("Print {}", folder_name))
+    .collapsible(false)
+    .resizable(true)
+    .show(ctx, |ui| {
+        print_profile_section(ui, settings);
+        ui.horizontal(|ui| {
+            ui.label("Rules Reference: ");
+            ui.checkbox(&mut settings.rules_reference, "").on_hover_text("Add the glossary text of every ability used");
+        });
+
         ui.horizontal(|ui| {
             if ui.button("Save").clicked() {
                 *result = Some(true);
@@ -164,11 +220,527 @@ pub fn export_window(ctx: &Context, result: &mut Option<bool>, export_type: &mut
                 *result = Some(false);
             }
         });
+    });
+}
+
+
+pub fn export_folder_window(ctx: &Context, result: &mut Option<bool>, folder_name: &str, export_type: &mut ExportType) {
+    egui::Window::new(format!("Export {}", folder_name))
+    .collapsible(false)
+    .resizable(false)
+    .show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Export File Type: ");
+            egui::ComboBox::from_id_salt(13)
+                .selected_text(export_type.to_string())
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(export_type, ExportType::JSON, "JSON");
+                    ui.selectable_value(export_type, ExportType::YAML, "YAML");
+                    ui.selectable_value(export_type, ExportType::TTS, "Tabletop Simulator");
+                })
+        });
+
+        ui.horizontal(|ui| {
+            if ui.button("Save").clicked() {
+                *result = Some(true);
+            }
+            if ui.button("Cancel").clicked() {
+                *result = Some(false);
+            }
+        });
+    });
+}
+
+
+pub fn report_window(ctx: &Context, result: &mut Option<bool>, title: &str, lines: &[String]) {
+    egui::Window::new(title)
+    .collapsible(false)
+    .resizable(true)
+    .show(ctx, |ui| {
+        egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            for line in lines.iter() {
+                ui.label(line);
+            }
+        });
+        if ui.button("Close").clicked() {
+            *result = Some(true);
+        }
+    });
+}
+
+
+pub fn csv_import_window(ctx: &Context, result: &mut Option<bool>, import: &mut CsvImport, error: &mut Option<String>) {
+    egui::Window::new("Import from CSV")
+    .collapsible(false)
+    .resizable(true)
+    .show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Units Sheet:");
+            if ui.button("Choose...").clicked() {
+                if let Some(path) = rfd::FileDialog::new().add_filter("CSV", &["csv"]).pick_file() {
+                    match CsvSheet::read(path) {
+                        Ok(sheet) => {
+                            let columns = UnitColumns::guess(&sheet);
+                            import.units = Some((sheet, columns));
+                        },
+                        Err(e) => *error = Some(e.to_string())
+                    }
+                }
+            }
+            if let Some((sheet, _)) = &import.units {
+                ui.label(sheet.path.file_name().unwrap().to_str().unwrap());
+            }
+        });
+        if let Some((sheet, columns)) = &mut import.units {
+            column_mapping(ui, "csv_unit_columns", &sheet.headers, &mut columns.fields_mut());
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Weapons Sheet:");
+            if ui.button("Choose...").clicked() {
+                if let Some(path) = rfd::FileDialog::new().add_filter("CSV", &["csv"]).pick_file() {
+                    match CsvSheet::read(path) {
+                        Ok(sheet) => {
+                            let columns = WeaponColumns::guess(&sheet);
+                            import.weapons = Some((sheet, columns));
+                        },
+                        Err(e) => *error = Some(e.to_string())
+                    }
+                }
+            }
+            if let Some((sheet, _)) = &import.weapons {
+                ui.label(sheet.path.file_name().unwrap().to_str().unwrap());
+            }
+        });
+        if let Some((sheet, columns)) = &mut import.weapons {
+            column_mapping(ui, "csv_weapon_columns", &sheet.headers, &mut columns.fields_mut());
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("Cancel").clicked() {
+                *result = Some(false);
+            }
+            if ui.button("Import").clicked() && import.units.is_some() {
+                *result = Some(true);
+            }
+        });
+    });
+}
+
+fn column_mapping(ui: &mut egui::Ui, id: &str, headers: &[String], fields: &mut [(&str, &mut Option<usize>)]) {
+    egui::Grid::new(id).show(ui, |ui| {
+        for (i, (field, column)) in fields.iter_mut().enumerate() {
+            ui.label(*field);
+            let selected = column.and_then(|column| headers.get(column)).cloned().unwrap_or("None".to_string());
+            egui::ComboBox::from_id_salt(format!("{}{}", id, i))
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(*column, None, "None");
+                    for (j, header) in headers.iter().enumerate() {
+                        ui.selectable_value(*column, Some(j), header);
+                    }
+                });
+            ui.end_row();
+        }
+    });
+}
+
+
+fn print_profile_section(ui: &mut egui::Ui, settings: &mut DatasheetAppSettings) {
+    if settings.print_profiles.is_empty() {
+        settings.print_profiles.push(PrintProfile::default());
+    }
+    if settings.selected_print_profile >= settings.print_profiles.len() {
+        settings.selected_print_profile = 0;
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("Print Profile: ");
+        egui::ComboBox::from_id_salt(11)
+            .selected_text(settings.print_profiles[settings.selected_print_profile].name.clone())
+            .show_ui(ui, |ui| {
+                for (i, profile) in settings.print_profiles.iter().enumerate() {
+                    ui.selectable_value(&mut settings.selected_print_profile, i, profile.name.clone());
+                }
+            });
+        if ui.button("+").on_hover_text("New profile").clicked() {
+            let mut profile = settings.print_profiles[settings.selected_print_profile].clone();
+            profile.name = format!("{} Copy", profile.name);
+            settings.selected_print_profile = settings.print_profiles.len();
+            settings.print_profiles.push(profile);
+        }
+        if settings.print_profiles.len() > 1 && ui.button("-").on_hover_text("Delete profile").clicked() {
+            settings.print_profiles.remove(settings.selected_print_profile);
+            settings.selected_print_profile = 0;
+        }
+    });
+
+    CollapsingHeader::new("Edit Profile")
+        .default_open(false)
+        .show(ui, |ui| {
+            settings.print_profiles[settings.selected_print_profile].edit(ui, 12);
+        });
+}
+
+
+pub fn record_battle_window(ctx: &Context, result: &mut Option<bool>, wizard: &mut BattleWizard) {
+    egui::Window::new("Record Battle")
+    .collapsible(false)
+    .resizable(true)
+    .show(ctx, |ui| {
+        let record = &mut wizard.record;
+        ui.horizontal(|ui| {
+            ui.label("Date:");
+            select_text_on_tab(record.date.len(), egui::TextEdit::singleline(&mut record.date), ui);
+            ui.label("Result:");
+            record.result.combo_box(ui, "battle_result");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Opponent:");
+            select_text_on_tab(record.opponent.len(), egui::TextEdit::singleline(&mut record.opponent), ui);
+            ui.label("Mission:");
+            select_text_on_tab(record.mission.len(), egui::TextEdit::singleline(&mut record.mission), ui);
+        });
+        ui.label("Notes:");
+        select_text_on_tab(record.notes.len(), egui::TextEdit::multiline(&mut record.notes).desired_rows(2), ui);
+
+        let rolled = wizard.rolled;
+        if !wizard.agendas.is_empty() {
+            ui.add_enabled_ui(!rolled, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label("Agendas:");
+                    for (agenda, chosen) in wizard.agendas.iter_mut() {
+                        ui.checkbox(chosen, &agenda.name).on_hover_text(&agenda.description);
+                    }
+                });
+            });
+        }
+
+        ui.separator();
+        let mut marked = None;
+        let agendas = &wizard.agendas;
+        let chosen: Vec<usize> = agendas.iter().enumerate().filter(|(_, (_, chosen))| *chosen).map(|(k, _)| k).collect();
+        ui.add_enabled_ui(!rolled, |ui| {
+            let mut table = TableBuilder::new(ui)
+                .id_salt(50)
+                .striped(true)
+                .resizable(false)
+                .column(Column::auto().at_least(200.0))
+                .column(Column::auto().at_least(40.0))
+                .column(Column::auto().at_least(60.0))
+                .column(Column::auto().at_least(60.0));
+            for _ in chosen.iter() {
+                table = table.column(Column::auto().at_least(60.0));
+            }
+            table
+                .column(Column::auto().at_least(40.0))
+                .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                .header(20.0, |mut header| {
+                    let agenda_headers = chosen.iter().map(|k| agendas[*k].0.name.as_str());
+                    for col_header in ["Unit", "Kills", "Destroyed", "Marked for Greatness"].into_iter().chain(agenda_headers).chain(["XP"]) {
+                        header.col(|ui| {
+                            ui.strong(RichText::new(col_header).size(15.0));
+                        });
+                    }
+                })
+                .body(|mut body| {
+                    for (i, participant) in wizard.participants.iter_mut().enumerate() {
+                        body.row(20.0, |mut row| {
+                            row.col(|ui| {
+                                ui.checkbox(&mut participant.took_part, &participant.unit.name);
+                            });
+                            if !participant.took_part {
+                                return;
+                            }
+                            row.col(|ui| {
+                                select_drag_value_with_range_on_tab(&mut participant.unit.kills, 0..=99, ui);
+                            });
+                            row.col(|ui| {
+                                ui.checkbox(&mut participant.unit.destroyed, "");
+                            });
+                            row.col(|ui| {
+                                if ui.radio(participant.unit.marked_for_greatness, "").clicked() {
+                                    marked = Some(i);
+                                }
+                            });
+                            for k in chosen.iter() {
+                                row.col(|ui| {
+                                    select_drag_value_with_range_on_tab(&mut participant.tallies[*k], 0..=99, ui);
+                                });
+                            }
+                            row.col(|ui| {
+                                ui.label(format!("+{}", participant.exp(agendas)));
+                            });
+                        });
+                    }
+                });
+        });
+        // only one unit can be marked for greatness
+        if let Some(i) = marked {
+            for (j, participant) in wizard.participants.iter_mut().enumerate() {
+                participant.unit.marked_for_greatness = i == j && !participant.unit.marked_for_greatness;
+            }
+        }
+
+        if rolled {
+            ui.separator();
+            ui.heading("Out of Action Tests");
+            let mut any = false;
+            for (i, participant) in wizard.participants.iter_mut().enumerate() {
+                let Some((roll, outcome)) = &mut participant.unit.out_of_action else {continue};
+                any = true;
+                ui.horizontal(|ui| {
+                    ui.label(format!("{}: rolled {}", participant.unit.name, roll));
+                    if *outcome == OutOfAction::Passed {
+                        ui.label("passed");
+                        return;
+                    }
+                    ui.label("failed,");
+                    ComboBox::from_id_salt(("out_of_action", i))
+                        .selected_text(outcome.to_string())
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(outcome, OutOfAction::BattleScar, "Battle Scar");
+                            for honour in participant.honours.iter() {
+                                ui.selectable_value(outcome, OutOfAction::DevastatingBlow(honour.clone()), format!("Devastating Blow ({})", honour));
+                            }
+                        });
+                });
+            }
+            if !any {
+                ui.label("No units were destroyed");
+            }
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("Cancel").clicked() {
+                *result = Some(false);
+            }
+            if !rolled {
+                if ui.button("Roll Out of Action Tests").clicked() {
+                    wizard.roll_out_of_action();
+                }
+            } else if ui.button("Record").clicked() {
+                *result = Some(true);
+            }
+        });
+    });
+}
+
+pub fn rank_up_window(ctx: &Context, result: &mut Option<bool>, rank_up: &mut RankUp, honours: u32, limit: u32) {
+    egui::Window::new("Rank Up")
+    .collapsible(false)
+    .resizable(false)
+    .show(ctx, |ui| {
+        ui.label(format!("{} has reached {}. Choose a battle honour.", rank_up.name, rank_up.rank.to_string()));
+        let full = honours >= limit;
+        if full {
+            ui.label(RichText::new(format!("Already has {} of {} battle honours allowed at {}", honours, limit, rank_up.rank.to_string())).color(Color32::RED));
+        } else {
+            ui.label(format!("Battle honours: {} / {}", honours, limit));
+        }
+
+        ui.add_enabled_ui(!full, |ui| {
+            ui.horizontal(|ui| {
+                if ui.selectable_label(matches!(rank_up.honour, CrusadeUpgrade::BattleTrait(_)), "Battle Trait").clicked() && !matches!(rank_up.honour, CrusadeUpgrade::BattleTrait(_)) {
+                    rank_up.honour = CrusadeUpgrade::BattleTrait(Ability::default());
+                }
+                if ui.selectable_label(matches!(rank_up.honour, CrusadeUpgrade::WeaponMod(_)), "Weapon Mod").clicked() && !matches!(rank_up.honour, CrusadeUpgrade::WeaponMod(_)) {
+                    rank_up.honour = CrusadeUpgrade::WeaponMod(WeaponMod::default());
+                }
+                if ui.selectable_label(matches!(rank_up.honour, CrusadeUpgrade::Relic(..)), "Relic").clicked() && !matches!(rank_up.honour, CrusadeUpgrade::Relic(..)) {
+                    rank_up.honour = CrusadeUpgrade::Relic(Ability::default(), RelicTier::default());
+                }
+            });
+            match &mut rank_up.honour {
+                CrusadeUpgrade::WeaponMod(weapon_mod) => {
+                    ui.horizontal(|ui| {
+                        ui.label("Name:");
+                        select_text_on_tab(weapon_mod.name.len(), egui::TextEdit::singleline(&mut weapon_mod.name), ui);
+                    });
+                    ui.horizontal(|ui| {
+                        weapon_mod.combo_boxes(ui, 60);
+                        weapon_mod.target_select(ui, 62, &rank_up.weapons);
+                    });
+                },
+                CrusadeUpgrade::BattleTrait(ability) | CrusadeUpgrade::Relic(ability, _) | CrusadeUpgrade::Enhancement(ability, _) | CrusadeUpgrade::BattleScar(ability, _) => {
+                    ui.horizontal(|ui| {
+                        ui.label("Name:");
+                        select_text_on_tab(ability.name.len(), egui::TextEdit::singleline(&mut ability.name), ui);
+                    });
+                    ui.label("Description:");
+                    select_text_on_tab(ability.description.len(), egui::TextEdit::multiline(&mut ability.description), ui);
+                },
+            }
+        });
+
+        ui.horizontal(|ui| {
+            if ui.button("Skip").clicked() {
+                *result = Some(false);
+            }
+            if ui.add_enabled(!full && !rank_up.honour.name().is_empty(), egui::Button::new("Add Honour")).clicked() {
+                *result = Some(true);
+            }
+        });
+    });
+}
+
+/// `tables` are the roll tables the scar can be rolled on
+pub fn battle_scar_window(ctx: &Context, result: &mut Option<bool>, pending: &mut PendingScar, tables: &[&RollTable]) {
+    egui::Window::new("Battle Scar")
+    .collapsible(false)
+    .resizable(false)
+    .show(ctx, |ui| {
+        ui.label(format!("{} failed its out of action test. Choose a battle scar.", pending.name));
+
+        if !tables.is_empty() {
+            ui.horizontal(|ui| {
+                let id = ui.id().with("scar_table");
+                let mut selected = ui.data(|data| data.get_temp::<usize>(id)).unwrap_or(0).min(tables.len() - 1);
+                ui.label("Roll Table:");
+                ComboBox::from_id_salt(id)
+                    .selected_text(&tables[selected].name)
+                    .show_ui(ui, |ui| {
+                        for (i, table) in tables.iter().enumerate() {
+                            ui.selectable_value(&mut selected, i, &table.name);
+                        }
+                    });
+                ui.data_mut(|data| data.insert_temp(id, selected));
+                if ui.button("Roll").clicked() {
+                    pending.roll(tables[selected]);
+                }
+            });
+            if let Some(record) = &pending.roll {
+                ui.label(record.to_string());
+            }
+        }
 
+        ui.horizontal(|ui| {
+            ui.label("Name:");
+            select_text_on_tab(pending.scar.name.len(), egui::TextEdit::singleline(&mut pending.scar.name), ui);
+        });
+        ScarEffect::checkboxes(&mut pending.effects, ui);
+        ui.label("Description:");
+        select_text_on_tab(pending.scar.description.len(), egui::TextEdit::multiline(&mut pending.scar.description), ui);
 
+        ui.horizontal(|ui| {
+            if ui.button("Skip").clicked() {
+                *result = Some(false);
+            }
+            if ui.add_enabled(!pending.scar.name.is_empty(), egui::Button::new("Add Scar")).clicked() {
+                *result = Some(true);
+            }
+        });
     });
 }
 
+/// `problem` is why the requisition can't be bought, if anything
+pub fn requisition_window(ctx: &Context, result: &mut Option<bool>, wizard: &mut RequisitionWizard, library: &WeaponLibrary, requisition_points: u32, problem: Option<String>) {
+    egui::Window::new("Requisition")
+    .collapsible(false)
+    .resizable(false)
+    .show(ctx, |ui| {
+        ui.label(format!("Requisition Points: {}", requisition_points));
+        wizard.requisition.combo_box(ui, "requisition");
+        ui.label(wizard.requisition.description());
+        ui.separator();
+
+        if wizard.requisition.targets_unit() {
+            if wizard.units.is_empty() {
+                ui.label("No crusade units in the working folder");
+            } else {
+                let selected = wizard.units.get(wizard.unit).map(|unit| unit.name.clone()).unwrap_or_default();
+                let previous = wizard.unit;
+                ComboBox::from_id_salt("requisition_unit")
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        for (i, unit) in wizard.units.iter().enumerate() {
+                            ui.selectable_value(&mut wizard.unit, i, &unit.name);
+                        }
+                    });
+                if previous != wizard.unit {
+                    wizard.scar = 0;
+                    wizard.weapon = 0;
+                }
+            }
+        }
+
+        let unit = wizard.units.get(wizard.unit);
+        match wizard.requisition {
+            Requisition::IncreaseSupplyLimit => {
+                ui.horizontal(|ui| {
+                    ui.label("Supply:");
+                    select_drag_value_with_range_on_tab(&mut wizard.supply, 0..=1000, ui);
+                });
+            },
+            Requisition::RepairAndRecuperate => {
+                if let Some(unit) = unit {
+                    ComboBox::from_id_salt("requisition_scar")
+                        .selected_text(unit.scars.get(wizard.scar).cloned().unwrap_or_default())
+                        .show_ui(ui, |ui| {
+                            for (i, scar) in unit.scars.iter().enumerate() {
+                                ui.selectable_value(&mut wizard.scar, i, scar);
+                            }
+                        });
+                }
+            },
+            Requisition::RearmAndResupply => {
+                if let Some(unit) = unit {
+                    ui.horizontal(|ui| {
+                        ui.label("Replace:");
+                        let selected = unit.weapons.iter().find(|(uid, _)| *uid == wizard.weapon).map(|(_, name)| name.clone()).unwrap_or_default();
+                        ComboBox::from_id_salt("requisition_weapon")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                for (uid, name) in unit.weapons.iter() {
+                                    ui.selectable_value(&mut wizard.weapon, *uid, name);
+                                }
+                            });
+                        ui.label("With:");
+                        ComboBox::from_id_salt("requisition_replacement")
+                            .selected_text(&wizard.replacement)
+                            .show_ui(ui, |ui| {
+                                for (id, weapon) in library.weapons.iter() {
+                                    ui.selectable_value(&mut wizard.replacement, id.clone(), id).on_hover_text(&weapon.name);
+                                }
+                            });
+                    });
+                }
+            },
+            Requisition::FreshRecruits => {
+                ui.horizontal(|ui| {
+                    ui.label("Points Added:");
+                    select_drag_value_with_range_on_tab(&mut wizard.points, 0..=1000, ui);
+                });
+            },
+            Requisition::RenownedHeroes => {
+                ui.horizontal(|ui| {
+                    ui.label("Relic:");
+                    select_text_on_tab(wizard.relic.name.len(), egui::TextEdit::singleline(&mut wizard.relic.name), ui);
+                    wizard.tier.combo_box(ui, 61);
+                });
+                ui.label("Description:");
+                select_text_on_tab(wizard.relic.description.len(), egui::TextEdit::multiline(&mut wizard.relic.description), ui);
+            },
+        }
+
+        ui.separator();
+        ui.label(format!("Cost: {} RP", wizard.cost()));
+        if let Some(problem) = &problem {
+            ui.label(RichText::new(problem).color(Color32::RED));
+        }
+        ui.horizontal(|ui| {
+            if ui.button("Cancel").clicked() {
+                *result = Some(false);
+            }
+            if ui.add_enabled(problem.is_none(), egui::Button::new("Buy")).clicked() {
+                *result = Some(true);
+            }
+        });
+    });
+}
 
 pub fn delete_folder_window(ctx: &Context, result: &mut Option<bool>) {
     egui::Window::new("Delete Subfolder?")
@@ -0,0 +1,50 @@
+use egui::{CollapsingHeader, Context, RichText};
+
+use crate::data::{Problem, Severity};
+
+use super::{datasheet_app::OpenFile, DatasheetApp};
+
+
+
+pub fn problems_panel(app: &mut DatasheetApp, ctx: &Context) {
+    let mut open = None;
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        egui::ScrollArea::both().show(ui, |ui| {
+            ui.heading(RichText::new("Problems").size(30.0));
+
+            let units: Vec<((usize, usize), Vec<Problem>)> = app.working_dir.iter().enumerate()
+                .flat_map(|(i, folder)| folder.units.iter().enumerate().map(move |(j, unit)| ((i, j), unit.validate())))
+                .filter(|(_, problems)| !problems.is_empty())
+                .collect();
+            let count = |severity: Severity| units.iter().flat_map(|(_, problems)| problems.iter()).filter(|problem| problem.severity == severity).count();
+
+            if units.is_empty() {
+                ui.label("No problems found in saved units");
+            } else {
+                ui.label(format!("{} errors, {} warnings in saved units", count(Severity::Error), count(Severity::Warning)));
+            }
+            ui.separator();
+
+            for ((i, j), problems) in units.iter() {
+                let worst = problems[0].severity;
+                let title = format!("{} / {} ({})", app.working_dir[*i].name, app.working_dir[*i].units[*j].name, problems.len());
+                CollapsingHeader::new(RichText::new(title).color(worst.colour()))
+                    .id_salt((i, j))
+                    .default_open(worst == Severity::Error)
+                    .show(ui, |ui| {
+                        if ui.button("Open").clicked() {
+                            open = Some((*i, *j));
+                        }
+                        for problem in problems.iter() {
+                            ui.label(RichText::new(problem.to_string()).color(problem.severity.colour()));
+                        }
+                    });
+            }
+        });
+    });
+
+    if let Some(index) = open {
+        app.open_file(OpenFile::Index(index));
+    }
+}
@@ -1,13 +1,14 @@
 use egui::{Color32, Context, Rect, RichText, ScrollArea, Ui};
 use egui_extras::{Column, TableBuilder};
 
-use crate::{app::helper, data::{CrusadeUpgrade, Unit}};
+use crate::{app::{helper, order_of_battle::battle_unit_summary}, data::{BattleRecord, CrusadeUpgrade, Glossary, HistoryEvent, ScarEffect, Unit}, export::{unit_to_markdown, unit_to_text, unit_to_tts}};
 
 use super::DatasheetAppSettings;
 
 
 
-pub fn read_unit(settings: &DatasheetAppSettings, dark_mode: bool, ctx: &Context, unit: &Unit) {
+/// `battle_log` is the log of the crusade force and `reference` the "folder/filename" of the unit, used to show its battle history
+pub fn read_unit(settings: &DatasheetAppSettings, glossary: &Glossary, battle_log: &[BattleRecord], reference: &str, dark_mode: bool, ctx: &Context, unit: &Unit) {
     // let index = match app.open_files[app.selected_file] {
     //     OpenFile::Index(index) => index,
     //     _ => return
@@ -35,43 +36,49 @@ pub fn read_unit(settings: &DatasheetAppSettings, dark_mode: bool, ctx: &Context
         };
 
         
-        let show_stat_func = |ui: &mut Ui, stat: String| {
+        let show_stat_func = |ui: &mut Ui, original: String, stat: String| {
             frame.show(ui, |ui| {
                 ui.set_width(50.0);
                 ui.set_height(50.0);
                 ui.centered_and_justified(|ui| {
-                    ui.label(egui::RichText::new(stat).size(20.0).color(Color32::WHITE));
+                    if original == stat {
+                        ui.label(egui::RichText::new(stat).size(20.0).color(Color32::WHITE));
+                    } else {
+                        ui.vertical_centered(|ui| {
+                            ui.label(egui::RichText::new(original).size(11.0).strikethrough().color(Color32::LIGHT_GRAY));
+                            ui.label(egui::RichText::new(stat).size(18.0).color(Color32::WHITE));
+                        });
+                    }
                 });
             });
         };
 
         ui.horizontal(|ui| {
             ui.heading(egui::RichText::new(&unit.name).size(30.0));
-            
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button("Copy Text").on_hover_text("Copy as plain text").clicked() {
+                    ui.ctx().copy_text(unit_to_text(unit));
+                }
+                if ui.button("Copy Markdown").on_hover_text("Copy as markdown").clicked() {
+                    ui.ctx().copy_text(unit_to_markdown(unit));
+                }
+                if ui.button("Copy TTS").on_hover_text("Copy as a Tabletop Simulator description").clicked() {
+                    ui.ctx().copy_text(unit_to_tts(unit));
+                }
+            });
         });
         
         egui::Grid::new("statsgrid").show(ui, |ui| {
-            show_stat_name_func(ui, "M");
-            show_stat_name_func(ui, "T");
-            show_stat_name_func(ui, "Sv");
-            if unit.stats.invuln.is_some() {
-                show_stat_name_func(ui, "Inv");
+            let stats = unit.stat_values();
+            for (name, _, _) in stats.iter() {
+                show_stat_name_func(ui, name);
             }
-            show_stat_name_func(ui, "W");
-            show_stat_name_func(ui, "ld");
-            show_stat_name_func(ui, "OC");
             ui.end_row();
-            show_stat_func(ui, format!("{}\"", unit.get_movement()));
-            show_stat_func(ui, unit.stats.toughness.to_string());
-            show_stat_func(ui, format!("{}+", unit.stats.save));
-            if let Some(invuln) = unit.stats.invuln {
-                show_stat_func(ui, format!("{}++", invuln));
+            for (_, original, shown) in stats {
+                show_stat_func(ui, original, shown);
             }
-            show_stat_func(ui, unit.stats.wounds.to_string());
-            show_stat_func(ui, format!("{}+", unit.stats.leadership));
-            show_stat_func(ui, unit.stats.oc.to_string());
             if unit.crusade_unit {
-                ui.label(egui::RichText::new(format!("{}({} exp)", unit.crusade_data.rank.to_string(), unit.crusade_data.exp)));
+                ui.label(egui::RichText::new(format!("{}({} exp, {} CP)", unit.crusade_data.rank.to_string(), unit.crusade_data.exp, unit.crusade_data.crusade_points())));
             }
         }); 
     });
@@ -92,10 +99,13 @@ pub fn read_unit(settings: &DatasheetAppSettings, dark_mode: bool, ctx: &Context
                 ui.label(RichText::new("CORE: "));
                 let last = unit.core_abilities.len().checked_sub(1).unwrap_or(0);
                 for (i, ability) in unit.core_abilities.iter().enumerate() {
-                    if i < last{
-                        ui.label(RichText::new(format!("{},", ability.to_render_string())).strong());
+                    let label = if i < last{
+                        ui.label(RichText::new(format!("{},", ability.to_render_string())).strong())
                     } else {
-                        ui.label(RichText::new(ability.to_render_string()).strong());
+                        ui.label(RichText::new(ability.to_render_string()).strong())
+                    };
+                    if let Some(description) = glossary.describe_core(ability) {
+                        label.on_hover_text(description);
                     }
                 }
             });
@@ -105,7 +115,10 @@ pub fn read_unit(settings: &DatasheetAppSettings, dark_mode: bool, ctx: &Context
         if let Some(ability) = &unit.faction_ability {
             ui.horizontal_wrapped(|ui| {
                 ui.label(RichText::new("FACTION: "));
-                ui.label(RichText::new(ability).strong());
+                let label = ui.label(RichText::new(ability).strong());
+                if let Some(description) = glossary.describe(ability) {
+                    label.on_hover_text(description);
+                }
             });
             ui.separator();
         }
@@ -119,6 +132,14 @@ pub fn read_unit(settings: &DatasheetAppSettings, dark_mode: bool, ctx: &Context
                 });
             }
 
+            if let Some(assigned) = &unit.enhancement {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(RichText::new(format!("ENHANCEMENT - {} ({} pts):", assigned.enhancement.name.to_uppercase(), assigned.enhancement.points)).strong())
+                        .on_hover_text(&assigned.detachment);
+                    ui.label(RichText::new(&assigned.enhancement.description));
+                });
+            }
+
             let mut has_crusade_ability = false;
             if unit.crusade_unit {
                 for upgrade in unit.crusade_data.upgrades.iter() {
@@ -130,23 +151,23 @@ pub fn read_unit(settings: &DatasheetAppSettings, dark_mode: bool, ctx: &Context
                             });
                             has_crusade_ability = true;
                         }, 
-                        CrusadeUpgrade::Relic(ability) => {
+                        CrusadeUpgrade::Relic(ability, tier) => {
                             ui.horizontal_wrapped(|ui| {
-                                ui.label(RichText::new(format!("{}:", ability.name.to_uppercase())).strong());
+                                ui.label(RichText::new(format!("{} ({} RELIC):", ability.name.to_uppercase(), tier.to_string().to_uppercase())).strong());
                                 ui.label(RichText::new(&ability.description));
                             });
                             has_crusade_ability = true;
                         },
-                        CrusadeUpgrade::Enhancement(ability) => {
+                        CrusadeUpgrade::Enhancement(ability, _) => {
                             ui.horizontal_wrapped(|ui| {
                                 ui.label(RichText::new(format!("{}:", ability.name.to_uppercase())).strong());
                                 ui.label(RichText::new(&ability.description));
                             });
                             has_crusade_ability = true;
                         },
-                        CrusadeUpgrade::BattleScar(ability) => {
+                        CrusadeUpgrade::BattleScar(ability, effects) => {
                             ui.horizontal_wrapped(|ui| {
-                                ui.label(RichText::new(format!("{}:", ability.name.to_uppercase())).strong());
+                                ui.label(RichText::new(format!("{}{}:", ability.name.to_uppercase(), ScarEffect::list(effects))).strong());
                                 ui.label(RichText::new(&ability.description));
                             });
                             has_crusade_ability = true;
@@ -157,7 +178,7 @@ pub fn read_unit(settings: &DatasheetAppSettings, dark_mode: bool, ctx: &Context
             }
             
 
-            if unit.unique_abilities.len() != 0 || has_crusade_ability {ui.separator();}
+            if unit.unique_abilities.len() != 0 || unit.enhancement.is_some() || has_crusade_ability {ui.separator();}
 
         });
 
@@ -218,7 +239,7 @@ pub fn read_unit(settings: &DatasheetAppSettings, dark_mode: bool, ctx: &Context
                     })
                     .body(|mut body| {
                         for (weapon, count) in ranged.iter() {
-                            helper::draw_weapon_row(weapon, *count, &mut body, settings.keyword_colour);
+                            helper::draw_weapon_row(weapon, unit.original_weapon(weapon), *count, &mut body, settings.keyword_colour, glossary);
                         }
                     });
                 ui.separator();
@@ -247,7 +268,41 @@ pub fn read_unit(settings: &DatasheetAppSettings, dark_mode: bool, ctx: &Context
                     })
                     .body(|mut body| {
                         for (weapon, count) in melee.iter() {
-                            helper::draw_weapon_row(weapon, *count, &mut body, settings.keyword_colour);
+                            helper::draw_weapon_row(weapon, unit.original_weapon(weapon), *count, &mut body, settings.keyword_colour, glossary);
+                        }
+                    });
+            }
+
+            let history: Vec<(&BattleRecord, _)> = battle_log.iter().filter_map(|record| record.unit(reference).map(|battle_unit| (record, battle_unit))).collect();
+            if unit.crusade_unit && !history.is_empty() {
+                ui.separator();
+                egui::CollapsingHeader::new(format!("Battle History ({})", history.len()))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        for (record, battle_unit) in history.iter().rev() {
+                            ui.label(RichText::new(format!("{} - {} vs {}", record.date, record.result.to_string(), if record.opponent.is_empty() {"Unknown"} else {&record.opponent})).strong());
+                            ui.label(battle_unit_summary(battle_unit));
+                        }
+                    });
+            }
+
+            if unit.crusade_unit && !unit.crusade_data.history.is_empty() {
+                ui.separator();
+                ui.label(unit.crusade_data.history_stats().to_string());
+                egui::CollapsingHeader::new(format!("Timeline ({})", unit.crusade_data.history.len()))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        let mut last_date = None;
+                        for entry in unit.crusade_data.history.iter().rev() {
+                            if last_date != Some(&entry.date) {
+                                ui.label(RichText::new(&entry.date).strong());
+                                last_date = Some(&entry.date);
+                            }
+                            let colour = match entry.event {
+                                HistoryEvent::BattleScar(_) | HistoryEvent::UpgradeRemoved(..) => Color32::LIGHT_RED,
+                                _ => ui.visuals().text_color()
+                            };
+                            ui.label(RichText::new(format!("  {}", entry.event.to_string())).color(colour));
                         }
                     });
             }
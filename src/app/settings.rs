@@ -1,6 +1,8 @@
 use egui::Color32;
 use serde::{Deserialize, Serialize};
 
+use crate::{data::RankThresholds, export::{default_print_profiles, PrintProfile}};
+
 
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -15,8 +17,23 @@ pub struct  DatasheetAppSettings {
     pub default_faction_keyword: String,
     #[serde(default)]
     pub dark_mode: bool,
+    #[serde(default = "default_print_profiles")]
+    pub print_profiles: Vec<PrintProfile>,
+    #[serde(default)]
+    pub selected_print_profile: usize,
+    #[serde(default = "default_image_dpi")]
+    pub image_dpi: u32,
+    #[serde(default = "default_svg_embed_fonts")]
+    pub svg_embed_fonts: bool,
+    #[serde(default)]
+    pub rules_reference: bool,
+    #[serde(default)]
+    pub rank_thresholds: RankThresholds,
 }
 
+fn default_image_dpi() -> u32 {300}
+fn default_svg_embed_fonts() -> bool {true}
+
 
 impl DatasheetAppSettings {
     pub fn save(&self, storage: &mut dyn eframe::Storage) {
@@ -25,8 +42,18 @@ impl DatasheetAppSettings {
         storage.set_string("Default_Faction_Ability", self.default_faction_ability.clone());
         storage.set_string("Default_Faction_Keyword", self.default_faction_keyword.clone());
         storage.set_string("Keyword_Colour", color32_to_string(self.keyword_colour));
+        storage.set_string("Print_Profiles", ron::to_string(&self.print_profiles).unwrap_or("".to_string()));
+        storage.set_string("Selected_Print_Profile", self.selected_print_profile.to_string());
+        storage.set_string("Image_DPI", self.image_dpi.to_string());
+        storage.set_string("SVG_Embed_Fonts", self.svg_embed_fonts.to_string());
+        storage.set_string("Rules_Reference", self.rules_reference.to_string());
+        storage.set_string("Rank_Thresholds", ron::to_string(&self.rank_thresholds).unwrap_or("".to_string()));
         storage.flush();
     }
+
+    pub fn print_profile(&self) -> PrintProfile {
+        self.print_profiles.get(self.selected_print_profile).cloned().unwrap_or_default()
+    }
 }
 
 impl Default for DatasheetAppSettings{
@@ -37,6 +64,12 @@ impl Default for DatasheetAppSettings{
             default_faction_ability: "".to_string(),
             default_faction_keyword: "".to_string(),
             dark_mode: true,
+            print_profiles: default_print_profiles(),
+            selected_print_profile: 0,
+            image_dpi: default_image_dpi(),
+            svg_embed_fonts: default_svg_embed_fonts(),
+            rules_reference: false,
+            rank_thresholds: RankThresholds::default(),
         }
     }
 }
@@ -1,16 +1,17 @@
 use egui::{ComboBox, Ui};
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 
 use crate::data::VariableValue;
 
 
-#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, JsonSchema)]
 pub struct Ability {
     pub name: String,
     pub description: String,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, JsonSchema)]
 pub enum CoreAbility {
     #[default]
     None,
@@ -55,6 +56,29 @@ impl CoreAbility {
         }
     }
 
+    /// Parses a core ability as printed on a datasheet, e.g. "Scouts 6\"" or "Feel No Pain 5+"
+    pub fn from_string(string: &str) -> Option<Self> {
+        let text = string.trim().to_uppercase();
+        let (name, value) = match text.rsplit_once(' ') {
+            Some((name, value)) if value.chars().next().is_some_and(|c| c.is_ascii_digit() || c == 'D') => (name.to_string(), value.trim_end_matches(['+', '"']).to_string()),
+            _ => (text.clone(), "".to_string())
+        };
+
+        match name.as_str() {
+            "DEEP STRIKE" => Some(CoreAbility::DeepStrike),
+            "SCOUTS" => value.parse().ok().map(CoreAbility::Scouts),
+            "LEADER" => Some(CoreAbility::Leader),
+            "INFILTRATORS" => Some(CoreAbility::Infiltrators),
+            "LONE OPERATIVE" => Some(CoreAbility::LoneOp),
+            "FIRING DECK" => value.parse().ok().map(CoreAbility::FiringDeck),
+            "STEALTH" => Some(CoreAbility::Stealth),
+            "FEEL NO PAIN" => value.parse().ok().map(CoreAbility::FeelnoPain),
+            "DEADLY DEMISE" => VariableValue::from_string(&value).ok().map(|val| CoreAbility::DeadlyDemise(val, val.to_string())),
+            "FIGHTS FIRST" => Some(CoreAbility::FightsFirst),
+            _ => None
+        }
+    }
+
     pub fn combo_box(&mut self, ui: &mut Ui, id: usize) {
         ComboBox::from_id_salt(id)
             .selected_text(format!("{}", self.to_string()))
@@ -74,7 +98,7 @@ impl CoreAbility {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, JsonSchema)]
 pub enum WeaponAbility {
     #[default]
     None,
@@ -145,6 +169,54 @@ impl WeaponAbility {
         }
     }
 
+    /// Parses keyword text as printed on a datasheet, e.g. "SUSTAINED HITS D3" or "ANTI-INFANTRY 4+"
+    pub fn from_string(string: &str) -> Self {
+        let text = string.trim().to_uppercase();
+        let (name, value) = match text.rsplit_once(' ') {
+            Some((name, value)) if value.chars().next().is_some_and(|c| c.is_ascii_digit() || c == 'D') => (name.to_string(), Some(value.trim_end_matches('+').to_string())),
+            _ => (text.clone(), None)
+        };
+
+        match (name.as_str(), value) {
+            ("", None) => WeaponAbility::None,
+            ("ASSAULT", None) => WeaponAbility::Assault,
+            ("RAPID FIRE", Some(x)) => match VariableValue::from_string(&x) {
+                Ok(val) => WeaponAbility::RapidFire(val, val.to_string()),
+                Err(_) => WeaponAbility::Custom(string.trim().to_string())
+            },
+            ("IGNORES COVER", None) => WeaponAbility::IgnoresCover,
+            ("TWIN-LINKED", None) => WeaponAbility::TwinLinked,
+            ("PISTOL", None) => WeaponAbility::Pistol,
+            ("TORRENT", None) => WeaponAbility::Torrent,
+            ("LETHAL HITS", None) => WeaponAbility::Lethal,
+            ("LANCE", None) => WeaponAbility::Lance,
+            ("INDIRECT FIRE", None) => WeaponAbility::Indirect,
+            ("PRECISION", None) => WeaponAbility::Precision,
+            ("BLAST", None) => WeaponAbility::Blast,
+            ("MELTA", Some(x)) => match x.parse() {
+                Ok(val) => WeaponAbility::Melta(val),
+                Err(_) => WeaponAbility::Custom(string.trim().to_string())
+            },
+            ("HEAVY", None) => WeaponAbility::Heavy,
+            ("HAZARDOUS", None) => WeaponAbility::Hazardous,
+            ("DEVASTATING WOUNDS", None) => WeaponAbility::Dev,
+            ("SUSTAINED HITS", Some(x)) => match VariableValue::from_string(&x) {
+                Ok(val) => WeaponAbility::Sustained(val, val.to_string()),
+                Err(_) => WeaponAbility::Custom(string.trim().to_string())
+            },
+            ("EXTRA ATTACKS", None) => WeaponAbility::ExtraAttacks,
+            ("ONE SHOT", None) => WeaponAbility::OneShot,
+            ("PRECISE", None) => WeaponAbility::Precise,
+            ("PSYCHIC", None) => WeaponAbility::Pyschic,
+            ("CONVERSION", None) => WeaponAbility::Conversion,
+            (anti, Some(x)) if anti.starts_with("ANTI-") => match x.parse() {
+                Ok(val) => WeaponAbility::AntiX(anti.replacen("ANTI-", "", 1), val),
+                Err(_) => WeaponAbility::Custom(string.trim().to_string())
+            },
+            _ => WeaponAbility::Custom(string.trim().to_string())
+        }
+    }
+
     pub fn combo_box_ranged(&mut self, ui: &mut Ui, id: usize) {
         ComboBox::from_id_salt(id)
             .selected_text(format!("{}", self.to_string()))
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+
+
+/// An agenda units gain experience from by making tallies during a battle
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
+pub struct Agenda {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_exp_per_tally")]
+    pub exp_per_tally: u32,
+    /// the most experience a unit can gain from the agenda in one battle, 0 for no limit
+    #[serde(default)]
+    pub max_exp: u32,
+}
+
+fn default_exp_per_tally() -> u32 {1}
+
+impl Default for Agenda {
+    fn default() -> Self {
+        Self {
+            name: "".to_string(),
+            description: "".to_string(),
+            exp_per_tally: default_exp_per_tally(),
+            max_exp: 0,
+        }
+    }
+}
+
+impl Agenda {
+    pub fn exp(&self, tally: u32) -> u32 {
+        let exp = tally * self.exp_per_tally;
+        if self.max_exp > 0 {exp.min(self.max_exp)} else {exp}
+    }
+}
+
+
+/// The agendas of a working folder. Stored in AGENDAS.ron next to SETTINGS.ron
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, JsonSchema)]
+pub struct Agendas {
+    #[serde(default)]
+    pub agendas: Vec<Agenda>,
+}
+
+
+/// A unit's tallies for one agenda in a battle, and the experience they earned
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, JsonSchema)]
+pub struct AgendaTally {
+    #[serde(default)]
+    pub agenda: String,
+    #[serde(default)]
+    pub tally: u32,
+    #[serde(default)]
+    pub exp: u32,
+}
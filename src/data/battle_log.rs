@@ -0,0 +1,314 @@
+use egui::{ComboBox, Ui};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use super::{Ability, Agenda, AgendaTally, Agendas, CrusadeUpgrade, HistoryEntry, HistoryEvent, RollRecord, RollTable, ScarEffect, Unit};
+
+
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default, JsonSchema)]
+pub enum BattleResult {
+    Victory,
+    #[default]
+    Draw,
+    Defeat
+}
+
+impl BattleResult {
+    pub fn to_string(&self) -> &str {
+        match self {
+            BattleResult::Victory => "Victory",
+            BattleResult::Draw => "Draw",
+            BattleResult::Defeat => "Defeat",
+        }
+    }
+
+    pub fn combo_box(&mut self, ui: &mut Ui, id: &str) {
+        ComboBox::from_id_salt(id)
+            .selected_text(self.to_string())
+            .show_ui(ui, |ui| {
+                ui.selectable_value(self, BattleResult::Victory, "Victory");
+                ui.selectable_value(self, BattleResult::Draw, "Draw");
+                ui.selectable_value(self, BattleResult::Defeat, "Defeat");
+            });
+    }
+}
+
+
+/// What happened to a unit that failed its out of action test
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
+pub enum OutOfAction {
+    Passed,
+    BattleScar,
+    /// the name of the battle honour that was lost
+    DevastatingBlow(String),
+}
+
+impl OutOfAction {
+    pub fn to_string(&self) -> String {
+        match self {
+            OutOfAction::Passed => "Passed".to_string(),
+            OutOfAction::BattleScar => "Battle Scar".to_string(),
+            OutOfAction::DevastatingBlow(honour) => format!("Devastating Blow (lost {})", honour),
+        }
+    }
+}
+
+
+/// How one unit did in a battle. `unit` is the "folder/filename" of the unit
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default, JsonSchema)]
+pub struct BattleUnit {
+    pub unit: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub kills: u32,
+    #[serde(default)]
+    pub destroyed: bool,
+    #[serde(default)]
+    pub marked_for_greatness: bool,
+    #[serde(default)]
+    pub exp_gained: u32,
+    /// the d6 rolled and its outcome, for units that were destroyed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub out_of_action: Option<(u32, OutOfAction)>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tallies: Vec<AgendaTally>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default, JsonSchema)]
+pub struct BattleRecord {
+    #[serde(default)]
+    pub date: String,
+    #[serde(default)]
+    pub opponent: String,
+    #[serde(default)]
+    pub mission: String,
+    #[serde(default)]
+    pub result: BattleResult,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub units: Vec<BattleUnit>,
+    /// names of the agendas chosen for the battle
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agendas: Vec<String>,
+}
+
+impl BattleRecord {
+    pub fn unit(&self, reference: &str) -> Option<&BattleUnit> {
+        self.units.iter().find(|unit| unit.unit == reference)
+    }
+}
+
+
+/// 1 for taking part, 3 for being Marked for Greatness and 1 for every third kill the unit has made
+pub fn battle_exp(previous_kills: u32, kills: u32, marked_for_greatness: bool) -> u32 {
+    let kill_exp = (previous_kills + kills) / 3 - previous_kills / 3;
+    1 + if marked_for_greatness {3} else {0} + kill_exp
+}
+
+/// Adds a unit's part in a battle to its crusade data and history
+pub fn apply_battle(unit: &mut Unit, battle_unit: &BattleUnit, record: &BattleRecord) {
+    unit.crusade_data.history.push(HistoryEntry {
+        date: record.date.clone(),
+        event: HistoryEvent::Battle {
+            opponent: record.opponent.clone(),
+            result: record.result,
+            kills: battle_unit.kills,
+            destroyed: battle_unit.destroyed,
+            marked_for_greatness: battle_unit.marked_for_greatness,
+        },
+    });
+    unit.crusade_data.exp += battle_unit.exp_gained;
+    unit.crusade_data.kills += battle_unit.kills;
+    // a battle scar is chosen afterwards, see PendingScar
+    if let Some((_, OutOfAction::DevastatingBlow(honour))) = &battle_unit.out_of_action {
+        if let Some(i) = unit.crusade_data.upgrades.iter().position(|upgrade| !matches!(upgrade, CrusadeUpgrade::BattleScar(..)) && &upgrade.name() == honour) {
+            unit.crusade_data.upgrades.remove(i);
+        }
+    }
+}
+
+/// A battle scar to choose for a unit that failed its out of action test. `unit` is the "folder/filename" of the unit
+pub struct PendingScar {
+    pub unit: String,
+    pub name: String,
+    pub scar: Ability,
+    pub effects: Vec<ScarEffect>,
+    /// the roll the scar came from, if it was rolled on a table
+    pub roll: Option<RollRecord>,
+}
+
+impl PendingScar {
+    pub fn new(unit: String, name: String) -> Self {
+        Self {
+            unit,
+            name,
+            scar: Ability::default(),
+            effects: Vec::new(),
+            roll: None,
+        }
+    }
+
+    /// Rolls the scar on a table, replacing anything chosen so far
+    pub fn roll(&mut self, table: &RollTable) {
+        let (record, upgrade) = table.roll(rand::random());
+        if let CrusadeUpgrade::BattleScar(scar, effects) = upgrade {
+            self.scar = scar;
+            self.effects = effects;
+        } else {
+            self.scar = Ability {name: upgrade.name(), ..Default::default()};
+            self.effects = Vec::new();
+        }
+        self.roll = Some(record);
+    }
+}
+
+
+/// One row of the record battle window
+pub struct BattleParticipant {
+    pub unit: BattleUnit,
+    pub took_part: bool,
+    pub previous_kills: u32,
+    /// names of the battle honours the unit could lose to a devastating blow
+    pub honours: Vec<String>,
+    /// tallies for each of the wizard's agendas
+    pub tallies: Vec<u32>,
+}
+
+impl BattleParticipant {
+    /// The experience the unit gains from the battle and the chosen agendas
+    pub fn exp(&self, agendas: &[(Agenda, bool)]) -> u32 {
+        battle_exp(self.previous_kills, self.unit.kills, self.unit.marked_for_greatness)
+            + agenda_tallies(agendas, &self.tallies).iter().map(|tally| tally.exp).sum::<u32>()
+    }
+}
+
+/// The tallies made on each chosen agenda, skipping agendas with none
+pub fn agenda_tallies(agendas: &[(Agenda, bool)], tallies: &[u32]) -> Vec<AgendaTally> {
+    agendas.iter().zip(tallies.iter())
+        .filter(|((_, chosen), tally)| *chosen && **tally > 0)
+        .map(|((agenda, _), tally)| AgendaTally {
+            agenda: agenda.name.clone(),
+            tally: *tally,
+            exp: agenda.exp(*tally),
+        })
+        .collect()
+}
+
+/// The state of the record battle window
+pub struct BattleWizard {
+    pub record: BattleRecord,
+    pub participants: Vec<BattleParticipant>,
+    /// every agenda of the folder and whether it was chosen for the battle
+    pub agendas: Vec<(Agenda, bool)>,
+    /// whether the out of action tests have been rolled
+    pub rolled: bool,
+}
+
+impl BattleWizard {
+    /// `units` are the crusade units of the working folder with their references
+    pub fn new(units: Vec<(String, &Unit)>, agendas: &Agendas) -> Self {
+        let participants = units.into_iter().map(|(reference, unit)| BattleParticipant {
+            unit: BattleUnit {
+                unit: reference,
+                name: unit.name.clone(),
+                ..Default::default()
+            },
+            took_part: false,
+            previous_kills: unit.crusade_data.kills,
+            honours: unit.crusade_data.upgrades.iter()
+                .filter(|upgrade| !matches!(upgrade, CrusadeUpgrade::BattleScar(..)))
+                .map(|upgrade| upgrade.name())
+                .collect(),
+            tallies: vec![0; agendas.agendas.len()],
+        }).collect();
+
+        Self {
+            record: BattleRecord {
+                date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+                ..Default::default()
+            },
+            participants,
+            agendas: agendas.agendas.iter().map(|agenda| (agenda.clone(), false)).collect(),
+            rolled: false,
+        }
+    }
+
+    /// Rolls a d6 for every destroyed unit, which is passed on a 2+
+    pub fn roll_out_of_action(&mut self) {
+        let mut rng = rand::thread_rng();
+        for participant in self.participants.iter_mut().filter(|participant| participant.took_part) {
+            participant.unit.out_of_action = if participant.unit.destroyed {
+                let roll = rng.gen_range(1..=6);
+                Some((roll, if roll == 1 {OutOfAction::BattleScar} else {OutOfAction::Passed}))
+            } else {None};
+        }
+        self.rolled = true;
+    }
+
+    /// The finished battle record, with the experience each unit gained
+    pub fn finish(&self) -> BattleRecord {
+        let mut record = self.record.clone();
+        record.agendas = self.agendas.iter().filter(|(_, chosen)| *chosen).map(|(agenda, _)| agenda.name.clone()).collect();
+        record.units = self.participants.iter()
+            .filter(|participant| participant.took_part)
+            .map(|participant| BattleUnit {
+                exp_gained: participant.exp(&self.agendas),
+                tallies: agenda_tallies(&self.agendas, &participant.tallies),
+                ..participant.unit.clone()
+            })
+            .collect();
+        record
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_third_kill_counts_across_battles() {
+        assert_eq!(battle_exp(0, 0, false), 1);
+        assert_eq!(battle_exp(0, 2, true), 4);
+        assert_eq!(battle_exp(2, 1, false), 2);
+        assert_eq!(battle_exp(5, 7, false), 4);
+    }
+
+    #[test]
+    fn devastating_blows_remove_the_honour_and_not_a_scar() {
+        let mut unit: Unit = ron::from_str(r#"(
+            crusade_unit: true,
+            crusade_data: (exp: 4, kills: 1, upgrades: [BattleScar((name: "Grim", description: ""), []), BattleTrait((name: "Grim", description: "")), BattleTrait((name: "Swift", description: ""))]),
+        )"#).unwrap();
+        let battle_unit = BattleUnit {kills: 2, destroyed: true, exp_gained: 2, out_of_action: Some((1, OutOfAction::DevastatingBlow("Grim".to_string()))), ..Default::default()};
+        let record = BattleRecord {date: "2024-05-01".to_string(), opponent: "Orks".to_string(), result: BattleResult::Defeat, ..Default::default()};
+        apply_battle(&mut unit, &battle_unit, &record);
+
+        assert_eq!((unit.crusade_data.exp, unit.crusade_data.kills), (6, 3));
+        let names: Vec<String> = unit.crusade_data.upgrades.iter().map(|upgrade| upgrade.name()).collect();
+        assert_eq!(names, vec!["Grim", "Swift"]);
+        assert!(matches!(unit.crusade_data.upgrades[0], CrusadeUpgrade::BattleScar(..)));
+        assert_eq!(unit.crusade_data.history[0].event.to_string(), "Defeat vs Orks, 2 kills, destroyed");
+    }
+
+    #[test]
+    fn agendas_are_capped_and_unchosen_ones_skipped() {
+        let agenda = |name: &str, max_exp| Agenda {name: name.to_string(), exp_per_tally: 2, max_exp, ..Default::default()};
+        let agendas = [(agenda("Capped", 3), true), (agenda("Open", 0), true), (agenda("Skipped", 0), false)];
+        let tallies = agenda_tallies(&agendas, &[4, 0, 5]);
+        assert_eq!(tallies, vec![AgendaTally {agenda: "Capped".to_string(), tally: 4, exp: 3}]);
+
+        let participant = BattleParticipant {
+            unit: BattleUnit {kills: 3, ..Default::default()},
+            took_part: true,
+            previous_kills: 0,
+            honours: Vec::new(),
+            tallies: vec![1, 2, 5],
+        };
+        assert_eq!(participant.exp(&agendas), 2 + 2 + 4);
+    }
+}
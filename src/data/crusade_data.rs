@@ -1,10 +1,11 @@
-use crate::data::{Ability, WeaponReference};
+use crate::data::{Ability, HistoryEntry, RollRecord, WeaponReference};
 use egui::{ComboBox, Ui};
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 
 
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
 pub struct CrusadeUnitData {
     #[serde(default)]
     pub exp: u32,
@@ -14,6 +15,12 @@ pub struct CrusadeUnitData {
     pub upgrades: Vec<CrusadeUpgrade>,
     #[serde(default)]
     pub kills: u32,
+    /// rolls made on the folder's roll tables
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rolls: Vec<RollRecord>,
+    /// everything that has happened to the unit, oldest first. Only ever added to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<HistoryEntry>,
 }
 
 impl Default for CrusadeUnitData {
@@ -22,12 +29,14 @@ impl Default for CrusadeUnitData {
             exp: 0,
             upgrades: Vec::new(),
             kills: 0,
-            rank: CrusadeRank::BattleReady
+            rank: CrusadeRank::BattleReady,
+            rolls: Vec::new(),
+            history: Vec::new(),
         }
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default, JsonSchema)]
 pub enum CrusadeRank {
     #[default]
     BattleReady,
@@ -47,18 +56,102 @@ impl CrusadeRank {
             CrusadeRank::Legendary => "Legendary",
         }
     }
-}
 
+    pub const ALL: [CrusadeRank; 5] = [CrusadeRank::BattleReady, CrusadeRank::Blooded, CrusadeRank::BattleHardended, CrusadeRank::Heroic, CrusadeRank::Legendary];
 
+    /// 0 for Battle-ready up to 4 for Legendary
+    pub fn level(&self) -> usize {
+        CrusadeRank::ALL.iter().position(|rank| rank == self).unwrap_or(0)
+    }
+}
 
 
+/// The experience needed to reach each rank above Battle-ready, and how many battle honours a unit can hold at each rank.
+/// Kept in the folder settings so house rules can change them
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct RankThresholds {
+    #[serde(default = "default_thresholds")]
+    pub character: [u32; 4],
+    #[serde(default = "default_thresholds")]
+    pub non_character: [u32; 4],
+    #[serde(default = "default_honour_limits")]
+    pub honour_limits: [u32; 5],
+}
+
+fn default_thresholds() -> [u32; 4] {[6, 16, 31, 51]}
+fn default_honour_limits() -> [u32; 5] {[0, 1, 2, 3, 4]}
+
+impl Default for RankThresholds {
+    fn default() -> Self {
+        Self {
+            character: default_thresholds(),
+            non_character: default_thresholds(),
+            honour_limits: default_honour_limits(),
+        }
+    }
+}
+
+impl RankThresholds {
+    pub fn rank(&self, exp: u32, character: bool) -> CrusadeRank {
+        let thresholds = if character {&self.character} else {&self.non_character};
+        CrusadeRank::ALL[thresholds.iter().filter(|threshold| exp >= **threshold).count()]
+    }
+
+    pub fn honour_limit(&self, rank: CrusadeRank) -> u32 {
+        self.honour_limits[rank.level()]
+    }
+}
+
+
+/// A battle honour to choose for a unit that has gained a rank. `unit` is the "folder/filename" of the unit
+pub struct RankUp {
+    pub unit: String,
+    pub name: String,
+    pub rank: CrusadeRank,
+    pub honour: CrusadeUpgrade,
+    /// the unit's weapons, for a weapon modification to target
+    pub weapons: Vec<WeaponReference>,
+}
+
+impl CrusadeUnitData {
+    /// Battle traits, weapon modifications and relics. Enhancements and battle scars do not count towards the honour limit
+    pub fn honours(&self) -> u32 {
+        self.upgrades.iter().filter(|upgrade| upgrade.is_honour()).count() as u32
+    }
+
+    pub fn crusade_points(&self) -> i32 {
+        self.upgrades.iter().map(|upgrade| upgrade.crusade_points()).sum()
+    }
+
+    /// Every effect of the unit's battle scars
+    pub fn scar_effects(&self) -> Vec<ScarEffect> {
+        self.upgrades.iter().flat_map(|upgrade| match upgrade {
+            CrusadeUpgrade::BattleScar(_, effects) => effects.clone(),
+            _ => Vec::new()
+        }).collect()
+    }
+
+    /// The points cost of the unit's enhancements, which count towards the supply used
+    pub fn enhancement_points(&self) -> u32 {
+        self.upgrades.iter().map(|upgrade| match upgrade {
+            CrusadeUpgrade::Enhancement(_, points) => *points,
+            _ => 0
+        }).sum()
+    }
+}
+
+
+
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
 pub enum CrusadeUpgrade {
     WeaponMod(WeaponMod),
-    Relic(Ability),
+    Relic(Ability, #[serde(default)] RelicTier),
     BattleTrait(Ability),
-    Enhancement(Ability),
-    BattleScar(Ability),
+    /// the ability and its points cost
+    Enhancement(Ability, #[serde(default)] u32),
+    /// the scar and the effects it has on the unit's profile
+    BattleScar(Ability, #[serde(default)] Vec<ScarEffect>),
 }
 
 impl CrusadeUpgrade {
@@ -66,10 +159,30 @@ impl CrusadeUpgrade {
     pub fn to_string(&self) -> &str {
         match self {
             CrusadeUpgrade::WeaponMod(_) => "Weapon Mod",
-            CrusadeUpgrade::Relic(_) => "Relic",
+            CrusadeUpgrade::Relic(..) => "Relic",
             CrusadeUpgrade::BattleTrait(_) => "Battle Trait",
-            CrusadeUpgrade::Enhancement(_) => "Enhancement",
-            CrusadeUpgrade::BattleScar(_) => "Battle Scar"
+            CrusadeUpgrade::Enhancement(..) => "Enhancement",
+            CrusadeUpgrade::BattleScar(..) => "Battle Scar"
+        }
+    }
+
+    pub fn is_honour(&self) -> bool {
+        matches!(self, CrusadeUpgrade::WeaponMod(_) | CrusadeUpgrade::Relic(..) | CrusadeUpgrade::BattleTrait(_))
+    }
+
+    /// +1 for battle traits, weapon mods and enhancements, +1 to +3 for relics by tier and -1 for battle scars
+    pub fn crusade_points(&self) -> i32 {
+        match self {
+            CrusadeUpgrade::Relic(_, tier) => tier.crusade_points(),
+            CrusadeUpgrade::BattleScar(..) => -1,
+            _ => 1
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            CrusadeUpgrade::WeaponMod(weapon_mod) => weapon_mod.name.clone(),
+            CrusadeUpgrade::Relic(ability, _) | CrusadeUpgrade::BattleTrait(ability) | CrusadeUpgrade::Enhancement(ability, _) | CrusadeUpgrade::BattleScar(ability, _) => ability.name.clone(),
         }
     }
 
@@ -78,15 +191,116 @@ impl CrusadeUpgrade {
             .selected_text(format!("{}", self.to_string()))
             .show_ui(ui, |ui| {
                 ui.selectable_value(self, CrusadeUpgrade::WeaponMod(WeaponMod::default()), "Weapon Mod");
-                ui.selectable_value(self, CrusadeUpgrade::Relic(Ability::default()), "Relic");
+                ui.selectable_value(self, CrusadeUpgrade::Relic(Ability::default(), RelicTier::default()), "Relic");
                 ui.selectable_value(self, CrusadeUpgrade::BattleTrait(Ability::default()), "Battle Trait");
-                ui.selectable_value(self, CrusadeUpgrade::Enhancement(Ability::default()), "Enhancement");
-                ui.selectable_value(self, CrusadeUpgrade::BattleScar(Ability::default()), "Battle Scar");
+                ui.selectable_value(self, CrusadeUpgrade::Enhancement(Ability::default(), 0), "Enhancement");
+                ui.selectable_value(self, CrusadeUpgrade::BattleScar(Ability::default(), Vec::new()), "Battle Scar");
             });
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Copy)]
+/// A change a battle scar makes to the crusade profile of a unit
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, JsonSchema)]
+pub enum ScarEffect {
+    /// worsens the BS and WS of every weapon by 1
+    HitPenalty,
+    /// worsens Leadership by 1
+    Leadership,
+    /// -1 OC
+    ObjectiveControl,
+    /// -1" Move
+    Movement,
+    /// the unit cannot be joined by a leader
+    CannotBeLed,
+}
+
+impl ScarEffect {
+    pub const ALL: [ScarEffect; 5] = [ScarEffect::HitPenalty, ScarEffect::Leadership, ScarEffect::ObjectiveControl, ScarEffect::Movement, ScarEffect::CannotBeLed];
+
+    pub fn to_string(&self) -> &str {
+        match self {
+            ScarEffect::HitPenalty => "-1 to Hit",
+            ScarEffect::Leadership => "-1 Leadership",
+            ScarEffect::ObjectiveControl => "-1 OC",
+            ScarEffect::Movement => "-1\" Move",
+            ScarEffect::CannotBeLed => "Cannot be Led",
+        }
+    }
+
+    /// The effects after a scar's description, e.g. " (-1 OC, -1 Leadership)"
+    pub fn list(effects: &[ScarEffect]) -> String {
+        if effects.is_empty() {
+            return "".to_string();
+        }
+        let names: Vec<&str> = effects.iter().map(|effect| effect.to_string()).collect();
+        format!(" ({})", names.join(", "))
+    }
+
+    /// A checkbox for each effect
+    pub fn checkboxes(effects: &mut Vec<ScarEffect>, ui: &mut Ui) {
+        ui.horizontal_wrapped(|ui| {
+            for effect in ScarEffect::ALL {
+                let mut checked = effects.contains(&effect);
+                if ui.checkbox(&mut checked, effect.to_string()).changed() {
+                    if checked {
+                        effects.push(effect);
+                    } else {
+                        effects.retain(|other| *other != effect);
+                    }
+                }
+            }
+        });
+    }
+}
+
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default, JsonSchema)]
+pub enum RelicTier {
+    #[default]
+    Artificer,
+    Antiquity,
+    Legendary,
+}
+
+impl RelicTier {
+    pub fn to_string(&self) -> &str {
+        match self {
+            RelicTier::Artificer => "Artificer",
+            RelicTier::Antiquity => "Antiquity",
+            RelicTier::Legendary => "Legendary",
+        }
+    }
+
+    pub fn combo_box(&mut self, ui: &mut Ui, id: usize) {
+        ComboBox::from_id_salt(id)
+            .selected_text(self.to_string())
+            .show_ui(ui, |ui| {
+                ui.selectable_value(self, RelicTier::Artificer, "Artificer");
+                ui.selectable_value(self, RelicTier::Antiquity, "Antiquity");
+                ui.selectable_value(self, RelicTier::Legendary, "Legendary");
+            });
+    }
+
+    pub fn crusade_points(&self) -> i32 {
+        match self {
+            RelicTier::Artificer => 1,
+            RelicTier::Antiquity => 2,
+            RelicTier::Legendary => 3,
+        }
+    }
+
+    /// The lowest rank a unit can be to hold a relic of this tier
+    pub fn min_rank(&self) -> CrusadeRank {
+        match self {
+            RelicTier::Artificer => CrusadeRank::BattleReady,
+            RelicTier::Antiquity => CrusadeRank::Heroic,
+            RelicTier::Legendary => CrusadeRank::Legendary,
+        }
+    }
+}
+
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Copy, JsonSchema)]
 pub enum WeaponModChange {
     Attacks,
     Skill,
@@ -109,7 +323,7 @@ impl WeaponModChange {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
 pub struct WeaponMod {
     pub name: String,
     pub change_one: WeaponModChange,
@@ -193,4 +407,38 @@ impl WeaponMod {
     pub fn precise(&self) -> bool {
         self.change_one == WeaponModChange::Precise || self.change_two == WeaponModChange::Precise
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crusade_points_count_relic_tiers_and_scars() {
+        let ability = |name: &str| Ability {name: name.to_string(), ..Default::default()};
+        let data = CrusadeUnitData {
+            upgrades: vec![
+                CrusadeUpgrade::BattleTrait(ability("Swift")),
+                CrusadeUpgrade::Relic(ability("Blade"), RelicTier::Legendary),
+                CrusadeUpgrade::Enhancement(ability("Banner"), 15),
+                CrusadeUpgrade::BattleScar(ability("Limp"), vec![ScarEffect::Movement]),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(data.crusade_points(), 1 + 3 + 1 - 1);
+        assert_eq!(data.honours(), 2);
+        assert_eq!(data.enhancement_points(), 15);
+        assert_eq!(data.scar_effects(), vec![ScarEffect::Movement]);
+    }
+
+    #[test]
+    fn ranks_follow_the_thresholds() {
+        let thresholds = RankThresholds {character: [4, 10, 20, 30], ..Default::default()};
+        assert_eq!(thresholds.rank(0, false), CrusadeRank::BattleReady);
+        assert_eq!(thresholds.rank(6, false), CrusadeRank::Blooded);
+        assert_eq!(thresholds.rank(5, false), CrusadeRank::BattleReady);
+        assert_eq!(thresholds.rank(10, true), CrusadeRank::BattleHardended);
+        assert_eq!(thresholds.rank(100, true), CrusadeRank::Legendary);
+        assert_eq!(thresholds.honour_limit(CrusadeRank::Blooded), 1);
+    }
+}
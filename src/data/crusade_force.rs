@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use super::{BattleRecord, RequisitionRecord, Unit};
+
+
+
+/// The Order of Battle of a working folder, whose members are its crusade units. Stored in CRUSADE.ron next to SETTINGS.ron
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
+pub struct CrusadeForce {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub faction: String,
+    #[serde(default = "default_supply_limit")]
+    pub supply_limit: u32,
+    #[serde(default = "default_requisition_points")]
+    pub requisition_points: u32,
+    #[serde(default)]
+    pub battles: u32,
+    #[serde(default)]
+    pub victories: u32,
+    #[serde(default = "default_upgrade_limit")]
+    pub enhancement_limit: u32,
+    #[serde(default = "default_upgrade_limit")]
+    pub relic_limit: u32,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub log: Vec<BattleRecord>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requisitions: Vec<RequisitionRecord>,
+}
+
+fn default_supply_limit() -> u32 {1000}
+fn default_requisition_points() -> u32 {5}
+fn default_upgrade_limit() -> u32 {3}
+
+impl Default for CrusadeForce {
+    fn default() -> Self {
+        Self {
+            name: "".to_string(),
+            faction: "".to_string(),
+            supply_limit: default_supply_limit(),
+            requisition_points: default_requisition_points(),
+            battles: 0,
+            victories: 0,
+            enhancement_limit: default_upgrade_limit(),
+            relic_limit: default_upgrade_limit(),
+            notes: "".to_string(),
+            log: Vec::new(),
+            requisitions: Vec::new(),
+        }
+    }
+}
+
+impl CrusadeForce {
+    /// The points of every member unit and its enhancements
+    pub fn supply_used(&self, members: &[&Unit]) -> u32 {
+        members.iter().map(|unit| unit.unit_comp.points.unwrap_or(0) + unit.crusade_data.enhancement_points() + unit.enhancement_points()).sum()
+    }
+
+    pub fn total_crusade_points(&self, members: &[&Unit]) -> i32 {
+        members.iter().map(|unit| unit.crusade_data.crusade_points()).sum()
+    }
+
+    /// Enhancements and relics over the force's limits, or taken by more than one unit
+    pub fn upgrade_problems(&self, members: &[&Unit]) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (kind, limit) in [("Enhancement", self.enhancement_limit), ("Relic", self.relic_limit)] {
+            let mut names: Vec<String> = members.iter()
+                .flat_map(|unit| unit.crusade_data.upgrades.iter())
+                .filter(|upgrade| upgrade.to_string() == kind)
+                .map(|upgrade| upgrade.name())
+                .collect();
+            if names.len() as u32 > limit {
+                problems.push(format!("{} {}s, more than the limit of {}", names.len(), kind.to_lowercase(), limit));
+            }
+            names.sort();
+            let mut duplicates: Vec<&String> = names.windows(2).filter(|pair| pair[0] == pair[1]).map(|pair| &pair[0]).collect();
+            duplicates.dedup();
+            for name in duplicates {
+                problems.push(format!("{} '{}' is taken by more than one unit", kind, name));
+            }
+        }
+        problems
+    }
+}
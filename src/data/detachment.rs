@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use super::{Ability, Unit};
+
+
+
+/// An upgrade a detachment lets one of its characters take, for a points cost
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, JsonSchema)]
+pub struct Enhancement {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub points: u32,
+    /// keywords a unit must have to take the enhancement, on top of CHARACTER
+    #[serde(default)]
+    pub keywords: Vec<String>,
+}
+
+impl Enhancement {
+    /// The eligibility keywords the unit is missing, including CHARACTER
+    pub fn missing_keywords(&self, unit: &Unit) -> Vec<String> {
+        let mut missing = Vec::new();
+        if !unit.is_character() {
+            missing.push("CHARACTER".to_string());
+        }
+        missing.extend(self.keywords.iter().filter(|keyword| !keyword.trim().is_empty() && !unit.has_keyword(keyword)).map(|keyword| keyword.to_uppercase()));
+        missing
+    }
+}
+
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, JsonSchema)]
+pub struct Detachment {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub rule: Ability,
+    #[serde(default)]
+    pub enhancements: Vec<Enhancement>,
+}
+
+
+/// The detachments of a working folder. Stored in DETACHMENTS.ron next to SETTINGS.ron
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, JsonSchema)]
+pub struct Detachments {
+    #[serde(default)]
+    pub detachments: Vec<Detachment>,
+}
+
+impl Detachments {
+    pub fn find_enhancement(&self, detachment: &str, name: &str) -> Option<&Enhancement> {
+        self.detachments.iter()
+            .find(|other| other.name == detachment)?
+            .enhancements.iter()
+            .find(|enhancement| enhancement.name == name)
+    }
+}
+
+
+/// A copy of a detachment's enhancement given to a unit outside of crusade, kept up to date when the detachments are saved
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, JsonSchema)]
+pub struct AssignedEnhancement {
+    #[serde(default)]
+    pub detachment: String,
+    #[serde(default)]
+    pub enhancement: Enhancement,
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detachments_with_old_stratagems_still_load() {
+        let detachments: Detachments = ron::from_str(r#"(detachments: [(name: "Gladius", stratagems: [(name: "Armour of Contempt", cost: 1)])])"#).unwrap();
+        assert_eq!(detachments.detachments[0].name, "Gladius");
+    }
+}
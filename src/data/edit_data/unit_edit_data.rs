@@ -1,5 +1,5 @@
 use std::collections::BTreeMap;
-use crate::data::{Ability, ChargeLevels, CrusadeRank, CrusadeUpgrade, Unit, VariableValue, Weapon, WeaponMod, abilities::{CoreAbility, WeaponAbility}, crusade_data::CrusadeUnitData, edit_data::{edit_stats::EditStats, weapon_edit_data::WeaponEditData}, index::WeaponReference, unit_stats::UnitStats};
+use crate::data::{Ability, AssignedEnhancement, ChargeLevels, CrusadeUpgrade, RankThresholds, ScarEffect, Unit, VariableValue, Weapon, WeaponMod, abilities::{CoreAbility, WeaponAbility}, crusade_data::CrusadeUnitData, unit_composition::UnitComposition, unit_base::UnitBase, edit_data::{edit_stats::EditStats, weapon_edit_data::WeaponEditData}, index::WeaponReference, unit_stats::UnitStats};
 
 
 
@@ -29,6 +29,10 @@ pub struct UnitEditData {
 
     pub leader: (bool, Vec<String>),
 
+    pub unit_comp: UnitComposition,
+    pub base: Option<UnitBase>,
+    pub enhancement: Option<AssignedEnhancement>,
+
     pub crusader: bool,
     pub crusade_data: CrusadeUnitData
 }
@@ -81,11 +85,20 @@ impl From<(&Unit, String)> for UnitEditData {
             leader: (value.leader.is_some(), value.leader.clone().unwrap_or(Vec::new())),
 
             crusader: value.crusade_unit,
+            unit_comp: value.unit_comp.clone(),
+            base: value.base.clone(),
+            enhancement: value.enhancement.clone(),
             crusade_data: value.crusade_data.clone(),
         }
     }
 }
 
+impl UnitEditData {
+    pub fn next_weapon_uid(&self) -> u32 {
+        self.ranged_weapons.iter().chain(self.melee_weapons.iter()).map(|(weapon, _)| weapon.uid).max().unwrap_or(0) + 1
+    }
+}
+
 impl Into<Unit> for UnitEditData {
     fn into(self) -> Unit {
         let mut ranged_weapons: Vec<(Weapon, u32)> = Vec::new();
@@ -128,10 +141,12 @@ impl Into<Unit> for UnitEditData {
         let mut crusade_ranged = Vec::new();
         let mut crusade_melee = Vec::new();
         let mut crusade_data = self.crusade_data.clone();
+        let stats: UnitStats = self.stats.into();
+        let mut crusade_stats = None;
         if self.crusader {
             let mut upgrades: Vec<(usize, WeaponMod)> = Vec::new();
             // list of names of upgrade parents
-            let mut upgrade_names: BTreeMap<WeaponReference, String> = BTreeMap::new();
+            let mut upgrade_names: BTreeMap<u32, String> = BTreeMap::new();
 
             for upgrade in self.crusade_data.upgrades.iter() {
                 match upgrade {
@@ -156,21 +171,21 @@ impl Into<Unit> for UnitEditData {
 
             let weapons = {
                 let mut list = Vec::new();
-                for (id, weapon) in ranged_weapons.iter().enumerate() {
-                    list.push((true, id, weapon));
+                for weapon in ranged_weapons.iter() {
+                    list.push((true, weapon));
                 }
-                for (id, weapon) in melee_weapons.iter().enumerate() {
-                    list.push((false, id, weapon));
+                for weapon in melee_weapons.iter() {
+                    list.push((false, weapon));
                 }
                 list
             };
 
-            for (ranged, index, (weapon, count)) in weapons.iter() {
+            for (ranged, (weapon, count)) in weapons.iter() {
                 let mut count = *count;
                 for (upgrade_count, upgrade) in upgrades.iter() {
                     let target = upgrade.target.as_ref().unwrap();
                     let upgrade_target =
-                        target.is_id(*ranged, *index) // direct target
+                        target.is_id(weapon.uid) // direct target
                         || (match &weapon.charge { // indirect target
                             ChargeLevels::Child(reference, _) => *target == *reference,
                             _ => false
@@ -182,12 +197,12 @@ impl Into<Unit> for UnitEditData {
                         let new_charge = match &weapon.charge {
                             ChargeLevels::None => ChargeLevels::None,
                             ChargeLevels::Parent(name) => {
-                                upgrade_names.insert(WeaponReference::new(weapon.name.clone(), *ranged, *index), upgrade.name.clone());
+                                upgrade_names.insert(weapon.uid, upgrade.name.clone());
                                 ChargeLevels::Parent(name.clone())
                             },
                             ChargeLevels::Child(parent_ref, name) => {
-                                if let Some(new_parent) = upgrade_names.get(&parent_ref) {
-                                    ChargeLevels::Child(WeaponReference::new(new_parent.to_string(), parent_ref.ranged, parent_ref.id), name.clone())
+                                if let Some(new_parent) = upgrade_names.get(&parent_ref.uid) {
+                                    ChargeLevels::Child(WeaponReference::new(new_parent.to_string(), parent_ref.ranged, parent_ref.uid), name.clone())
                                 } else {
                                     ChargeLevels::Child(parent_ref.clone(), name.clone())
                                 }
@@ -198,7 +213,7 @@ impl Into<Unit> for UnitEditData {
                             name: upgrade.name.clone(),
                             range: weapon.range,
                             attacks: if upgrade.attacks() {weapon.attacks.add_one()} else {weapon.attacks},
-                            skill: if upgrade.skill() {weapon.skill - 1} else {weapon.skill},
+                            skill: if upgrade.skill() {weapon.skill.saturating_sub(1).max(2)} else {weapon.skill},
                             strength: if upgrade.strength() {weapon.strength + 1} else {weapon.strength},
                             ap: if upgrade.ap() {if weapon.ap <= 0 {weapon.ap - 1} else {weapon.ap + 1}} else {weapon.ap},
                             damage: if upgrade.damage() {weapon.damage.add_one()} else {weapon.damage},
@@ -207,7 +222,9 @@ impl Into<Unit> for UnitEditData {
                                 keywords.push(WeaponAbility::Precise);
                                 keywords
                             } else {weapon.keywords.clone()},
-                            charge: new_charge
+                            charge: new_charge,
+                            library: None,
+                            uid: weapon.uid
                         }, i);
                         count -= i;
                         if *ranged {
@@ -224,20 +241,36 @@ impl Into<Unit> for UnitEditData {
                 }
             }
 
-            crusade_data.rank = match crusade_data.exp {
-                0..6 => {CrusadeRank::BattleReady},
-                6..16 => {CrusadeRank::Blooded},
-                16..31 => {CrusadeRank::BattleHardended},
-                31..51 => {CrusadeRank::Heroic},
-                51.. => {CrusadeRank::Legendary},
+            // battle scars change the crusade profile the same way weapon mods change the crusade weapons
+            let mut scarred = stats;
+            for effect in crusade_data.scar_effects() {
+                match effect {
+                    ScarEffect::HitPenalty => {
+                        for (weapon, _) in crusade_ranged.iter_mut().chain(crusade_melee.iter_mut()) {
+                            if !weapon.keywords.contains(&WeaponAbility::Torrent) {
+                                weapon.skill = (weapon.skill + 1).min(6);
+                            }
+                        }
+                    },
+                    ScarEffect::Leadership => scarred.leadership += 1,
+                    ScarEffect::ObjectiveControl => scarred.oc = scarred.oc.saturating_sub(1),
+                    ScarEffect::Movement => scarred.movement = scarred.movement.saturating_sub(1),
+                    ScarEffect::CannotBeLed => ()
+                }
             }
+            if scarred != stats {
+                crusade_stats = Some(scarred);
+            }
+
+            // the app reranks with the folder's thresholds, which the edit data does not know about
+            crusade_data.rank = RankThresholds::default().rank(crusade_data.exp, sanitised_keywords.contains(&"CHARACTER".to_string()));
             
         }
 
 
         Unit {
             name: self.name,
-            stats: self.stats.into(),
+            stats,
             extra_statlines,
             ranged_weapons,
             melee_weapons,
@@ -261,11 +294,69 @@ impl Into<Unit> for UnitEditData {
                 None
             },
 
+            unit_comp: self.unit_comp,
+            base: self.base,
+            enhancement: self.enhancement,
+
             crusade_unit: self.crusader,
             crusade_data: crusade_data,
             crusade_weapons: (crusade_ranged, crusade_melee),
+            crusade_stats,
+        }
+    }
+}
 
-            ..Default::default()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebuilt_edit_data_has_no_pending_edits() {
+        let unit: Unit = ron::from_str(r#"(
+            name: "Intercessors",
+            ranged_weapons: [((name: "Bolt rifle", range: Ranged(24), skill: 3, strength: 4, ap: 1, uid: 1), 5)],
+            melee_weapons: [((name: "Close combat weapon", range: Melee, skill: 3, strength: 4, uid: 2), 5)],
+            keywords: ["INFANTRY"],
+            crusade_unit: true,
+            crusade_data: (exp: 7, kills: 3),
+        )"#).unwrap();
+        let saved: Unit = UnitEditData::from((&unit, "intercessors".to_string())).into();
+        let rebuilt: Unit = UnitEditData::from((&saved, "intercessors".to_string())).into();
+        assert_eq!(ron::to_string(&saved).unwrap(), ron::to_string(&rebuilt).unwrap());
+    }
+
+    #[test]
+    fn weapon_mods_keep_skill_at_two_or_worse() {
+        for skill in [0, 2, 4] {
+            let unit: Unit = ron::from_str(&format!(r#"(
+                ranged_weapons: [((name: "Bolter", range: Ranged(24), skill: {}, uid: 1), 1)],
+                crusade_unit: true,
+                crusade_data: (upgrades: [WeaponMod((name: "Honed", change_one: Skill, change_two: Attacks, target: Some((name: "Bolter", ranged: true, uid: 1))))]),
+            )"#, skill)).unwrap();
+            let unit: Unit = UnitEditData::from((&unit, "unit".to_string())).into();
+            let modded = unit.crusade_weapons.0.iter().find(|(weapon, _)| weapon.name == "Honed").map(|(weapon, _)| weapon.skill);
+            assert_eq!(modded, Some(if skill == 4 {3} else {2}));
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn battle_scars_change_the_crusade_profile() {
+        let unit: Unit = ron::from_str(r#"(
+            stats: (movement: 6, toughness: 4, save: 3, wounds: 2, leadership: 6, oc: 2),
+            ranged_weapons: [((name: "Bolter", range: Ranged(24), skill: 3, uid: 1), 1), ((name: "Flamer", range: Ranged(12), skill: 0, keywords: [Torrent], uid: 2), 1)],
+            crusade_unit: true,
+            crusade_data: (upgrades: [BattleScar((name: "Shell Shock", description: ""), [HitPenalty, Leadership]), BattleScar((name: "Crippled", description: ""), [Movement, ObjectiveControl])]),
+        )"#).unwrap();
+        let unit: Unit = UnitEditData::from((&unit, "unit".to_string())).into();
+        let stats = unit.crusade_stats.unwrap();
+        assert_eq!((stats.movement, stats.leadership, stats.oc), (5, 7, 1));
+        assert_eq!(unit.stats.leadership, 6);
+        let skills: Vec<u32> = unit.crusade_weapons.0.iter().map(|(weapon, _)| weapon.skill).collect();
+        assert_eq!(skills, vec![4, 0]);
+
+        let mut healed = unit.clone();
+        healed.crusade_data.upgrades.clear();
+        let healed: Unit = UnitEditData::from((&healed, "unit".to_string())).into();
+        assert_eq!(healed.crusade_stats, None);
+    }
+}
@@ -1,6 +1,6 @@
 use egui::{ComboBox, Ui};
 
-use crate::{data::{ChargeLevels, Range, VariableValue, Weapon, WeaponAbility, WeaponReference}, helper_funcs::select_text_on_tab};
+use crate::{data::{ChargeLevels, LibraryLink, Range, VariableValue, Weapon, WeaponAbility, WeaponLibrary, WeaponReference}, helper_funcs::select_text_on_tab};
 
 
 
@@ -14,7 +14,9 @@ pub struct WeaponEditData {
     pub ap: u32,
     pub damage: String,
     pub keywords: Vec<WeaponAbility>,
-    pub charge_levels_info: (bool, Option<WeaponReference>, String) // has levels, is parent, level name
+    pub charge_levels_info: (bool, Option<WeaponReference>, String), // has levels, is parent, level name
+    pub library: Option<LibraryLink>,
+    pub uid: u32
 }
 
 impl Default for WeaponEditData {
@@ -28,7 +30,9 @@ impl Default for WeaponEditData {
             ap: 0,
             damage: "1".to_string(),
             keywords: Vec::new(),
-            charge_levels_info: (false, None, "".to_string())
+            charge_levels_info: (false, None, "".to_string()),
+            library: None,
+            uid: 0
         }
     }
 }
@@ -47,7 +51,9 @@ impl From<&Weapon> for WeaponEditData {
             ap: value.ap.abs() as u32,
             damage: value.damage.to_string(),
             keywords: value.keywords.clone(),
-            charge_levels_info: value.charge.to_edit()
+            charge_levels_info: value.charge.to_edit(),
+            library: value.library.clone(),
+            uid: value.uid
         }
     }
 }
@@ -82,14 +88,16 @@ impl Into<Weapon> for WeaponEditData {
             ap: self.ap as i32,
             damage: VariableValue::from_string(&self.damage).unwrap_or(VariableValue::Set(0)),
             keywords: keywords,
-            charge: ChargeLevels::from_edit(self.charge_levels_info.0, self.charge_levels_info.1, self.charge_levels_info.2) 
+            charge: ChargeLevels::from_edit(self.charge_levels_info.0, self.charge_levels_info.1, self.charge_levels_info.2),
+            library: self.library,
+            uid: self.uid
         }
     }
 }
 
 
 impl WeaponEditData {
-    pub fn charge_edit_section(&mut self, ui: &mut Ui, index: usize, weapons: &Vec<WeaponReference>, id: usize) {
+    pub fn charge_edit_section(&mut self, ui: &mut Ui, weapons: &Vec<WeaponReference>, id: usize) {
         ui.horizontal(|ui| {
             ui.checkbox(&mut self.charge_levels_info.0, "");
             if self.charge_levels_info.0 {
@@ -100,8 +108,8 @@ impl WeaponEditData {
                     .width(20.0)
                     .show_ui(ui, |ui| {
                         ui.selectable_value(&mut self.charge_levels_info.1, None, "Parent");
-                        for (i, weapon) in weapons.iter().enumerate() {
-                            if i == index {continue;}
+                        for weapon in weapons.iter() {
+                            if weapon.is_id(self.uid) {continue;}
                             ui.selectable_value(&mut self.charge_levels_info.1, Some(weapon.clone()), &weapon.name);
                         }
                     });
@@ -109,4 +117,33 @@ impl WeaponEditData {
             }
         });
     }
-}
\ No newline at end of file
+
+    /// Lets the weapon be linked to a library entry, copying its profile in when picked
+    pub fn library_edit_section(&mut self, ui: &mut Ui, library: &WeaponLibrary, id: usize) {
+        let selected = self.library.as_ref().map(|link| link.id.clone()).unwrap_or("Local".to_string());
+        let mut picked = None;
+        ComboBox::from_id_salt(id)
+            .selected_text(selected)
+            .width(60.0)
+            .show_ui(ui, |ui| {
+                if ui.selectable_label(self.library.is_none(), "Local").clicked() {
+                    self.library = None;
+                }
+                for (weapon_id, weapon) in library.weapons.iter() {
+                    let is_selected = self.library.as_ref().is_some_and(|link| &link.id == weapon_id);
+                    if ui.selectable_label(is_selected, weapon_id).on_hover_text(&weapon.name).clicked() && !is_selected {
+                        picked = Some((weapon_id.clone(), weapon));
+                    }
+                }
+            }).response.on_hover_text("Weapon library entry");
+
+        if let Some((weapon_id, weapon)) = picked {
+            let charge_levels_info = self.charge_levels_info.clone();
+            let uid = self.uid;
+            *self = WeaponEditData::from(weapon);
+            self.charge_levels_info = charge_levels_info;
+            self.uid = uid;
+            self.library = Some(LibraryLink {id: weapon_id, ..Default::default()});
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use egui::{ComboBox, Ui};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use super::{CoreAbility, Unit, WeaponAbility};
+
+
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default, JsonSchema)]
+pub enum GlossaryKind {
+    Core,
+    Faction,
+    Weapon,
+    #[default]
+    Other
+}
+
+impl GlossaryKind {
+    pub fn to_string(&self) -> &str {
+        match self {
+            GlossaryKind::Core => "Core",
+            GlossaryKind::Faction => "Faction",
+            GlossaryKind::Weapon => "Weapon",
+            GlossaryKind::Other => "Other",
+        }
+    }
+
+    pub fn combo_box(&mut self, ui: &mut Ui, id: usize) {
+        ComboBox::from_id_salt(id)
+            .selected_text(self.to_string())
+            .show_ui(ui, |ui| {
+                ui.selectable_value(self, GlossaryKind::Core, "Core");
+                ui.selectable_value(self, GlossaryKind::Faction, "Faction");
+                ui.selectable_value(self, GlossaryKind::Weapon, "Weapon");
+                ui.selectable_value(self, GlossaryKind::Other, "Other");
+            });
+    }
+}
+
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, JsonSchema)]
+pub struct GlossaryEntry {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub kind: GlossaryKind,
+    #[serde(default)]
+    pub description: String,
+}
+
+
+/// Rules text for named abilities, shared by a working folder. Stored in GLOSSARY.ron next to SETTINGS.ron
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, JsonSchema)]
+pub struct Glossary {
+    #[serde(default)]
+    pub entries: Vec<GlossaryEntry>,
+}
+
+fn weapon_ability_name(ability: &WeaponAbility) -> String {
+    match ability {
+        WeaponAbility::Custom(name) => name.clone(),
+        WeaponAbility::AntiX(_, _) => "ANTI".to_string(),
+        _ => ability.to_string().to_string()
+    }
+}
+
+impl Glossary {
+    pub fn entry(&self, name: &str) -> Option<&GlossaryEntry> {
+        self.entries.iter().find(|entry| entry.name.trim().eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn describe(&self, name: &str) -> Option<&str> {
+        self.entry(name).map(|entry| entry.description.as_str()).filter(|description| !description.is_empty())
+    }
+
+    pub fn describe_core(&self, ability: &CoreAbility) -> Option<&str> {
+        self.describe(ability.to_string())
+    }
+
+    pub fn describe_weapon(&self, ability: &WeaponAbility) -> Option<&str> {
+        self.describe(&weapon_ability_name(ability))
+    }
+
+    /// The descriptions of all of a weapon's keywords, one per line, for use as a tooltip
+    pub fn weapon_tooltip(&self, keywords: &[WeaponAbility]) -> Option<String> {
+        let lines: Vec<String> = keywords.iter()
+            .filter_map(|keyword| self.describe_weapon(keyword).map(|description| format!("{}: {}", keyword.to_render_string(), description)))
+            .collect();
+        if lines.is_empty() {None} else {Some(lines.join("\n"))}
+    }
+
+    /// Names of everything a unit could have a glossary entry for
+    fn names(unit: &Unit) -> Vec<(String, GlossaryKind)> {
+        let mut names = Vec::new();
+        for ability in unit.core_abilities.iter() {
+            if ability != &CoreAbility::None {
+                names.push((ability.to_string().to_string(), GlossaryKind::Core));
+            }
+        }
+        if let Some(ability) = &unit.faction_ability {
+            names.push((ability.clone(), GlossaryKind::Faction));
+        }
+        let weapons = unit.ranged_weapons.iter().chain(unit.melee_weapons.iter()).chain(unit.crusade_weapons.0.iter()).chain(unit.crusade_weapons.1.iter());
+        for (weapon, _) in weapons {
+            for keyword in weapon.keywords.iter() {
+                if keyword != &WeaponAbility::None {
+                    names.push((weapon_ability_name(keyword), GlossaryKind::Weapon));
+                }
+            }
+        }
+        names
+    }
+
+    /// Entries with rules text used by any of the units, in glossary order
+    pub fn used_by(&self, units: &[&Unit]) -> Vec<&GlossaryEntry> {
+        let names: Vec<String> = units.iter().flat_map(|unit| Glossary::names(unit)).map(|(name, _)| name).collect();
+        self.entries.iter()
+            .filter(|entry| !entry.description.is_empty() && names.iter().any(|name| name.trim().eq_ignore_ascii_case(entry.name.trim())))
+            .collect()
+    }
+
+    /// Adds an empty entry for every ability the units use that the glossary does not have yet
+    pub fn add_missing(&mut self, units: &[&Unit]) {
+        for unit in units {
+            for (name, kind) in Glossary::names(unit) {
+                if !name.trim().is_empty() && self.entry(&name).is_none() {
+                    self.entries.push(GlossaryEntry {name, kind, description: "".to_string()});
+                }
+            }
+        }
+    }
+}
@@ -1,29 +1,115 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 
+use super::{ChargeLevels, CrusadeUpgrade, Unit, WeaponMod};
 
-#[derive(Debug, Default, Deserialize, Clone, Serialize, PartialOrd, Ord, Eq)]
+
+#[derive(Debug, Default, Deserialize, Clone, Serialize, Eq, JsonSchema)]
 pub struct WeaponReference {
     pub name: String,
     pub ranged: bool,
-    pub id: usize
+    /// index of the weapon in files saved before weapons had ids, only read when migrating
+    #[serde(default, skip_serializing)]
+    pub id: usize,
+    #[serde(default)]
+    pub uid: u32,
 }
 
 impl WeaponReference {
-    pub fn new(name: String, ranged: bool, id: usize) -> Self {
+    pub fn new(name: String, ranged: bool, uid: u32) -> Self {
         Self {
             name,
             ranged,
-            id
+            id: 0,
+            uid
         }
     }
 
-    pub fn is_id(&self, ranged: bool, id: usize) -> bool {
-        self.ranged == ranged && self.id == id
+    pub fn is_id(&self, uid: u32) -> bool {
+        self.uid == uid
     }
 }
 
 impl PartialEq for WeaponReference {
     fn eq(&self, other: &Self) -> bool {
-        self.ranged == other.ranged && self.id == other.id
+        self.uid == other.uid
+    }
+}
+
+
+impl Unit {
+    pub fn next_weapon_uid(&self) -> u32 {
+        self.ranged_weapons.iter().chain(self.melee_weapons.iter()).map(|(weapon, _)| weapon.uid).max().unwrap_or(0) + 1
+    }
+
+    /// Gives every weapon without a unique id one, and points references saved by index at those ids.
+    /// Returns whether anything changed
+    pub fn assign_weapon_uids(&mut self) -> bool {
+        let mut changed = false;
+        let mut next = self.next_weapon_uid();
+        let mut taken = Vec::new();
+        for (weapon, _) in self.ranged_weapons.iter_mut().chain(self.melee_weapons.iter_mut()) {
+            if weapon.uid == 0 || taken.contains(&weapon.uid) {
+                weapon.uid = next;
+                next += 1;
+                changed = true;
+            }
+            taken.push(weapon.uid);
+        }
+
+        // old references are the index of the weapon in its list
+        let ranged_uids: Vec<u32> = self.ranged_weapons.iter().map(|(weapon, _)| weapon.uid).collect();
+        let melee_uids: Vec<u32> = self.melee_weapons.iter().map(|(weapon, _)| weapon.uid).collect();
+        let mut migrate = |reference: &mut WeaponReference| {
+            if reference.uid != 0 {return}
+            let uids = if reference.ranged {&ranged_uids} else {&melee_uids};
+            if let Some(uid) = uids.get(reference.id) {
+                reference.uid = *uid;
+                changed = true;
+            }
+        };
+        for (weapon, _) in self.ranged_weapons.iter_mut().chain(self.melee_weapons.iter_mut()) {
+            if let ChargeLevels::Child(reference, _) = &mut weapon.charge {
+                migrate(reference);
+            }
+        }
+        for upgrade in self.crusade_data.upgrades.iter_mut() {
+            if let CrusadeUpgrade::WeaponMod(WeaponMod {target: Some(reference), ..}) = upgrade {
+                migrate(reference);
+            }
+        }
+        changed
     }
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn old_references_point_at_the_new_uids() {
+        let mut unit: Unit = ron::from_str(r#"(
+            ranged_weapons: [((name: "Plasma", range: Ranged(24), charge: Parent("Standard")), 1), ((name: "Plasma", range: Ranged(24), charge: Child((name: "Plasma", ranged: true, id: 0), "Supercharge")), 1)],
+            melee_weapons: [((name: "Chainsword", range: Melee), 1), ((name: "Power fist", range: Melee), 1)],
+            crusade_data: (upgrades: [WeaponMod((name: "Heirloom", change_one: Attacks, change_two: Damage, target: Some((name: "Power fist", ranged: false, id: 1))))]),
+        )"#).unwrap();
+        assert!(unit.assign_weapon_uids());
+        let uids: Vec<u32> = unit.ranged_weapons.iter().chain(unit.melee_weapons.iter()).map(|(weapon, _)| weapon.uid).collect();
+        assert_eq!(uids, vec![1, 2, 3, 4]);
+
+        let ChargeLevels::Child(parent, _) = &unit.ranged_weapons[1].0.charge else {panic!()};
+        assert_eq!(parent.uid, 1);
+        let CrusadeUpgrade::WeaponMod(weapon_mod) = &unit.crusade_data.upgrades[0] else {panic!()};
+        assert_eq!(weapon_mod.target.as_ref().unwrap().uid, 4);
+
+        assert!(!unit.assign_weapon_uids());
+    }
+
+    #[test]
+    fn duplicate_uids_are_replaced() {
+        let mut unit: Unit = ron::from_str(r#"(ranged_weapons: [((name: "Bolter", range: Ranged(24), uid: 3), 1), ((name: "Copy", range: Ranged(24), uid: 3), 1)])"#).unwrap();
+        assert!(unit.assign_weapon_uids());
+        assert_eq!(unit.ranged_weapons[1].0.uid, 4);
+    }
+}
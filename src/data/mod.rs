@@ -8,6 +8,18 @@ mod unit_stats;
 mod unit_composition;
 mod crusade_data;
 mod index;
+mod weapon_library;
+mod glossary;
+mod crusade_force;
+mod battle_log;
+mod roll_tables;
+mod requisition;
+mod agendas;
+mod unit_history;
+mod detachment;
+mod stratagem;
+mod unit_base;
+mod validation;
 
 pub use unit::*;
 pub use weapon::*;
@@ -16,4 +28,17 @@ pub use abilities::*;
 pub use wargear::*;
 pub use edit_data::*;
 pub use crusade_data::*;
-pub use index::WeaponReference;
\ No newline at end of file
+pub use index::WeaponReference;
+pub use glossary::*;
+pub use crusade_force::*;
+pub use battle_log::*;
+pub use roll_tables::*;
+pub use requisition::*;
+pub use agendas::*;
+pub use unit_history::*;
+pub use detachment::*;
+pub use stratagem::*;
+pub use unit_base::*;
+pub use validation::*;
+pub use unit_stats::UnitStats;
+pub use weapon_library::*;
\ No newline at end of file
@@ -0,0 +1,310 @@
+use egui::{ComboBox, Ui};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use super::{Ability, ChargeLevels, CrusadeForce, CrusadeRank, CrusadeUpgrade, LibraryLink, RelicTier, Unit, Weapon, WeaponLibrary};
+
+
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default, JsonSchema)]
+pub enum Requisition {
+    #[default]
+    IncreaseSupplyLimit,
+    RearmAndResupply,
+    RepairAndRecuperate,
+    FreshRecruits,
+    RenownedHeroes,
+}
+
+impl Requisition {
+    pub const ALL: [Requisition; 5] = [Requisition::IncreaseSupplyLimit, Requisition::RearmAndResupply, Requisition::RepairAndRecuperate, Requisition::FreshRecruits, Requisition::RenownedHeroes];
+
+    pub fn to_string(&self) -> &str {
+        match self {
+            Requisition::IncreaseSupplyLimit => "Increase Supply Limit",
+            Requisition::RearmAndResupply => "Rearm and Resupply",
+            Requisition::RepairAndRecuperate => "Repair and Recuperate",
+            Requisition::FreshRecruits => "Fresh Recruits",
+            Requisition::RenownedHeroes => "Renowned Heroes",
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        match self {
+            Requisition::IncreaseSupplyLimit => "Raise the supply limit of the force.",
+            Requisition::RearmAndResupply => "Replace one of a unit's ranged weapons with a weapon library entry. Weapon mods on the old weapon are lost.",
+            Requisition::RepairAndRecuperate => "Remove a battle scar from a unit. Costs 1 RP plus 1 for each battle honour the unit has, up to 5.",
+            Requisition::FreshRecruits => "Add models to a unit, raising its points. Costs 1 RP plus 1 for every two battle honours the unit has, up to 4.",
+            Requisition::RenownedHeroes => "Give a character a crusade relic. Costs 1 to 3 RP by the relic's tier.",
+        }
+    }
+
+    pub fn combo_box(&mut self, ui: &mut Ui, id: &str) {
+        ComboBox::from_id_salt(id)
+            .selected_text(self.to_string())
+            .show_ui(ui, |ui| {
+                for requisition in Requisition::ALL {
+                    ui.selectable_value(self, requisition, requisition.to_string());
+                }
+            });
+    }
+
+    /// Whether the requisition is spent on a unit rather than the whole force
+    pub fn targets_unit(&self) -> bool {
+        *self != Requisition::IncreaseSupplyLimit
+    }
+}
+
+
+/// A requisition the force has bought. `unit` is the "folder/filename" of the unit it was spent on, empty for the whole force
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default, JsonSchema)]
+pub struct RequisitionRecord {
+    #[serde(default)]
+    pub date: String,
+    #[serde(default)]
+    pub requisition: Requisition,
+    #[serde(default)]
+    pub cost: u32,
+    #[serde(default)]
+    pub unit: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub details: String,
+}
+
+impl RequisitionRecord {
+    pub fn to_string(&self) -> String {
+        let target = if self.name.is_empty() {"".to_string()} else {format!(" on {}", self.name)};
+        format!("{} - {}{} ({} RP): {}", self.date, self.requisition.to_string(), target, self.cost, self.details)
+    }
+}
+
+
+/// What the requisition window needs to know about a crusade unit
+pub struct RequisitionUnit {
+    pub reference: String,
+    pub name: String,
+    pub rank: CrusadeRank,
+    pub honours: u32,
+    pub character: bool,
+    /// names of the unit's battle scars, in the order they appear in its upgrades
+    pub scars: Vec<String>,
+    /// uid and name of each ranged weapon
+    pub weapons: Vec<(u32, String)>,
+}
+
+/// The state of the requisition window
+pub struct RequisitionWizard {
+    pub requisition: Requisition,
+    pub units: Vec<RequisitionUnit>,
+    pub unit: usize,
+    pub scar: usize,
+    pub weapon: u32,
+    /// id of the weapon library entry a weapon is replaced with
+    pub replacement: String,
+    pub supply: u32,
+    pub points: u32,
+    pub relic: Ability,
+    pub tier: RelicTier,
+}
+
+impl RequisitionWizard {
+    /// `units` are the crusade units of the working folder with their references
+    pub fn new(units: Vec<(String, &Unit)>) -> Self {
+        let units = units.into_iter().map(|(reference, unit)| RequisitionUnit {
+            reference,
+            name: unit.name.clone(),
+            rank: unit.crusade_data.rank,
+            honours: unit.crusade_data.honours(),
+            character: unit.is_character(),
+            scars: unit.crusade_data.upgrades.iter()
+                .filter(|upgrade| matches!(upgrade, CrusadeUpgrade::BattleScar(..)))
+                .map(|upgrade| upgrade.name())
+                .collect(),
+            weapons: unit.ranged_weapons.iter().map(|(weapon, _)| (weapon.uid, weapon.name.clone())).collect(),
+        }).collect();
+
+        Self {
+            requisition: Requisition::default(),
+            units,
+            unit: 0,
+            scar: 0,
+            weapon: 0,
+            replacement: "".to_string(),
+            supply: 200,
+            points: 0,
+            relic: Ability::default(),
+            tier: RelicTier::default(),
+        }
+    }
+
+    pub fn selected_unit(&self) -> Option<&RequisitionUnit> {
+        if self.requisition.targets_unit() {self.units.get(self.unit)} else {None}
+    }
+
+    pub fn cost(&self) -> u32 {
+        let honours = self.selected_unit().map(|unit| unit.honours).unwrap_or(0);
+        match self.requisition {
+            Requisition::IncreaseSupplyLimit | Requisition::RearmAndResupply => 1,
+            Requisition::RepairAndRecuperate => (1 + honours).min(5),
+            Requisition::FreshRecruits => (1 + honours / 2).min(4),
+            Requisition::RenownedHeroes => self.tier.crusade_points() as u32,
+        }
+    }
+
+    /// Why the requisition can't be bought as it is, if anything. `members` are the crusade units of the force with their references
+    pub fn problem(&self, force: &CrusadeForce, members: &[(String, &Unit)], library: &WeaponLibrary) -> Option<String> {
+        if self.cost() > force.requisition_points {
+            return Some(format!("Costs {} RP, only {} left", self.cost(), force.requisition_points));
+        }
+        if !self.requisition.targets_unit() {
+            return None;
+        }
+        let Some(unit) = self.selected_unit() else {return Some("No crusade unit selected".to_string())};
+        match self.requisition {
+            Requisition::RepairAndRecuperate if unit.scars.get(self.scar).is_none() => Some(format!("{} has no battle scars", unit.name)),
+            Requisition::RearmAndResupply if !unit.weapons.iter().any(|(uid, _)| *uid == self.weapon) => Some("Choose a ranged weapon to replace".to_string()),
+            Requisition::RearmAndResupply if !library.weapons.contains_key(&self.replacement) => Some("Choose a weapon library entry to replace it with".to_string()),
+            Requisition::FreshRecruits if self.points == 0 => Some("Enter the points the new models add".to_string()),
+            Requisition::RenownedHeroes if !unit.character => Some(format!("{} is not a character", unit.name)),
+            Requisition::RenownedHeroes if self.relic.name.is_empty() => Some("Name the relic".to_string()),
+            Requisition::RenownedHeroes if unit.rank.level() < self.tier.min_rank().level() => Some(format!("{} relics need a rank of {}", self.tier.to_string(), self.tier.min_rank().to_string())),
+            Requisition::RenownedHeroes => self.relic_problem(force, members, &unit.reference),
+            _ => None
+        }
+    }
+
+    /// A problem the force's relic and enhancement limits would gain from the relic
+    fn relic_problem(&self, force: &CrusadeForce, members: &[(String, &Unit)], reference: &str) -> Option<String> {
+        let units: Vec<&Unit> = members.iter().map(|(_, unit)| *unit).collect();
+        let before = force.upgrade_problems(&units);
+        let mut hero = members.iter().find(|(other, _)| other == reference)?.1.clone();
+        hero.crusade_data.upgrades.push(CrusadeUpgrade::Relic(self.relic.clone(), self.tier));
+        let after: Vec<&Unit> = members.iter().map(|(other, unit)| if other == reference {&hero} else {*unit}).collect();
+        force.upgrade_problems(&after).into_iter().find(|problem| !before.contains(problem))
+    }
+
+    /// The log entry for the requisition, without its details
+    pub fn record(&self) -> RequisitionRecord {
+        let unit = self.selected_unit();
+        RequisitionRecord {
+            date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+            requisition: self.requisition,
+            cost: self.cost(),
+            unit: unit.map(|unit| unit.reference.clone()).unwrap_or_default(),
+            name: unit.map(|unit| unit.name.clone()).unwrap_or_default(),
+            details: "".to_string(),
+        }
+    }
+}
+
+
+/// Applies a requisition to the unit it was bought for, returning a description of what changed.
+/// The crusade weapons are rebuilt when the unit is next round tripped through its edit data
+pub fn apply_requisition(unit: &mut Unit, wizard: &RequisitionWizard, library: &WeaponLibrary) -> String {
+    match wizard.requisition {
+        Requisition::IncreaseSupplyLimit => "".to_string(),
+        Requisition::RepairAndRecuperate => {
+            let Some(i) = unit.crusade_data.upgrades.iter().enumerate()
+                .filter(|(_, upgrade)| matches!(upgrade, CrusadeUpgrade::BattleScar(..)))
+                .nth(wizard.scar)
+                .map(|(i, _)| i) else {return "no battle scar removed".to_string()};
+            let scar = unit.crusade_data.upgrades.remove(i);
+            format!("removed {}", scar.name())
+        },
+        Requisition::RearmAndResupply => {
+            let Some((weapon, _)) = unit.ranged_weapons.iter_mut().find(|(weapon, _)| weapon.uid == wizard.weapon) else {return "no weapon replaced".to_string()};
+            let Some(entry) = library.weapons.get(&wizard.replacement) else {return "no weapon replaced".to_string()};
+            let old = weapon.name.clone();
+            // the replacement takes the old weapon's uid and charge levels, so charged profiles linked to it still are
+            *weapon = library.resolve(&Weapon {
+                library: Some(LibraryLink {id: wizard.replacement.clone(), ..Default::default()}),
+                uid: wizard.weapon,
+                charge: weapon.charge.clone(),
+                ..entry.clone()
+            });
+            let new = weapon.name.clone();
+            for (weapon, _) in unit.ranged_weapons.iter_mut().chain(unit.melee_weapons.iter_mut()) {
+                if let ChargeLevels::Child(reference, _) = &mut weapon.charge {
+                    if reference.is_id(wizard.weapon) {
+                        reference.name = new.clone();
+                    }
+                }
+            }
+            let before = unit.crusade_data.upgrades.len();
+            unit.crusade_data.upgrades.retain(|upgrade| match upgrade {
+                CrusadeUpgrade::WeaponMod(weapon_mod) => !weapon_mod.target.as_ref().is_some_and(|target| target.is_id(wizard.weapon)),
+                _ => true
+            });
+            let lost = before - unit.crusade_data.upgrades.len();
+            if lost > 0 {
+                format!("replaced {} with {}, losing {} weapon mod(s)", old, new, lost)
+            } else {
+                format!("replaced {} with {}", old, new)
+            }
+        },
+        Requisition::FreshRecruits => {
+            let points = unit.unit_comp.points.unwrap_or(0) + wizard.points;
+            unit.unit_comp.points = Some(points);
+            format!("+{} points, now {}", wizard.points, points)
+        },
+        Requisition::RenownedHeroes => {
+            unit.crusade_data.upgrades.push(CrusadeUpgrade::Relic(wizard.relic.clone(), wizard.tier));
+            format!("gained {} ({} relic)", wizard.relic.name, wizard.tier.to_string())
+        },
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(text: &str) -> Unit {
+        ron::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn rearmed_weapons_keep_their_uid_and_charged_profiles() {
+        let mut unit = unit(r#"(
+            ranged_weapons: [
+                ((name: "Plasma gun", range: Ranged(24), uid: 3, charge: Parent("standard")), 1),
+                ((name: "Plasma gun", range: Ranged(24), uid: 4, charge: Child((name: "Plasma gun", ranged: true, uid: 3), "supercharge")), 1),
+            ],
+            crusade_data: (upgrades: [WeaponMod((name: "Honed", change_one: Attacks, change_two: Skill, target: Some((name: "Plasma gun", ranged: true, uid: 3))))]),
+        )"#);
+        let library: WeaponLibrary = ron::from_str(r#"(weapons: {"melta": (name: "Meltagun", range: Ranged(12))})"#).unwrap();
+        let mut wizard = RequisitionWizard::new(vec![("folder/unit".to_string(), &unit)]);
+        wizard.requisition = Requisition::RearmAndResupply;
+        wizard.weapon = 3;
+        wizard.replacement = "melta".to_string();
+
+        let details = apply_requisition(&mut unit, &wizard, &library);
+        assert_eq!(details, "replaced Plasma gun with Meltagun, losing 1 weapon mod(s)");
+        let (replacement, _) = &unit.ranged_weapons[0];
+        assert_eq!((replacement.name.as_str(), replacement.uid), ("Meltagun", 3));
+        assert_eq!(replacement.charge, ChargeLevels::Parent("standard".to_string()));
+        let ChargeLevels::Child(parent, _) = &unit.ranged_weapons[1].0.charge else {panic!()};
+        assert_eq!((parent.name.as_str(), parent.uid), ("Meltagun", 3));
+        assert!(unit.crusade_data.upgrades.is_empty());
+    }
+
+    #[test]
+    fn relics_over_the_force_limit_are_refused() {
+        let hero = unit(r#"(name: "Captain", keywords: ["CHARACTER"], crusade_unit: true)"#);
+        let other = unit(r#"(name: "Chaplain", keywords: ["CHARACTER"], crusade_unit: true, crusade_data: (upgrades: [Relic((name: "Crozius", description: ""), Artificer)]))"#);
+        let members = vec![("folder/captain".to_string(), &hero), ("folder/chaplain".to_string(), &other)];
+        let mut wizard = RequisitionWizard::new(members.clone());
+        wizard.requisition = Requisition::RenownedHeroes;
+        wizard.relic.name = "Bolter of Ages".to_string();
+
+        let mut force = CrusadeForce {requisition_points: 5, ..Default::default()};
+        assert_eq!(wizard.problem(&force, &members, &WeaponLibrary::default()), None);
+        wizard.relic.name = "Crozius".to_string();
+        assert_eq!(wizard.problem(&force, &members, &WeaponLibrary::default()), Some("Relic 'Crozius' is taken by more than one unit".to_string()));
+        wizard.relic.name = "Bolter of Ages".to_string();
+        force.relic_limit = 1;
+        assert_eq!(wizard.problem(&force, &members, &WeaponLibrary::default()), Some("2 relics, more than the limit of 1".to_string()));
+    }
+}
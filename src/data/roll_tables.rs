@@ -0,0 +1,212 @@
+use std::ops::RangeInclusive;
+
+use egui::{ComboBox, Ui};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use super::{Ability, CrusadeUpgrade, WeaponMod};
+
+
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default, JsonSchema)]
+pub enum Dice {
+    #[default]
+    D6,
+    TwoD6,
+}
+
+impl Dice {
+    pub fn to_string(&self) -> &str {
+        match self {
+            Dice::D6 => "D6",
+            Dice::TwoD6 => "2D6",
+        }
+    }
+
+    pub fn combo_box(&mut self, ui: &mut Ui, id: usize) {
+        ComboBox::from_id_salt(id)
+            .selected_text(self.to_string())
+            .show_ui(ui, |ui| {
+                ui.selectable_value(self, Dice::D6, "D6");
+                ui.selectable_value(self, Dice::TwoD6, "2D6");
+            });
+    }
+
+    /// Every total the dice can roll
+    pub fn totals(&self) -> RangeInclusive<u32> {
+        match self {
+            Dice::D6 => 1..=6,
+            Dice::TwoD6 => 2..=12,
+        }
+    }
+
+    fn roll(&self, rng: &mut impl Rng) -> Vec<u32> {
+        let count = match self {
+            Dice::D6 => 1,
+            Dice::TwoD6 => 2,
+        };
+        (0..count).map(|_| rng.gen_range(1..=6)).collect()
+    }
+}
+
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default, JsonSchema)]
+pub enum RollTableKind {
+    #[default]
+    BattleTrait,
+    WeaponMod,
+    BattleScar,
+}
+
+impl RollTableKind {
+    pub fn to_string(&self) -> &str {
+        match self {
+            RollTableKind::BattleTrait => "Battle Traits",
+            RollTableKind::WeaponMod => "Weapon Mods",
+            RollTableKind::BattleScar => "Battle Scars",
+        }
+    }
+
+    pub fn combo_box(&mut self, ui: &mut Ui, id: usize) {
+        ComboBox::from_id_salt(id)
+            .selected_text(self.to_string())
+            .show_ui(ui, |ui| {
+                ui.selectable_value(self, RollTableKind::BattleTrait, "Battle Traits");
+                ui.selectable_value(self, RollTableKind::WeaponMod, "Weapon Mods");
+                ui.selectable_value(self, RollTableKind::BattleScar, "Battle Scars");
+            });
+    }
+
+    /// Converts an entry to this kind of upgrade, keeping its name
+    fn convert(&self, upgrade: &CrusadeUpgrade) -> CrusadeUpgrade {
+        let (name, description) = match upgrade {
+            CrusadeUpgrade::WeaponMod(weapon_mod) => (weapon_mod.name.clone(), "".to_string()),
+            CrusadeUpgrade::Relic(ability, _) | CrusadeUpgrade::BattleTrait(ability) | CrusadeUpgrade::Enhancement(ability, _) | CrusadeUpgrade::BattleScar(ability, _) => (ability.name.clone(), ability.description.clone()),
+        };
+        match self {
+            RollTableKind::BattleTrait => CrusadeUpgrade::BattleTrait(Ability {name, description}),
+            RollTableKind::BattleScar => match upgrade {
+                CrusadeUpgrade::BattleScar(ability, effects) => CrusadeUpgrade::BattleScar(ability.clone(), effects.clone()),
+                _ => CrusadeUpgrade::BattleScar(Ability {name, description}, Vec::new())
+            },
+            RollTableKind::WeaponMod => match upgrade {
+                CrusadeUpgrade::WeaponMod(weapon_mod) => CrusadeUpgrade::WeaponMod(weapon_mod.clone()),
+                _ => CrusadeUpgrade::WeaponMod(WeaponMod {name, ..Default::default()})
+            },
+        }
+    }
+}
+
+
+/// A table of upgrades with one entry for each total the dice can roll
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, JsonSchema)]
+pub struct RollTable {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub kind: RollTableKind,
+    #[serde(default)]
+    pub dice: Dice,
+    #[serde(default)]
+    pub entries: Vec<CrusadeUpgrade>,
+}
+
+impl RollTable {
+    /// Makes every entry match the table's kind and gives it one entry per total
+    pub fn tidy(&mut self) {
+        for entry in self.entries.iter_mut() {
+            *entry = self.kind.convert(entry);
+        }
+        let count = self.dice.totals().count();
+        let blank = self.kind.convert(&CrusadeUpgrade::BattleTrait(Ability::default()));
+        self.entries.resize(count, blank);
+    }
+
+    /// Rolls the table with a seeded rng, so the result can be checked again from the record
+    pub fn roll(&self, seed: u64) -> (RollRecord, CrusadeUpgrade) {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let dice = self.dice.roll(&mut rng);
+        let total: u32 = dice.iter().sum();
+        let upgrade = self.entries.get((total - self.dice.totals().start()) as usize)
+            .cloned()
+            .unwrap_or_else(|| self.kind.convert(&CrusadeUpgrade::BattleTrait(Ability::default())));
+        let record = RollRecord {
+            table: self.name.clone(),
+            dice,
+            seed,
+            result: upgrade.name(),
+        };
+        (record, upgrade)
+    }
+}
+
+
+/// A roll made on a roll table, kept in the unit's crusade data
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, JsonSchema)]
+pub struct RollRecord {
+    #[serde(default)]
+    pub table: String,
+    #[serde(default)]
+    pub dice: Vec<u32>,
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default)]
+    pub result: String,
+}
+
+impl RollRecord {
+    pub fn to_string(&self) -> String {
+        let dice: Vec<String> = self.dice.iter().map(|die| die.to_string()).collect();
+        let total: u32 = self.dice.iter().sum();
+        let rolled = if self.dice.len() > 1 {format!("{} = {}", dice.join(" + "), total)} else {total.to_string()};
+        format!("{}: rolled {}, {} (seed {})", self.table, rolled, self.result, self.seed)
+    }
+}
+
+
+/// The roll tables of a working folder. Stored in ROLLTABLES.ron next to SETTINGS.ron
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, JsonSchema)]
+pub struct RollTables {
+    #[serde(default)]
+    pub tables: Vec<RollTable>,
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(dice: Dice) -> RollTable {
+        let mut table = RollTable {name: "Scars".to_string(), kind: RollTableKind::BattleScar, dice, entries: Vec::new()};
+        table.tidy();
+        for (i, entry) in table.entries.iter_mut().enumerate() {
+            if let CrusadeUpgrade::BattleScar(ability, _) = entry {
+                ability.name = format!("Scar {}", i);
+            }
+        }
+        table
+    }
+
+    #[test]
+    fn rolls_repeat_from_their_seed() {
+        for dice in [Dice::D6, Dice::TwoD6] {
+            let table = table(dice);
+            for seed in 0..50 {
+                let (record, upgrade) = table.roll(seed);
+                assert_eq!(table.roll(seed), (record.clone(), upgrade.clone()));
+                let total: u32 = record.dice.iter().sum();
+                assert!(dice.totals().contains(&total));
+                assert_eq!(upgrade.name(), format!("Scar {}", total - dice.totals().start()));
+            }
+        }
+    }
+
+    // a saved record has to roll the same dice in later versions
+    #[test]
+    fn seeds_give_the_same_dice_as_before() {
+        let table = table(Dice::TwoD6);
+        assert_eq!(table.roll(1).0.dice, vec![4, 3]);
+    }
+}
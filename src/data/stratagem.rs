@@ -0,0 +1,110 @@
+use egui::{ComboBox, Ui};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use tera::Context;
+
+use super::Unit;
+
+
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default, JsonSchema)]
+pub enum StratagemPhase {
+    #[default]
+    Any,
+    Command,
+    Movement,
+    Shooting,
+    Charge,
+    Fight,
+}
+
+impl StratagemPhase {
+    pub const ALL: [StratagemPhase; 6] = [StratagemPhase::Any, StratagemPhase::Command, StratagemPhase::Movement, StratagemPhase::Shooting, StratagemPhase::Charge, StratagemPhase::Fight];
+
+    pub fn to_string(&self) -> &str {
+        match self {
+            StratagemPhase::Any => "Any Phase",
+            StratagemPhase::Command => "Command Phase",
+            StratagemPhase::Movement => "Movement Phase",
+            StratagemPhase::Shooting => "Shooting Phase",
+            StratagemPhase::Charge => "Charge Phase",
+            StratagemPhase::Fight => "Fight Phase",
+        }
+    }
+
+    pub fn combo_box(&mut self, ui: &mut Ui, id: usize) {
+        ComboBox::from_id_salt(id)
+            .selected_text(self.to_string())
+            .show_ui(ui, |ui| {
+                for phase in StratagemPhase::ALL {
+                    ui.selectable_value(self, phase, phase.to_string());
+                }
+            });
+    }
+
+    /// The colour of the card's header, as css
+    fn colour(&self) -> &str {
+        match self {
+            StratagemPhase::Any => "rgb(60, 60, 60)",
+            StratagemPhase::Command => "rgb(40, 90, 140)",
+            StratagemPhase::Movement => "rgb(60, 120, 60)",
+            StratagemPhase::Shooting => "rgb(170, 110, 20)",
+            StratagemPhase::Charge => "rgb(130, 60, 130)",
+            StratagemPhase::Fight => "rgb(150, 30, 30)",
+        }
+    }
+}
+
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, JsonSchema)]
+pub struct Stratagem {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub cost: u32,
+    #[serde(default)]
+    pub phase: StratagemPhase,
+    /// the name of the detachment the stratagem belongs to, empty for core stratagems
+    #[serde(default)]
+    pub detachment: String,
+    #[serde(default)]
+    pub when: String,
+    #[serde(default)]
+    pub target: String,
+    #[serde(default, alias = "description")]
+    pub effect: String,
+    #[serde(default)]
+    pub restrictions: String,
+    /// keywords a unit must have to be targeted
+    #[serde(default)]
+    pub keywords: Vec<String>,
+}
+
+impl Stratagem {
+    pub fn can_target(&self, unit: &Unit) -> bool {
+        self.keywords.iter().all(|keyword| keyword.trim().is_empty() || unit.has_keyword(keyword))
+    }
+
+    pub fn get_context(&self) -> Context {
+        let mut context = Context::new();
+        context.insert("name", &self.name.to_uppercase());
+        context.insert("cost", &self.cost);
+        context.insert("phase", self.phase.to_string());
+        context.insert("colour", self.phase.colour());
+        context.insert("detachment", if self.detachment.is_empty() {"Core Stratagem"} else {&self.detachment});
+        context.insert("when", &self.when);
+        context.insert("target", &self.target);
+        context.insert("effect", &self.effect);
+        context.insert("restrictions", &self.restrictions);
+        context.insert("keywords", &self.keywords.iter().map(|keyword| keyword.to_uppercase()).collect::<Vec<String>>());
+        context
+    }
+}
+
+
+/// The stratagems of a working folder. Stored in STRATAGEMS.ron next to SETTINGS.ron
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, JsonSchema)]
+pub struct Stratagems {
+    #[serde(default)]
+    pub stratagems: Vec<Stratagem>,
+}
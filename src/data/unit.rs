@@ -1,11 +1,16 @@
-use crate::data::{abilities::CoreAbility, crusade_data::CrusadeUnitData};
+use crate::data::{abilities::CoreAbility, crusade_data::{CrusadeUnitData, CrusadeUpgrade, ScarEffect}};
 
-use super::{unit_composition::UnitComposition, unit_stats::UnitStats, Ability, Weapon, WeaponRenderTuple};
+use super::{unit_base::UnitBase, AssignedEnhancement, unit_composition::UnitComposition, unit_stats::UnitStats, Ability, Weapon};
+use std::collections::BTreeMap;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use tera::Context;
 
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+/// A weapon's render data followed by the originals of its changed columns
+type StruckWeaponTuple = (String, String, String, String, String, String, String, String, Vec<String>);
+
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
 pub struct Unit {
     #[serde(default)]
     pub name: String,
@@ -45,7 +50,16 @@ pub struct Unit {
     #[serde(default)]
     pub crusade_data: CrusadeUnitData,
     #[serde(default)]
-    pub crusade_weapons: (Vec<(Weapon, u32)>, Vec<(Weapon, u32)>) // used for display in crusade, allows us to apply weapon upgrades
+    pub crusade_weapons: (Vec<(Weapon, u32)>, Vec<(Weapon, u32)>), // used for display in crusade, allows us to apply weapon upgrades
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crusade_stats: Option<UnitStats>, // the stats with battle scars applied, when they change any
+
+    /// an enhancement from one of the folder's detachments, for characters outside of crusade
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enhancement: Option<AssignedEnhancement>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<UnitBase>
 }
 
 impl Default for Unit {
@@ -72,17 +86,62 @@ impl Default for Unit {
 
             crusade_unit: false,
             crusade_data: CrusadeUnitData::default(),
-            crusade_weapons: (Vec::new(), Vec::new())
+            crusade_weapons: (Vec::new(), Vec::new()),
+            crusade_stats: None,
+
+            enhancement: None,
+
+            base: None
         }
     }
 }
 
 impl Unit {
 
-    pub fn get_movement(&self) -> String {
-        if self.keywords.contains(&"AIRCRAFT".to_string()) {
-            return "20+".to_string();
-        } else {return format!("{}", self.stats.movement);}
+    pub fn is_character(&self) -> bool {
+        self.keywords.contains(&"CHARACTER".to_string())
+    }
+
+    /// Whether the unit has a keyword or faction keyword, ignoring case
+    pub fn has_keyword(&self, keyword: &str) -> bool {
+        self.keywords.iter().chain(std::iter::once(&self.faction_keyword)).any(|other| other.trim().eq_ignore_ascii_case(keyword.trim()))
+    }
+
+    /// The points of the detachment enhancement assigned to the unit, if it has one
+    pub fn enhancement_points(&self) -> u32 {
+        self.enhancement.as_ref().map(|assigned| assigned.enhancement.points).unwrap_or(0)
+    }
+
+    /// The datasheet stats as (name, original, shown), which only differ where a battle scar has changed a crusade unit
+    pub fn stat_values(&self) -> Vec<(&'static str, String, String)> {
+        let aircraft = self.keywords.contains(&"AIRCRAFT".to_string());
+        let values = |stats: &UnitStats| {
+            let mut values = vec![
+                ("M", if aircraft {"20+\"".to_string()} else {format!("{}\"", stats.movement)}),
+                ("T", stats.toughness.to_string()),
+                ("Sv", format!("{}+", stats.save)),
+            ];
+            if let Some(invuln) = stats.invuln {
+                values.push(("Inv", format!("{}++", invuln)));
+            }
+            values.push(("W", stats.wounds.to_string()));
+            values.push(("Ld", format!("{}+", stats.leadership)));
+            values.push(("OC", stats.oc.to_string()));
+            values
+        };
+        let shown = match (self.crusade_unit, &self.crusade_stats) {
+            (true, Some(stats)) => stats,
+            _ => &self.stats
+        };
+        values(&self.stats).into_iter().zip(values(shown)).map(|((name, original), (_, shown))| (name, original, shown)).collect()
+    }
+
+    /// The weapon a crusade weapon was made from, to show what weapon mods and battle scars changed
+    pub fn original_weapon(&self, weapon: &Weapon) -> Option<&Weapon> {
+        if !self.crusade_unit {
+            return None;
+        }
+        self.ranged_weapons.iter().chain(self.melee_weapons.iter()).map(|(original, _)| original).find(|original| original.uid == weapon.uid)
     }
 
     // pub fn format_keywords(&self) -> String{
@@ -99,26 +158,41 @@ impl Unit {
     //     keywords
     // }
 
-    fn get_ranged_weapon_list(&self) -> Vec<WeaponRenderTuple> {
+    fn get_ranged_weapon_list(&self) -> Vec<StruckWeaponTuple> {
         let mut res = Vec::new();
-        for (weapon, _) in self.ranged_weapons.iter() {
-            res.push(weapon.get_render_data());
+        let weapons = if self.crusade_unit {&self.crusade_weapons.0} else {&self.ranged_weapons};
+        for (weapon, _) in weapons.iter() {
+            res.push(self.struck_render_data(weapon));
         }
         res
     }
 
-    fn get_melee_weapon_list(&self) -> Vec<WeaponRenderTuple> {
+    fn get_melee_weapon_list(&self) -> Vec<StruckWeaponTuple> {
         let mut res = Vec::new();
-        for (weapon, _) in self.melee_weapons.iter() {
-            res.push(weapon.get_render_data());
+        let weapons = if self.crusade_unit {&self.crusade_weapons.1} else {&self.melee_weapons};
+        for (weapon, _) in weapons.iter() {
+            res.push(self.struck_render_data(weapon));
         }
         res
     }
 
+    /// Render data for the html template, followed by the original of every column a weapon mod or battle scar changed, empty if unchanged
+    fn struck_render_data(&self, weapon: &Weapon) -> StruckWeaponTuple {
+        let (originals, shown): (Vec<String>, Vec<String>) = weapon.column_values(self.original_weapon(weapon)).into_iter()
+            .map(|(original, shown)| (if original == shown {String::new()} else {original}, shown))
+            .unzip();
+        let data = weapon.get_render_data();
+        (data.0, shown[0].clone(), shown[1].clone(), shown[2].clone(), shown[3].clone(), shown[4].clone(), shown[5].clone(), data.7, originals)
+    }
+
     pub fn get_context(
         &self
     ) -> Context {
         let mut context = Context::new();
+        let stats = match (self.crusade_unit, &self.crusade_stats) {
+            (true, Some(stats)) => stats,
+            _ => &self.stats
+        };
 
         let mut cased_keywords = Vec::new();
         for keyword in self.keywords.iter() {
@@ -127,7 +201,17 @@ impl Unit {
         
         
         context.insert("unit_name", &self.name);
-        self.stats.add_context(&mut context);
+        stats.add_context(&mut context);
+        // originals of the stats a battle scar changed, the template strikes them through. It adds the " and + itself
+        let mut original_stats = BTreeMap::new();
+        for (name, original, shown) in self.stat_values() {
+            if original != shown {
+                let key = match name {"M" => "movement", "T" => "toughness", "Sv" => "save", "W" => "wounds", "Ld" => "leadership", "OC" => "oc", _ => continue};
+                original_stats.insert(key, original.trim_end_matches(['"', '+']).to_string());
+                context.insert(key, shown.trim_end_matches(['"', '+']));
+            }
+        }
+        context.insert("original_stats", &original_stats);
 
         // aircraft movement box
         if cased_keywords.contains(&"AIRCRAFT".to_string()) {
@@ -157,6 +241,28 @@ impl Unit {
         context.insert("leader", &self.leader.clone().unwrap_or(Vec::new()));
         context.insert("wargear_options", "none");
 
+        // crusade rank, crusade points and upgrades as (type, name, description)
+        let crusade_upgrades: Vec<(String, String, String)> = self.crusade_data.upgrades.iter().map(|upgrade| {
+            let description = match upgrade {
+                CrusadeUpgrade::WeaponMod(weapon_mod) => format!("{}, {}{}", weapon_mod.change_one.to_string(), weapon_mod.change_two.to_string(), weapon_mod.target.as_ref().map(|target| format!(" on {}", target.name)).unwrap_or("".to_string())),
+                CrusadeUpgrade::BattleScar(ability, effects) => format!("{}{}", ability.description, ScarEffect::list(effects)),
+                CrusadeUpgrade::Relic(ability, _) | CrusadeUpgrade::BattleTrait(ability) | CrusadeUpgrade::Enhancement(ability, _) => ability.description.clone(),
+            };
+            let kind = match upgrade {
+                CrusadeUpgrade::Relic(_, tier) => format!("{} Relic", tier.to_string()),
+                _ => upgrade.to_string().to_string()
+            };
+            (kind, upgrade.name(), description)
+        }).collect();
+        // detachment enhancement as (detachment, name, points, description)
+        let enhancement = self.enhancement.as_ref().map(|assigned| (&assigned.detachment, &assigned.enhancement.name, assigned.enhancement.points, &assigned.enhancement.description));
+        context.insert("enhancement", &enhancement);
+        context.insert("crusade_unit", &self.crusade_unit);
+        context.insert("crusade_rank", self.crusade_data.rank.to_string());
+        context.insert("crusade_exp", &self.crusade_data.exp);
+        context.insert("crusade_points", &self.crusade_data.crusade_points());
+        context.insert("crusade_upgrades", &crusade_upgrades);
+
         context
     }
 
@@ -0,0 +1,299 @@
+use std::{collections::BTreeMap, fmt, marker::PhantomData};
+
+use serde::{de::{self, Visitor}, Deserialize, Deserializer, Serialize};
+use schemars::JsonSchema;
+
+use super::{unit_composition::UnitComposition, Ability, ChargeLevels, CoreAbility, CrusadeUpgrade, Unit, UnitEditData, UnitStats, Weapon, WeaponMod};
+
+
+
+/// Marks a unit as a variant of another, storing only what differs from it.
+/// `unit` is "folder/filename" of the base unit
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
+pub struct UnitBase {
+    pub unit: String,
+    #[serde(default)]
+    pub overrides: UnitOverrides,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
+pub struct UnitOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<UnitStats>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra_statlines: Option<(String, Vec<(String, UnitStats)>)>,
+
+    /// names of removed weapons, only used by variants saved before weapons were removed by uid
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_weapons: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_weapon_uids: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added_ranged_weapons: Vec<(Weapon, u32)>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added_melee_weapons: Vec<(Weapon, u32)>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "double_option")]
+    pub faction_ability: Option<Option<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub core_abilities: Option<Vec<CoreAbility>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_abilities: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added_abilities: Vec<Ability>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub faction_keyword: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_keywords: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added_keywords: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "double_option")]
+    pub damaged: Option<Option<u32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "double_option")]
+    pub leader: Option<Option<Vec<String>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit_comp: Option<UnitComposition>,
+}
+
+/// Reads an override that can clear a value. JSON and YAML write a cleared value as null, which would otherwise read back as no override
+fn double_option<'de, T: Deserialize<'de>, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Option<T>>, D::Error> {
+    struct DoubleOption<T>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>> Visitor<'de> for DoubleOption<T> {
+        type Value = Option<Option<T>>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("an optional value")
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(Some(None))
+        }
+
+        // ron writes Some(None) and Some(Some(value)), JSON and YAML write the value itself
+        fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+            Option::<T>::deserialize(deserializer).map(Some)
+        }
+    }
+
+    deserializer.deserialize_option(DoubleOption(PhantomData))
+}
+
+fn differs<T: PartialEq + Clone>(value: &T, base: &T) -> Option<T> {
+    if value == base {None} else {Some(value.clone())}
+}
+
+/// items of `list` not exactly in `base`, and items of `base` no longer exactly in `list`
+fn list_diff<T: PartialEq + Clone>(list: &[T], base: &[T]) -> (Vec<T>, Vec<T>) {
+    let added = list.iter().filter(|item| !base.contains(item)).cloned().collect();
+    let removed = base.iter().filter(|item| !list.contains(item)).cloned().collect();
+    (added, removed)
+}
+
+/// base items not removed, followed by the added ones
+fn list_apply<T: Clone>(base: &[T], added: &[T], removed: impl Fn(&T) -> bool) -> Vec<T> {
+    base.iter().filter(|item| !removed(item)).chain(added.iter()).cloned().collect()
+}
+
+/// Weapons a variant adds take uids from a block above every uid of its base, so weapons the base gains later can't take them
+pub const VARIANT_UID_BLOCK: u32 = 1 << 16;
+
+impl UnitOverrides {
+
+    /// Everything about `unit` that differs from `base`
+    pub fn diff(unit: &Unit, base: &Unit) -> Self {
+        let (added_ranged_weapons, removed_ranged) = list_diff(&unit.ranged_weapons, &base.ranged_weapons);
+        let (added_melee_weapons, removed_melee) = list_diff(&unit.melee_weapons, &base.melee_weapons);
+        let removed: Vec<&Weapon> = removed_ranged.iter().chain(removed_melee.iter()).map(|(weapon, _)| weapon).collect();
+        let (added_abilities, removed_abilities) = list_diff(&unit.unique_abilities, &base.unique_abilities);
+        let (added_keywords, removed_keywords) = list_diff(&unit.keywords, &base.keywords);
+
+        Self {
+            name: differs(&unit.name, &base.name),
+            stats: differs(&unit.stats, &base.stats),
+            extra_statlines: differs(&unit.extra_statlines, &base.extra_statlines),
+            removed_weapons: removed.iter().filter(|weapon| weapon.uid == 0).map(|weapon| weapon.name.clone()).collect(),
+            removed_weapon_uids: removed.iter().filter(|weapon| weapon.uid != 0).map(|weapon| weapon.uid).collect(),
+            added_ranged_weapons,
+            added_melee_weapons,
+            faction_ability: differs(&unit.faction_ability, &base.faction_ability),
+            core_abilities: differs(&unit.core_abilities, &base.core_abilities),
+            removed_abilities: removed_abilities.into_iter().map(|ability| ability.name).collect(),
+            added_abilities,
+            faction_keyword: differs(&unit.faction_keyword, &base.faction_keyword),
+            removed_keywords,
+            added_keywords,
+            damaged: differs(&unit.damaged, &base.damaged),
+            leader: differs(&unit.leader, &base.leader),
+            unit_comp: differs(&unit.unit_comp, &base.unit_comp),
+        }
+    }
+
+    /// Whether a weapon of the base unit is removed, by uid or by the name of an older save
+    pub fn removes_weapon(&self, weapon: &Weapon) -> bool {
+        (weapon.uid != 0 && self.removed_weapon_uids.contains(&weapon.uid)) || self.removed_weapons.contains(&weapon.name)
+    }
+
+    /// The base unit with these overrides applied. Crusade progress always belongs to `unit` and is never inherited
+    pub fn apply(&self, base: &Unit, unit: &Unit) -> Unit {
+        Unit {
+            name: self.name.clone().unwrap_or(base.name.clone()),
+            stats: self.stats.unwrap_or(base.stats),
+            extra_statlines: self.extra_statlines.clone().unwrap_or(base.extra_statlines.clone()),
+            ranged_weapons: list_apply(&base.ranged_weapons, &self.added_ranged_weapons, |(weapon, _)| self.removes_weapon(weapon)),
+            melee_weapons: list_apply(&base.melee_weapons, &self.added_melee_weapons, |(weapon, _)| self.removes_weapon(weapon)),
+            faction_ability: self.faction_ability.clone().unwrap_or(base.faction_ability.clone()),
+            core_abilities: self.core_abilities.clone().unwrap_or(base.core_abilities.clone()),
+            unique_abilities: list_apply(&base.unique_abilities, &self.added_abilities, |ability| self.removed_abilities.contains(&ability.name)),
+            faction_keyword: self.faction_keyword.clone().unwrap_or(base.faction_keyword.clone()),
+            keywords: list_apply(&base.keywords, &self.added_keywords, |keyword| self.removed_keywords.contains(keyword)),
+            damaged: self.damaged.unwrap_or(base.damaged),
+            leader: self.leader.clone().unwrap_or(base.leader.clone()),
+            unit_comp: self.unit_comp.clone().unwrap_or(base.unit_comp.clone()),
+            crusade_unit: unit.crusade_unit,
+            crusade_data: unit.crusade_data.clone(),
+            crusade_weapons: unit.crusade_weapons.clone(),
+            crusade_stats: unit.crusade_stats,
+            enhancement: unit.enhancement.clone(),
+            base: unit.base.clone(),
+        }
+    }
+}
+
+impl Unit {
+    /// This variant rebuilt from its base unit and overrides, with its crusade weapons brought up to date.
+    /// Variants saved before added weapons had their own uids are moved on the way
+    pub fn resolve_variant(&self, base: &Unit) -> Unit {
+        let mut unit = self.clone();
+        unit.separate_variant_uids(base);
+        let Some(unit_base) = &unit.base else {return unit};
+        let mut resolved = unit_base.overrides.apply(base, &unit);
+        resolved.assign_weapon_uids();
+        UnitEditData::from((&resolved, String::new())).into()
+    }
+
+    /// Moves the weapons a variant adds into the uid block above its base's uids, pointing the variant's references at the new uids.
+    /// Added weapons replacing a removed base weapon are edited copies of it and keep its uid. Returns whether anything moved
+    pub fn separate_variant_uids(&mut self, base: &Unit) -> bool {
+        let Some(unit_base) = &mut self.base else {return false};
+        let overrides = &mut unit_base.overrides;
+        let base_weapons: Vec<&Weapon> = base.ranged_weapons.iter().chain(base.melee_weapons.iter()).map(|(weapon, _)| weapon).collect();
+        let start = (base_weapons.iter().map(|weapon| weapon.uid).max().unwrap_or(0) / VARIANT_UID_BLOCK + 1) * VARIANT_UID_BLOCK;
+        let replaced: Vec<u32> = base_weapons.iter().filter(|weapon| overrides.removes_weapon(weapon)).map(|weapon| weapon.uid).collect();
+
+        let added = overrides.added_ranged_weapons.iter().chain(overrides.added_melee_weapons.iter());
+        let mut next = added.map(|(weapon, _)| weapon.uid + 1).max().unwrap_or(0).max(start);
+        let mut moved = BTreeMap::new();
+        for (weapon, _) in overrides.added_ranged_weapons.iter_mut().chain(overrides.added_melee_weapons.iter_mut()) {
+            if weapon.uid < start && !replaced.contains(&weapon.uid) {
+                moved.insert(weapon.uid, next);
+                weapon.uid = next;
+                next += 1;
+            }
+        }
+        if moved.is_empty() {
+            return false;
+        }
+
+        let charges = overrides.added_ranged_weapons.iter_mut().chain(overrides.added_melee_weapons.iter_mut())
+            .filter_map(|(weapon, _)| match &mut weapon.charge {
+                ChargeLevels::Child(reference, _) => Some(reference),
+                _ => None
+            });
+        let targets = self.crusade_data.upgrades.iter_mut().filter_map(|upgrade| match upgrade {
+            CrusadeUpgrade::WeaponMod(WeaponMod {target: Some(reference), ..}) => Some(reference),
+            _ => None
+        });
+        for reference in charges.chain(targets) {
+            if let Some(uid) = moved.get(&reference.uid) {
+                reference.uid = *uid;
+            }
+        }
+        true
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(text: &str) -> Unit {
+        ron::from_str(text).unwrap()
+    }
+
+    fn weapon_uids(unit: &Unit) -> Vec<(String, u32)> {
+        unit.ranged_weapons.iter().map(|(weapon, _)| (weapon.name.clone(), weapon.uid)).collect()
+    }
+
+    #[test]
+    fn applying_the_diff_rebuilds_the_unit() {
+        let base = unit(r#"(name: "Base", ranged_weapons: [((name: "Bolter", range: Ranged(24), uid: 1), 1), ((name: "Flamer", range: Ranged(12), uid: 2), 1)], keywords: ["INFANTRY", "IMPERIUM"])"#);
+        let variant = unit(r#"(name: "Variant", ranged_weapons: [((name: "Bolter", range: Ranged(30), uid: 1), 1), ((name: "Plasma", range: Ranged(24), uid: 3), 1)], keywords: ["INFANTRY", "GRENADES"])"#);
+        let overrides = UnitOverrides::diff(&variant, &base);
+        assert_eq!(overrides.removed_weapon_uids, vec![1, 2]);
+        assert_eq!(overrides.removed_keywords, vec!["IMPERIUM".to_string()]);
+
+        let applied = overrides.apply(&base, &variant);
+        assert_eq!(weapon_uids(&applied), weapon_uids(&variant));
+        assert_eq!(applied.keywords, variant.keywords);
+        assert_eq!(applied.name, "Variant");
+    }
+
+    #[test]
+    fn removed_weapons_stay_removed_when_renamed() {
+        let base = unit(r#"(ranged_weapons: [((name: "Bolter", range: Ranged(24), uid: 1), 1), ((name: "Flamer", range: Ranged(24), uid: 2), 1)])"#);
+        let variant = unit(r#"(ranged_weapons: [((name: "Bolter", range: Ranged(24), uid: 1), 1)])"#);
+        let overrides = UnitOverrides::diff(&variant, &base);
+
+        let renamed = unit(r#"(ranged_weapons: [((name: "Bolter", range: Ranged(24), uid: 1), 1), ((name: "Heavy Flamer", range: Ranged(24), uid: 2), 1)])"#);
+        assert_eq!(weapon_uids(&overrides.apply(&renamed, &variant)), vec![("Bolter".to_string(), 1)]);
+
+        // older saves remove by name
+        let legacy = UnitOverrides {removed_weapons: vec!["Flamer".to_string()], ..Default::default()};
+        assert_eq!(weapon_uids(&legacy.apply(&base, &variant)), vec![("Bolter".to_string(), 1)]);
+    }
+
+    #[test]
+    fn added_weapons_keep_their_uid_when_the_base_gains_one() {
+        let base = unit(r#"(ranged_weapons: [((name: "Bolter", range: Ranged(24), uid: 1), 1)])"#);
+        let mut variant = unit(r#"(
+            ranged_weapons: [((name: "Bolter", range: Ranged(24), uid: 1), 1), ((name: "Plasma", range: Ranged(24), uid: 2), 1)],
+            crusade_data: (upgrades: [WeaponMod((name: "Master-worked", change_one: Attacks, change_two: Skill, target: Some((name: "Plasma", ranged: true, uid: 2))))]),
+        )"#);
+        variant.base = Some(UnitBase {unit: "folder/base".to_string(), overrides: UnitOverrides::diff(&variant, &base)});
+        assert!(variant.separate_variant_uids(&base));
+        assert!(!variant.separate_variant_uids(&base));
+        let resolved = variant.base.as_ref().unwrap().overrides.apply(&base, &variant);
+        let plasma = resolved.ranged_weapons[1].0.uid;
+        assert_eq!(plasma, VARIANT_UID_BLOCK);
+        let CrusadeUpgrade::WeaponMod(weapon_mod) = &resolved.crusade_data.upgrades[0] else {panic!()};
+        assert_eq!(weapon_mod.target.as_ref().unwrap().uid, plasma);
+
+        let grown = unit(r#"(ranged_weapons: [((name: "Bolter", range: Ranged(24), uid: 1), 1), ((name: "Flamer", range: Ranged(24), uid: 2), 1)])"#);
+        variant.separate_variant_uids(&grown);
+        let mut resolved = variant.base.as_ref().unwrap().overrides.apply(&grown, &variant);
+        assert!(!resolved.assign_weapon_uids());
+        assert_eq!(weapon_uids(&resolved), vec![("Bolter".to_string(), 1), ("Flamer".to_string(), 2), ("Plasma".to_string(), plasma)]);
+    }
+
+    #[test]
+    fn cleared_overrides_survive_every_format() {
+        let overrides = UnitOverrides {faction_ability: Some(None), damaged: Some(None), leader: Some(None), ..Default::default()};
+        let json: UnitOverrides = serde_json::from_str(&serde_json::to_string(&overrides).unwrap()).unwrap();
+        let yaml: UnitOverrides = serde_yaml::from_str(&serde_yaml::to_string(&overrides).unwrap()).unwrap();
+        let ron: UnitOverrides = ron::from_str(&ron::to_string(&overrides).unwrap()).unwrap();
+        assert_eq!(json, overrides);
+        assert_eq!(yaml, overrides);
+        assert_eq!(ron, overrides);
+
+        let set = UnitOverrides {faction_ability: Some(Some("Oath of Moment".to_string())), damaged: Some(Some(3)), ..Default::default()};
+        assert_eq!(serde_json::from_str::<UnitOverrides>(&serde_json::to_string(&set).unwrap()).unwrap(), set);
+        assert_eq!(ron::from_str::<UnitOverrides>(&ron::to_string(&set).unwrap()).unwrap(), set);
+        assert_eq!(serde_json::from_str::<UnitOverrides>("{}").unwrap(), UnitOverrides::default());
+    }
+}
@@ -1,15 +1,26 @@
 use super::Wargear;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
 pub struct UnitComposition {
-    comp: Vec<(u32, String, Vec<Wargear>)>
+    #[serde(default)]
+    comp: Vec<(u32, String, Vec<Wargear>)>,
+    #[serde(default)]
+    pub points: Option<u32>,
 }
 
 impl Default for UnitComposition {
     fn default() -> Self {
         UnitComposition {
-            comp: Vec::new()
+            comp: Vec::new(),
+            points: None
         }
     }
-}
\ No newline at end of file
+}
+
+impl UnitComposition {
+    pub fn add_model(&mut self, count: u32, name: String) {
+        self.comp.push((count, name, Vec::new()));
+    }
+}
@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use super::{BattleResult, CrusadeRank, CrusadeUnitData, CrusadeUpgrade};
+
+
+
+/// Something that happened to a crusade unit
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
+pub enum HistoryEvent {
+    Battle {
+        #[serde(default)]
+        opponent: String,
+        #[serde(default)]
+        result: BattleResult,
+        #[serde(default)]
+        kills: u32,
+        #[serde(default)]
+        destroyed: bool,
+        #[serde(default)]
+        marked_for_greatness: bool,
+    },
+    /// experience gained or lost, negative when it was lowered by hand
+    Exp(i32),
+    RankUp(CrusadeRank),
+    /// the kind of upgrade and its name
+    UpgradeAdded(String, String),
+    UpgradeRemoved(String, String),
+    BattleScar(String),
+}
+
+impl HistoryEvent {
+    pub fn to_string(&self) -> String {
+        match self {
+            HistoryEvent::Battle {opponent, result, kills, destroyed, marked_for_greatness} => {
+                let mut line = format!("{} vs {}, {} kills", result.to_string(), if opponent.is_empty() {"Unknown"} else {opponent}, kills);
+                if *marked_for_greatness {
+                    line += ", Marked for Greatness";
+                }
+                if *destroyed {
+                    line += ", destroyed";
+                }
+                line
+            },
+            HistoryEvent::Exp(exp) => if *exp >= 0 {format!("+{} XP", exp)} else {format!("{} XP", exp)},
+            HistoryEvent::RankUp(rank) => format!("Reached {}", rank.to_string()),
+            HistoryEvent::UpgradeAdded(kind, name) => format!("Gained {}: {}", kind, name),
+            HistoryEvent::UpgradeRemoved(kind, name) => format!("Lost {}: {}", kind, name),
+            HistoryEvent::BattleScar(name) => format!("Battle Scar: {}", name),
+        }
+    }
+}
+
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
+pub struct HistoryEntry {
+    #[serde(default)]
+    pub date: String,
+    pub event: HistoryEvent,
+}
+
+
+/// Totals worked out from a unit's history
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct HistoryStats {
+    pub battles: u32,
+    pub victories: u32,
+    pub destroyed: u32,
+    pub kills: u32,
+}
+
+impl HistoryStats {
+    /// The share of battles the unit was not destroyed in, as a percentage
+    pub fn survival_rate(&self) -> f32 {
+        if self.battles == 0 {return 100.0}
+        100.0 * (self.battles - self.destroyed) as f32 / self.battles as f32
+    }
+
+    /// Kills for each time the unit was destroyed, or its kills if it never has been
+    pub fn kill_ratio(&self) -> f32 {
+        self.kills as f32 / self.destroyed.max(1) as f32
+    }
+
+    pub fn to_string(&self) -> String {
+        format!("{} battles, {} victories, {:.0}% survival, {} kills ({:.1} per loss)", self.battles, self.victories, self.survival_rate(), self.kills, self.kill_ratio())
+    }
+}
+
+
+impl CrusadeUnitData {
+    /// Adds an entry for every way the unit changed since `previous`: experience, rank and upgrades gained or lost
+    pub fn record_history(&mut self, previous: &CrusadeUnitData, date: &str) {
+        let mut events = Vec::new();
+        if self.exp != previous.exp {
+            events.push(HistoryEvent::Exp(self.exp as i32 - previous.exp as i32));
+        }
+        for rank in CrusadeRank::ALL.iter().filter(|rank| rank.level() > previous.rank.level() && rank.level() <= self.rank.level()) {
+            events.push(HistoryEvent::RankUp(*rank));
+        }
+
+        let key = |upgrade: &CrusadeUpgrade| (upgrade.to_string().to_string(), upgrade.name());
+        let mut removed: Vec<(String, String)> = previous.upgrades.iter().map(key).collect();
+        for upgrade in self.upgrades.iter() {
+            let upgrade_key = key(upgrade);
+            if let Some(i) = removed.iter().position(|other| *other == upgrade_key) {
+                removed.remove(i);
+            } else if matches!(upgrade, CrusadeUpgrade::BattleScar(..)) {
+                events.push(HistoryEvent::BattleScar(upgrade_key.1));
+            } else {
+                events.push(HistoryEvent::UpgradeAdded(upgrade_key.0, upgrade_key.1));
+            }
+        }
+        events.extend(removed.into_iter().map(|(kind, name)| HistoryEvent::UpgradeRemoved(kind, name)));
+
+        self.history.extend(events.into_iter().map(|event| HistoryEntry {date: date.to_string(), event}));
+    }
+
+    pub fn history_stats(&self) -> HistoryStats {
+        let mut stats = HistoryStats::default();
+        for entry in self.history.iter() {
+            if let HistoryEvent::Battle {result, kills, destroyed, ..} = &entry.event {
+                stats.battles += 1;
+                stats.kills += kills;
+                if *result == BattleResult::Victory {
+                    stats.victories += 1;
+                }
+                if *destroyed {
+                    stats.destroyed += 1;
+                }
+            }
+        }
+        stats
+    }
+}
@@ -0,0 +1,341 @@
+use egui::Color32;
+
+use super::{ChargeLevels, CoreAbility, CrusadeUpgrade, Range, Unit, UnitStats, VariableValue, Weapon, WeaponAbility, WeaponReference};
+
+
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+pub enum Severity {
+    Warning,
+    Error
+}
+
+impl Severity {
+    pub fn to_string(&self) -> &str {
+        match self {
+            Severity::Warning => "Warning",
+            Severity::Error => "Error",
+        }
+    }
+
+    pub fn colour(&self) -> Color32 {
+        match self {
+            Severity::Warning => Color32::ORANGE,
+            Severity::Error => Color32::RED,
+        }
+    }
+}
+
+
+/// Something wrong with a datasheet. `location` names the field, e.g. "Ranged Weapons > Bolter > Skill"
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub severity: Severity,
+    pub location: String,
+    pub message: String,
+}
+
+impl Problem {
+    pub fn to_string(&self) -> String {
+        format!("{} at {}: {}", self.severity.to_string(), self.location, self.message)
+    }
+}
+
+
+struct Problems(Vec<Problem>);
+
+impl Problems {
+    fn warn(&mut self, location: &str, message: &str) {
+        self.0.push(Problem {severity: Severity::Warning, location: location.to_string(), message: message.to_string()});
+    }
+
+    fn error(&mut self, location: &str, message: &str) {
+        self.0.push(Problem {severity: Severity::Error, location: location.to_string(), message: message.to_string()});
+    }
+}
+
+
+fn variable_is_zero(value: &VariableValue) -> bool {
+    matches!(value, VariableValue::Set(0) | VariableValue::Rolled(0, _, _))
+}
+
+/// Keywords the weapon editor only offers for one kind of weapon
+fn ranged_only(keyword: &WeaponAbility) -> bool {
+    matches!(keyword, WeaponAbility::Assault | WeaponAbility::RapidFire(_, _) | WeaponAbility::Pistol | WeaponAbility::Torrent
+        | WeaponAbility::Indirect | WeaponAbility::Blast | WeaponAbility::Melta(_) | WeaponAbility::Heavy | WeaponAbility::Conversion)
+}
+
+fn melee_only(keyword: &WeaponAbility) -> bool {
+    matches!(keyword, WeaponAbility::Lance | WeaponAbility::ExtraAttacks)
+}
+
+fn validate_stats(stats: &UnitStats, location: &str, problems: &mut Problems) {
+    if stats.toughness == 0 {
+        problems.error(&format!("{} > Toughness", location), "toughness must be at least 1");
+    }
+    if !(2..=6).contains(&stats.save) {
+        problems.error(&format!("{} > Save", location), "save must be between 2+ and 6+");
+    }
+    if let Some(invuln) = stats.invuln {
+        if !(2..=6).contains(&invuln) {
+            problems.error(&format!("{} > Invulnerable Save", location), "invulnerable save must be between 2+ and 6+");
+        } else if invuln >= stats.save && (2..=6).contains(&stats.save) {
+            problems.warn(&format!("{} > Invulnerable Save", location), "invulnerable save is no better than the save");
+        }
+    }
+    if stats.wounds == 0 {
+        problems.error(&format!("{} > Wounds", location), "wounds must be at least 1");
+    }
+    if !(2..=9).contains(&stats.leadership) {
+        problems.warn(&format!("{} > Leadership", location), "leadership is usually between 2+ and 9+");
+    }
+}
+
+fn weapon_exists<'a>(unit: &'a Unit, reference: &WeaponReference) -> Option<&'a Weapon> {
+    unit.ranged_weapons.iter().chain(unit.melee_weapons.iter()).map(|(weapon, _)| weapon).find(|weapon| reference.is_id(weapon.uid))
+}
+
+fn validate_weapon(unit: &Unit, weapon: &Weapon, count: u32, ranged: bool, location: &str, problems: &mut Problems) {
+    let at = |field: &str| format!("{} > {}", location, field);
+
+    if weapon.name.trim().is_empty() {
+        problems.warn(location, "weapon has no name");
+    }
+    if count == 0 {
+        problems.warn(&at("Count"), "weapon count is 0");
+    }
+    match (ranged, weapon.range) {
+        (true, Range::Melee) => problems.error(&at("Range"), "ranged weapon has melee range"),
+        (true, Range::Ranged(0)) => problems.error(&at("Range"), "range must be at least 1\""),
+        (false, Range::Ranged(_)) => problems.error(&at("Range"), "melee weapon has a range"),
+        _ => ()
+    }
+    if variable_is_zero(&weapon.attacks) {
+        problems.error(&at("Attacks"), "attacks must be at least 1");
+    }
+    if !weapon.keywords.contains(&WeaponAbility::Torrent) && !(2..=6).contains(&weapon.skill) {
+        problems.error(&at("Skill"), "skill must be between 2+ and 6+");
+    }
+    if weapon.strength == 0 {
+        problems.error(&at("Strength"), "strength must be at least 1");
+    }
+    if variable_is_zero(&weapon.damage) {
+        problems.error(&at("Damage"), "damage must be at least 1");
+    }
+
+    for (i, keyword) in weapon.keywords.iter().enumerate() {
+        let keyword_at = at(&format!("Keywords > {}", keyword.to_string()));
+        if weapon.keywords[..i].contains(keyword) && keyword != &WeaponAbility::None {
+            problems.warn(&keyword_at, "duplicate keyword");
+        }
+        if !ranged && ranged_only(keyword) {
+            problems.error(&keyword_at, "keyword only applies to ranged weapons");
+        }
+        if ranged && melee_only(keyword) {
+            problems.error(&keyword_at, "keyword only applies to melee weapons");
+        }
+        match keyword {
+            WeaponAbility::AntiX(target, value) => {
+                if target.trim().is_empty() {
+                    problems.error(&keyword_at, "anti keyword has no target");
+                }
+                if !(2..=6).contains(value) {
+                    problems.warn(&keyword_at, &format!("anti {}+ is outside 2+ to 6+ and is shown as {}+", value, value.clamp(&2, &6)));
+                }
+            },
+            WeaponAbility::Melta(0) => problems.warn(&keyword_at, "melta value is 0"),
+            WeaponAbility::RapidFire(value, _) | WeaponAbility::Sustained(value, _) if variable_is_zero(value) => {
+                problems.warn(&keyword_at, "keyword value is 0");
+            },
+            WeaponAbility::Custom(name) if name.trim().is_empty() => problems.warn(&keyword_at, "custom keyword has no text"),
+            _ => ()
+        }
+    }
+
+    if let ChargeLevels::Child(parent, _) = &weapon.charge {
+        match weapon_exists(unit, parent) {
+            None => problems.error(&at("Charge"), &format!("charge level points to missing weapon '{}'", parent.name)),
+            Some(target) => {
+                if !matches!(target.charge, ChargeLevels::Parent(_)) {
+                    problems.error(&at("Charge"), &format!("charge level points to '{}', which has no charge levels", target.name));
+                } else if target.name != parent.name {
+                    problems.warn(&at("Charge"), &format!("charge level was made for '{}' but now points to '{}'", parent.name, target.name));
+                }
+            }
+        }
+    }
+}
+
+impl Unit {
+    /// Checks a datasheet for values that are out of range, contradictory or point to something missing
+    pub fn validate(&self) -> Vec<Problem> {
+        let mut problems = Problems(Vec::new());
+
+        if self.name.trim().is_empty() {
+            problems.warn("Name", "unit has no name");
+        }
+
+        validate_stats(&self.stats, "Stats", &mut problems);
+        for (name, stats) in self.extra_statlines.1.iter() {
+            validate_stats(stats, &format!("Stats > {}", name), &mut problems);
+        }
+        if let Some(damaged) = self.damaged {
+            if damaged >= self.stats.wounds {
+                problems.warn("Damaged", "damaged profile starts at or above the unit's wounds");
+            }
+        }
+
+        for (weapon, count) in self.ranged_weapons.iter() {
+            validate_weapon(self, weapon, *count, true, &format!("Ranged Weapons > {}", weapon.name), &mut problems);
+        }
+        for (weapon, count) in self.melee_weapons.iter() {
+            validate_weapon(self, weapon, *count, false, &format!("Melee Weapons > {}", weapon.name), &mut problems);
+        }
+
+        for (i, ability) in self.core_abilities.iter().enumerate() {
+            let at = format!("Core Abilities > {}", ability.to_string());
+            if ability != &CoreAbility::None && self.core_abilities[..i].contains(ability) {
+                problems.warn(&at, "duplicate core ability");
+            }
+            if let CoreAbility::FeelnoPain(value) = ability {
+                if !(2..=6).contains(value) {
+                    problems.error(&at, "feel no pain must be between 2+ and 6+");
+                }
+            }
+        }
+        let is_leader = self.core_abilities.contains(&CoreAbility::Leader);
+        match (&self.leader, is_leader) {
+            (Some(_), false) => problems.warn("Leader", "unit lists units it can lead but does not have the Leader ability"),
+            (None, true) => problems.warn("Leader", "unit has the Leader ability but no units it can lead"),
+            _ => ()
+        }
+        for ability in self.unique_abilities.iter() {
+            if ability.name.trim().is_empty() {
+                problems.warn("Abilities", "ability has no name");
+            } else if ability.description.trim().is_empty() {
+                problems.warn(&format!("Abilities > {}", ability.name), "ability has no description");
+            }
+        }
+
+        if self.faction_keyword.trim().is_empty() {
+            problems.warn("Faction Keyword", "unit has no faction keyword");
+        }
+        for (i, keyword) in self.keywords.iter().enumerate() {
+            let at = format!("Keywords > {}", keyword);
+            if keyword.trim().is_empty() {
+                problems.warn("Keywords", "empty keyword");
+            } else if keyword.trim().eq_ignore_ascii_case(self.faction_keyword.trim()) {
+                problems.warn(&at, "keyword duplicates the faction keyword");
+            } else if self.keywords[..i].iter().any(|other| other.trim().eq_ignore_ascii_case(keyword.trim())) {
+                problems.warn(&at, "duplicate keyword");
+            }
+        }
+
+        if let Some(assigned) = &self.enhancement {
+            let at = format!("Enhancement > {}", assigned.enhancement.name);
+            let missing = assigned.enhancement.missing_keywords(self);
+            if !missing.is_empty() {
+                problems.error(&at, &format!("unit is missing the keywords {} needed for the enhancement", missing.join(", ")));
+            }
+            if self.crusade_unit && self.crusade_data.upgrades.iter().any(|upgrade| matches!(upgrade, CrusadeUpgrade::Enhancement(..))) {
+                problems.error(&at, "unit already has an enhancement in its crusade upgrades");
+            }
+        }
+
+        if self.crusade_unit {
+            for upgrade in self.crusade_data.upgrades.iter() {
+                if let CrusadeUpgrade::WeaponMod(weapon_mod) = upgrade {
+                    let at = format!("Crusade Upgrades > {}", weapon_mod.name);
+                    match &weapon_mod.target {
+                        None => problems.warn(&at, "weapon modification has no target weapon"),
+                        Some(target) => match weapon_exists(self, target) {
+                            None => problems.error(&at, &format!("weapon modification points to missing weapon '{}'", target.name)),
+                            Some(weapon) if weapon.name != target.name => {
+                                problems.warn(&at, &format!("weapon modification was made for '{}' but now points to '{}'", target.name, weapon.name));
+                            },
+                            _ => ()
+                        }
+                    }
+                }
+                if let CrusadeUpgrade::Relic(ability, tier) = upgrade {
+                    if self.crusade_data.rank.level() < tier.min_rank().level() {
+                        problems.warn(&format!("Crusade Upgrades > {}", ability.name), &format!("{} relics need the unit to be {}", tier.to_string(), tier.min_rank().to_string()));
+                    }
+                }
+            }
+            for kind in ["Relic", "Enhancement"] {
+                let count = self.crusade_data.upgrades.iter().filter(|upgrade| upgrade.to_string() == kind).count();
+                if count > 1 {
+                    problems.error("Crusade Upgrades", &format!("unit has {} {}s but can only have one", count, kind.to_lowercase()));
+                }
+                if count > 0 && !self.is_character() {
+                    problems.warn("Crusade Upgrades", &format!("only CHARACTER units can have a {}", kind.to_lowercase()));
+                }
+            }
+        }
+
+        problems.0.sort_by_key(|problem| std::cmp::Reverse(problem.severity));
+        problems.0
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::WeaponMod;
+
+    fn unit(text: &str) -> Unit {
+        ron::from_str(text).unwrap()
+    }
+
+    fn messages(unit: &Unit) -> Vec<String> {
+        unit.validate().iter().map(|problem| problem.to_string()).collect()
+    }
+
+    const INTERCESSORS: &str = r#"(
+        name: "Intercessors",
+        stats: (movement: 6, toughness: 4, save: 3, wounds: 2, leadership: 6, oc: 2),
+        ranged_weapons: [((name: "Bolt rifle", range: Ranged(24), attacks: Set(2), skill: 3, strength: 4, ap: 1, damage: Set(1), keywords: [Assault, Heavy], uid: 1), 5)],
+        melee_weapons: [((name: "Close combat weapon", range: Melee, attacks: Set(3), skill: 3, strength: 4, damage: Set(1), uid: 2), 5)],
+        faction_keyword: "Adeptus Astartes",
+        keywords: ["INFANTRY", "BATTLELINE"],
+    )"#;
+
+    #[test]
+    fn a_complete_datasheet_has_no_problems() {
+        assert_eq!(messages(&unit(INTERCESSORS)), Vec::<String>::new());
+    }
+
+    #[test]
+    fn errors_come_before_warnings() {
+        let mut broken = unit(INTERCESSORS);
+        broken.name = String::new();
+        broken.stats.save = 7;
+        broken.keywords.push("INFANTRY".to_string());
+        broken.melee_weapons[0].0.keywords.push(WeaponAbility::Torrent);
+        broken.ranged_weapons[0].0.charge = ChargeLevels::Child(WeaponReference::new("Plasma".to_string(), true, 9), "Supercharge".to_string());
+        assert_eq!(messages(&broken), vec![
+            "Error at Stats > Save: save must be between 2+ and 6+",
+            "Error at Ranged Weapons > Bolt rifle > Charge: charge level points to missing weapon 'Plasma'",
+            "Error at Melee Weapons > Close combat weapon > Keywords > TORRENT: keyword only applies to ranged weapons",
+            "Warning at Name: unit has no name",
+            "Warning at Keywords > INFANTRY: duplicate keyword",
+        ]);
+    }
+
+    #[test]
+    fn weapon_mods_follow_renamed_weapons() {
+        let mut modded = unit(INTERCESSORS);
+        modded.crusade_unit = true;
+        modded.crusade_data.upgrades.push(CrusadeUpgrade::WeaponMod(WeaponMod {
+            name: "Master-worked".to_string(),
+            target: Some(WeaponReference::new("Boltgun".to_string(), true, 1)),
+            ..Default::default()
+        }));
+        assert_eq!(messages(&modded), vec!["Warning at Crusade Upgrades > Master-worked: weapon modification was made for 'Boltgun' but now points to 'Bolt rifle'"]);
+
+        modded.ranged_weapons.clear();
+        assert_eq!(messages(&modded), vec!["Error at Crusade Upgrades > Master-worked: weapon modification points to missing weapon 'Boltgun'"]);
+    }
+}
@@ -1,8 +1,9 @@
 
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use regex::Regex;
 
-#[derive(Debug, Deserialize, Clone, Copy, Serialize, PartialEq)]
+#[derive(Debug, Deserialize, Clone, Copy, Serialize, PartialEq, JsonSchema)]
 pub enum Dice {
     D3,
     D6
@@ -20,7 +21,7 @@ impl Dice {
 
 
 
-#[derive(Debug, Deserialize, Clone, Copy, Serialize, PartialEq)]
+#[derive(Debug, Deserialize, Clone, Copy, Serialize, PartialEq, JsonSchema)]
 pub enum VariableValue {
     Set(u32),
     Rolled(u32, Dice, u32)
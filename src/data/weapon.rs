@@ -1,11 +1,12 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 
-use crate::data::{abilities::WeaponAbility, index::WeaponReference};
+use crate::data::{abilities::WeaponAbility, index::WeaponReference, weapon_library::LibraryLink};
 
 use super::variable_val::VariableValue;
 
 
-#[derive(Debug, Deserialize, Clone, Copy, Serialize)]
+#[derive(Debug, Deserialize, Clone, Copy, Serialize, PartialEq, JsonSchema)]
 pub enum Range {
     Melee,
     Ranged(u32)
@@ -20,7 +21,7 @@ impl Range {
     }
 }
 
-#[derive(Debug, Default, Deserialize, Clone, Serialize)]
+#[derive(Debug, Default, Deserialize, Clone, Serialize, PartialEq, JsonSchema)]
 pub enum ChargeLevels {
     #[default]
     None,
@@ -47,7 +48,7 @@ impl ChargeLevels {
 }
 
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
 pub struct Weapon {
     #[serde(default)]
     pub name: String,
@@ -65,13 +66,32 @@ pub struct Weapon {
     #[serde(default)]
     pub keywords: Vec<WeaponAbility>,
     #[serde(default)]
-    pub charge: ChargeLevels
+    pub charge: ChargeLevels,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub library: Option<LibraryLink>,
+    /// unique within the unit, used by charge levels and weapon mods to find the weapon. 0 until assigned
+    #[serde(default)]
+    pub uid: u32
 }
 
-pub type WeaponRenderTuple = (String, String, String, String, u32, String, String, String);
+pub type WeaponRenderTuple = (String, String, String, String, String, String, String, String);
 
 impl Weapon {
 
+    /// The range, A, skill, S, AP and D columns as (original, shown), against the weapon a crusade weapon was made from
+    pub fn column_values(&self, original: Option<&Weapon>) -> Vec<(String, String)> {
+        let shown = self.get_render_data();
+        let original = original.map(|original| original.get_render_data()).unwrap_or(shown.clone());
+        vec![
+            (original.1, shown.1),
+            (original.2, shown.2),
+            (original.3, shown.3),
+            (original.4, shown.4),
+            (original.5, shown.5),
+            (original.6, shown.6),
+        ]
+    }
+
     pub fn get_render_data(&self) -> WeaponRenderTuple {
         let skill: String;
         if self.keywords.contains(&WeaponAbility::Torrent) {
@@ -92,13 +112,23 @@ impl Weapon {
             self.range.to_string(),
             self.attacks.to_string(),
             skill,
-            self.strength,
+            self.strength.to_string(),
             ap,
             self.damage.to_string(),
             self.format_keywords()
         )
     }
 
+    pub fn get_title(&self, count: u32) -> String {
+        let (weapon_name, level_name) = match &self.charge {
+            ChargeLevels::None => (self.name.clone(), "".to_string()),
+            ChargeLevels::Parent(level_name) => (self.name.clone(), level_name.clone()),
+            ChargeLevels::Child(parent_ref, level_name) => (parent_ref.name.clone(), level_name.clone())
+        };
+        let name = if level_name == "" {weapon_name} else {format!("{} - {}", weapon_name, level_name)};
+        if count == 1 {name} else {format!("{}x {}", count, name)}
+    }
+
     pub fn format_keywords(&self) -> String{
         if self.keywords.len() == 0 {return "[]".to_string();} // zero keywords case
         if self.keywords.len() == 1 && self.keywords[0] == WeaponAbility::None {return "[]".to_string();}
@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use super::{Range, Unit, VariableValue, Weapon, WeaponAbility};
+
+
+
+/// Weapons shared by every unit in a working folder, keyed by id. Stored in WEAPONS.ron next to SETTINGS.ron
+#[derive(Debug, Default, Deserialize, Serialize, Clone, JsonSchema)]
+pub struct WeaponLibrary {
+    #[serde(default)]
+    pub weapons: BTreeMap<String, Weapon>,
+}
+
+/// Marks a unit's weapon as a copy of a library entry, with any fields that differ from it
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
+pub struct LibraryLink {
+    pub id: String,
+    #[serde(default)]
+    pub overrides: WeaponOverrides,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
+pub struct WeaponOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<Range>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attacks: Option<VariableValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skill: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strength: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ap: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub damage: Option<VariableValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<Vec<WeaponAbility>>,
+}
+
+fn differs<T: PartialEq + Clone>(value: &T, base: &T) -> Option<T> {
+    if value == base {None} else {Some(value.clone())}
+}
+
+impl WeaponLibrary {
+
+    /// The concrete profile of a weapon, built from its library entry if it links to one
+    pub fn resolve(&self, weapon: &Weapon) -> Weapon {
+        let Some(link) = &weapon.library else {return weapon.clone()};
+        let Some(entry) = self.weapons.get(&link.id) else {return weapon.clone()};
+
+        let overrides = &link.overrides;
+        Weapon {
+            name: overrides.name.clone().unwrap_or(entry.name.clone()),
+            range: overrides.range.unwrap_or(entry.range),
+            attacks: overrides.attacks.unwrap_or(entry.attacks),
+            skill: overrides.skill.unwrap_or(entry.skill),
+            strength: overrides.strength.unwrap_or(entry.strength),
+            ap: overrides.ap.unwrap_or(entry.ap),
+            damage: overrides.damage.unwrap_or(entry.damage),
+            keywords: overrides.keywords.clone().unwrap_or(entry.keywords.clone()),
+            // charge levels belong to the unit, not the shared profile
+            charge: weapon.charge.clone(),
+            library: Some(link.clone()),
+            uid: weapon.uid,
+        }
+    }
+
+    /// Recalculates the overrides of a linked weapon from how it differs to its library entry
+    pub fn relink(&self, weapon: &mut Weapon) {
+        let Some(link) = &mut weapon.library else {return};
+        let Some(entry) = self.weapons.get(&link.id) else {return};
+
+        link.overrides = WeaponOverrides {
+            name: differs(&weapon.name, &entry.name),
+            range: differs(&weapon.range, &entry.range),
+            attacks: differs(&weapon.attacks, &entry.attacks),
+            skill: differs(&weapon.skill, &entry.skill),
+            strength: differs(&weapon.strength, &entry.strength),
+            ap: differs(&weapon.ap, &entry.ap),
+            damage: differs(&weapon.damage, &entry.damage),
+            keywords: differs(&weapon.keywords, &entry.keywords),
+        };
+    }
+
+    pub fn relink_unit(&self, unit: &mut Unit) {
+        for (weapon, _) in unit.ranged_weapons.iter_mut().chain(unit.melee_weapons.iter_mut()) {
+            self.relink(weapon);
+        }
+    }
+
+    /// Resolves every linked weapon of a unit, following any renamed ids, and returns whether anything changed
+    pub fn resolve_unit(&self, unit: &mut Unit, renames: &BTreeMap<String, String>) -> bool {
+        let mut changed = false;
+        for (weapon, _) in unit.ranged_weapons.iter_mut().chain(unit.melee_weapons.iter_mut()) {
+            if let Some(link) = &mut weapon.library {
+                if let Some(new_id) = renames.get(&link.id) {
+                    link.id = new_id.clone();
+                }
+            }
+            let resolved = self.resolve(weapon);
+            if ron::to_string(&resolved).ok() != ron::to_string(weapon).ok() {
+                *weapon = resolved;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+impl Unit {
+    pub fn uses_library_weapon(&self, id: &str) -> bool {
+        self.ranged_weapons.iter().chain(self.melee_weapons.iter())
+            .any(|(weapon, _)| weapon.library.as_ref().is_some_and(|link| link.id == id))
+    }
+}
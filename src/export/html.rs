@@ -7,10 +7,39 @@ use crate::data::Unit;
 
 
 
-pub fn export_to_html(unit: &Unit, template: &Tera, path: PathBuf) -> Result<(), std::io::Error>  {
+pub fn export_to_html(unit: &Unit, template: &Tera, reference: Option<String>, path: PathBuf) -> Result<(), std::io::Error>  {
     let context = unit.get_context();
     
-    let rendered = template.render("datasheet", &context).unwrap();
+    let mut rendered = template.render("datasheet", &context).unwrap();
+    if let Some(reference) = reference {
+        rendered = rendered.replace("</body>", &format!("{}</body>", reference));
+    }
 
     write(path, rendered)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{data::UnitEditData, export::load_export_templates};
+
+    use super::*;
+
+    #[test]
+    fn changed_values_are_struck_by_the_template() {
+        let unit: Unit = ron::from_str(r#"(
+            name: "Scarred",
+            stats: (movement: 6, toughness: 4, save: 3, wounds: 2, leadership: 6, oc: 2),
+            ranged_weapons: [((name: "Bolter", range: Ranged(24), skill: 3, strength: 4, uid: 1), 1)],
+            crusade_unit: true,
+            crusade_data: (upgrades: [BattleScar((name: "Limp", description: ""), [Movement, HitPenalty])]),
+        )"#).unwrap();
+        let unit: Unit = UnitEditData::from((&unit, "scarred".to_string())).into();
+        let mut context = unit.get_context();
+        // the unit composition section isn't filled in by get_context yet
+        context.insert("unit_composition", &Vec::<(u32, u32)>::new());
+        let rendered = load_export_templates().html.render("datasheet", &context).unwrap();
+        assert!(rendered.contains("<s>6</s> 5\""), "movement is not struck");
+        assert!(rendered.contains("<s>3+</s> 4+"), "skill is not struck");
+        assert_eq!(rendered.matches("<s>").count(), 2);
+    }
+}
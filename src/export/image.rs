@@ -0,0 +1,32 @@
+use std::{fs::write, path::PathBuf};
+
+use headless_chrome::{protocol::cdp::Page::CaptureScreenshotFormatOption, Browser, LaunchOptions};
+use tera::Tera;
+use crate::data::Unit;
+use anyhow::Result;
+
+use super::embedded_fonts;
+
+// css pixels per inch used by chrome
+const CSS_DPI: f64 = 96.0;
+
+
+pub fn export_to_image(unit: &Unit, template: &Tera, format: CaptureScreenshotFormatOption, dpi: u32, path: PathBuf) -> Result<()>  {
+    let context = unit.get_context();
+    
+    let rendered = format!("{}{}", embedded_fonts(), template.render("datasheet", &context)?);
+
+
+    let browser = Browser::new(LaunchOptions::default())?;
+    let tab = browser.new_tab()?;
+    tab.navigate_to(format!("data:text/html;charset=utf-8,{}", rendered).as_str())?;
+    tab.wait_until_navigated()?;
+
+    let mut clip = tab.find_element(".container")?.get_box_model()?.margin_viewport();
+    clip.scale = dpi as f64 / CSS_DPI;
+
+    let bytes = tab.capture_screenshot(format, None, Some(clip), true)?;
+    
+    write(path, bytes)?;
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use std::{collections::BTreeMap, fs::write, path::PathBuf};
+
+use anyhow::Result;
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+
+use crate::data::Unit;
+
+
+/// A whole subfolder of units, keyed by filename
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+pub struct FolderFile {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub units: BTreeMap<String, Unit>,
+}
+
+
+pub fn export_to_json(unit: &Unit, path: PathBuf) -> Result<()> {
+    write(path, serde_json::to_string_pretty(unit)?)?;
+    Ok(())
+}
+
+pub fn export_to_yaml(unit: &Unit, path: PathBuf) -> Result<()> {
+    write(path, serde_yaml::to_string(unit)?)?;
+    Ok(())
+}
+
+pub fn export_folder(folder: &FolderFile, yaml: bool, path: PathBuf) -> Result<()> {
+    let s = if yaml {
+        serde_yaml::to_string(folder)?
+    } else {
+        serde_json::to_string_pretty(folder)?
+    };
+    write(path, s)?;
+    Ok(())
+}
+
+
+pub fn unit_schema() -> String {
+    serde_json::to_string_pretty(&schema_for!(Unit)).unwrap()
+}
+
+pub fn folder_schema() -> String {
+    serde_json::to_string_pretty(&schema_for!(FolderFile)).unwrap()
+}
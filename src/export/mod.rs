@@ -1,16 +1,42 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use headless_chrome::protocol::cdp::Page::CaptureScreenshotFormatOption;
 use include_assets::{include_dir, NamedArchive};
 use tera::Tera;
 use std::{path::PathBuf, str};
-use crate::data::Unit;
+use crate::{app::DatasheetAppSettings, data::{Glossary, Unit}};
 mod pdf;
 mod html;
+mod image;
+mod json;
+mod svg;
+mod text;
+mod tts;
+mod print_profile;
+mod reference;
+mod order_of_battle;
+mod stratagems;
+
+pub use print_profile::{default_print_profiles, PrintProfile};
+pub use text::{unit_to_markdown, unit_to_text};
+pub use json::{export_folder, folder_schema, unit_schema, FolderFile};
+pub use tts::{export_to_tts, unit_to_tts};
+pub use order_of_battle::{export_agenda_tracker, export_order_of_battle};
+pub use stratagems::export_stratagem_cards;
 
 
 #[derive(PartialEq, Clone, Copy)]
 pub enum ExportType {
     PDF,
     // LATEX,
-    HTML
+    HTML,
+    PNG,
+    WEBP,
+    SVG,
+    MARKDOWN,
+    TEXT,
+    JSON,
+    YAML,
+    TTS
 }
 
 impl ExportType {
@@ -19,6 +45,14 @@ impl ExportType {
             ExportType::PDF => "PDF".to_string(),
             // ExportType::LATEX => "LaTeX".to_string(),
             ExportType::HTML => "HTML".to_string(),
+            ExportType::PNG => "PNG".to_string(),
+            ExportType::WEBP => "WebP".to_string(),
+            ExportType::SVG => "SVG".to_string(),
+            ExportType::MARKDOWN => "Markdown".to_string(),
+            ExportType::TEXT => "Plain Text".to_string(),
+            ExportType::JSON => "JSON".to_string(),
+            ExportType::YAML => "YAML".to_string(),
+            ExportType::TTS => "Tabletop Simulator".to_string(),
         }
     }
 
@@ -27,9 +61,21 @@ impl ExportType {
             ExportType::PDF => &["pdf"],
             // ExportType::LATEX => &["tex"],
             ExportType::HTML => &["html"],
+            ExportType::PNG => &["png"],
+            ExportType::WEBP => &["webp"],
+            ExportType::SVG => &["svg"],
+            ExportType::MARKDOWN => &["md"],
+            ExportType::TEXT => &["txt"],
+            ExportType::JSON => &["json"],
+            ExportType::YAML => &["yaml"],
+            ExportType::TTS => &["json"],
         }
     }
 
+    pub fn is_image(&self) -> bool {
+        *self == ExportType::PNG || *self == ExportType::WEBP
+    }
+
     
 }
 
@@ -59,6 +105,18 @@ pub fn load_export_templates() -> ExportTemplates {
         };
         let mut tera = Tera::default();
         let _ = tera.add_raw_template("datasheet", template);
+
+        let back = match str::from_utf8(templates.get("card_back.html").unwrap()) {
+            Ok(v) => v,
+            Err(e) => panic!("Invalid UTF-8 sequence in Card Back Template: {}", e)
+        };
+        let _ = tera.add_raw_template("card_back", back);
+
+        let stratagem = match str::from_utf8(templates.get("stratagem.html").unwrap()) {
+            Ok(v) => v,
+            Err(e) => panic!("Invalid UTF-8 sequence in Stratagem Template: {}", e)
+        };
+        let _ = tera.add_raw_template("stratagem", stratagem);
         tera
     };
 
@@ -71,10 +129,48 @@ pub fn load_export_templates() -> ExportTemplates {
     }
 }
 
-pub fn export_unit(unit: &Unit, export_type: ExportType, export_path: PathBuf, export_templates: &ExportTemplates) {
+pub fn export_unit(unit: &Unit, export_type: ExportType, export_path: PathBuf, export_templates: &ExportTemplates, settings: &DatasheetAppSettings, glossary: &Glossary) -> anyhow::Result<()> {
+    let entries = if settings.rules_reference {glossary.used_by(&[unit])} else {Vec::new()};
+    let html_reference = if entries.is_empty() {None} else {Some(reference::reference_html(&entries))};
     match export_type {
-        ExportType::PDF => pdf::export_to_pdf(unit, &export_templates.html, export_path).unwrap(),
-        // ExportType::LATEX => latex::export_to_latex(unit, &export_templates.latex, export_path).unwrap(),
-        ExportType::HTML => html::export_to_html(unit, &export_templates.html, export_path).unwrap(),
+        ExportType::PDF => pdf::export_to_pdf(&[unit], &export_templates.html, &settings.print_profile(), html_reference, export_path)?,
+        // ExportType::LATEX => latex::export_to_latex(unit, &export_templates.latex, export_path)?,
+        ExportType::HTML => html::export_to_html(unit, &export_templates.html, html_reference, export_path)?,
+        ExportType::PNG => image::export_to_image(unit, &export_templates.html, CaptureScreenshotFormatOption::Png, settings.image_dpi, export_path)?,
+        ExportType::WEBP => image::export_to_image(unit, &export_templates.html, CaptureScreenshotFormatOption::Webp, settings.image_dpi, export_path)?,
+        ExportType::SVG => svg::export_to_svg(unit, settings, export_path)?,
+        ExportType::MARKDOWN => text::export_to_markdown(unit, reference::reference_markdown(&entries), export_path)?,
+        ExportType::TEXT => text::export_to_text(unit, reference::reference_text(&entries), export_path)?,
+        ExportType::JSON => json::export_to_json(unit, export_path)?,
+        ExportType::YAML => json::export_to_yaml(unit, export_path)?,
+        ExportType::TTS => tts::export_to_tts(&[unit], &unit.name, export_path)?,
     };
-}
\ No newline at end of file
+    Ok(())
+}
+
+/// The shown value, led by the original wrapped in `open` and `close` when a weapon mod or battle scar changed it
+fn struck(original: &str, shown: &str, open: &str, close: &str) -> String {
+    if original == shown {
+        shown.to_string()
+    } else {
+        format!("{}{}{} {}", open, original, close, shown)
+    }
+}
+
+/// css declaring the bundled fonts, so rendered datasheets don't depend on what is installed
+pub fn embedded_fonts() -> String {
+    let assets = NamedArchive::load(include_dir!("assets"));
+    let conduit = STANDARD.encode(assets.get("Conduit-ITC-Std-Font.otf").unwrap());
+    let roboto = STANDARD.encode(assets.get("Roboto_Condensed-Bold.ttf").unwrap());
+
+    format!(
+        "<style>@font-face {{ font-family: ConduitITCStd; src: url(data:font/otf;base64,{}); }} @font-face {{ font-family: Roboto; src: url(data:font/ttf;base64,{}); }}</style>",
+        conduit, roboto
+    )
+}
+
+pub fn print_units(units: &[&Unit], export_path: PathBuf, export_templates: &ExportTemplates, settings: &DatasheetAppSettings, glossary: &Glossary) -> anyhow::Result<()> {
+    let entries = if settings.rules_reference {glossary.used_by(units)} else {Vec::new()};
+    let html_reference = if entries.is_empty() {None} else {Some(reference::reference_html(&entries))};
+    pdf::export_to_pdf(units, &export_templates.html, &settings.print_profile(), html_reference, export_path)
+}
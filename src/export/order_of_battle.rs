@@ -0,0 +1,115 @@
+use std::{fs::write, path::PathBuf};
+
+use anyhow::Result;
+use headless_chrome::{Browser, LaunchOptions};
+
+use crate::data::{Agendas, CrusadeForce, Unit};
+
+use super::embedded_fonts;
+
+
+
+fn html_head() -> String {
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\">{}<style>\
+        body {{ font-family: Roboto, sans-serif; margin: 20px; }}\
+        h1, h2 {{ font-family: ConduitITCStd, sans-serif; margin-bottom: 4px; }}\
+        table {{ border-collapse: collapse; width: 100%; margin-top: 12px; }}\
+        th, td {{ border: 1px solid rgb(68, 68, 68); padding: 4px 6px; text-align: left; vertical-align: top; }}\
+        th {{ background: rgb(221, 221, 221); }}\
+        td.tally {{ height: 28px; min-width: 80px; }}\
+        </style></head><body>",
+        embedded_fonts()
+    )
+}
+
+/// A printable roster of the force and every member unit
+pub fn order_of_battle_html(force: &CrusadeForce, members: &[&Unit]) -> String {
+    let mut html = html_head();
+
+    html.push_str(&format!("<h1>{}</h1>", escape(if force.name.is_empty() {"Order of Battle"} else {&force.name})));
+    if !force.faction.is_empty() {
+        html.push_str(&format!("<h2>{}</h2>", escape(&force.faction)));
+    }
+    html.push_str(&format!(
+        "<p>Supply: {} / {} &nbsp; Requisition Points: {} &nbsp; Battles: {} &nbsp; Victories: {} &nbsp; Crusade Points: {}</p>",
+        force.supply_used(members), force.supply_limit, force.requisition_points, force.battles, force.victories, force.total_crusade_points(members)
+    ));
+
+    html.push_str("<table><tr><th>Unit</th><th>Points</th><th>Rank</th><th>XP</th><th>Crusade Points</th><th>Kills</th><th>Honours and Scars</th></tr>");
+    for unit in members {
+        let upgrades: Vec<String> = unit.crusade_data.upgrades.iter().map(|upgrade| format!("{}: {}", upgrade.to_string(), escape(&upgrade.name()))).collect();
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape(&unit.name),
+            unit.unit_comp.points.map(|points| points.to_string()).unwrap_or("-".to_string()),
+            unit.crusade_data.rank.to_string(),
+            unit.crusade_data.exp,
+            unit.crusade_data.crusade_points(),
+            unit.crusade_data.kills,
+            upgrades.join("<br>")
+        ));
+    }
+    html.push_str("</table>");
+
+    if !force.notes.is_empty() {
+        html.push_str(&format!("<h2>Notes</h2><p>{}</p>", escape(&force.notes).replace("\n", "<br>")));
+    }
+    html.push_str("</body></html>");
+    html
+}
+
+/// A sheet for tallying agendas during a battle, with a blank box for every unit and agenda
+pub fn agenda_tracker_html(agendas: &Agendas, members: &[&Unit]) -> String {
+    let mut html = html_head();
+    html.push_str("<h1>Agenda Tracker</h1>");
+    html.push_str("<p>Date: ____________ &nbsp; Opponent: ______________________ &nbsp; Mission: ______________________</p>");
+
+    html.push_str("<table><tr><th>Agenda</th><th>XP</th><th>Description</th></tr>");
+    for agenda in agendas.agendas.iter() {
+        let exp = if agenda.max_exp > 0 {format!("{} per tally, up to {}", agenda.exp_per_tally, agenda.max_exp)} else {format!("{} per tally", agenda.exp_per_tally)};
+        html.push_str(&format!("<tr><td>{}</td><td>{}</td><td>{}</td></tr>", escape(&agenda.name), exp, escape(&agenda.description).replace("\n", "<br>")));
+    }
+    html.push_str("</table>");
+
+    html.push_str("<table><tr><th>Unit</th>");
+    for agenda in agendas.agendas.iter() {
+        html.push_str(&format!("<th>{}</th>", escape(&agenda.name)));
+    }
+    html.push_str("<th>Kills</th></tr>");
+    for unit in members {
+        html.push_str(&format!("<tr><td>{}</td>", escape(&unit.name)));
+        for _ in 0..=agendas.agendas.len() {
+            html.push_str("<td class=\"tally\"></td>");
+        }
+        html.push_str("</tr>");
+    }
+    html.push_str("</table></body></html>");
+    html
+}
+
+/// Writes the roster as html, or prints it to pdf if the path ends in .pdf
+pub fn export_order_of_battle(force: &CrusadeForce, members: &[&Unit], path: PathBuf) -> Result<()> {
+    write_html(order_of_battle_html(force, members), path)
+}
+
+/// Writes the agenda tracker as html, or prints it to pdf if the path ends in .pdf
+pub fn export_agenda_tracker(agendas: &Agendas, members: &[&Unit], path: PathBuf) -> Result<()> {
+    write_html(agenda_tracker_html(agendas, members), path)
+}
+
+fn write_html(html: String, path: PathBuf) -> Result<()> {
+    if path.extension().is_some_and(|extension| extension == "pdf") {
+        let browser = Browser::new(LaunchOptions::default())?;
+        let tab = browser.new_tab()?;
+        tab.navigate_to(format!("data:text/html;charset=utf-8,{}", html).as_str())?;
+        write(path, tab.print_to_pdf(None)?)?;
+    } else {
+        write(path, html)?;
+    }
+    Ok(())
+}
+
+fn escape(content: &str) -> String {
+    content.replace("&", "&amp;").replace("<", "&lt;").replace(">", "&gt;")
+}
@@ -1,26 +1,57 @@
 use std::{fs::write, path::PathBuf};
 
-use headless_chrome::{types::PrintToPdfOptions, Browser, LaunchOptions};
+use headless_chrome::{Browser, LaunchOptions};
 use tera::Tera;
 use crate::data::Unit;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 
+use super::print_profile::PrintProfile;
 
 
-pub fn export_to_pdf(unit: &Unit, template: &Tera, path: PathBuf) -> Result<()>  {
-    let context = unit.get_context();
-    
-    let rendered = template.render("datasheet", &context).unwrap();
 
+pub fn export_to_pdf(units: &[&Unit], template: &Tera, profile: &PrintProfile, reference: Option<String>, path: PathBuf) -> Result<()>  {
+    if units.is_empty() {
+        return Err(anyhow!("There are no datasheets to print"));
+    }
+
+    let mut head = String::new();
+    let mut fronts = Vec::new();
+    let mut backs = Vec::new();
+    for unit in units {
+        let context = unit.get_context();
+        let rendered = template.render("datasheet", &context)?;
+        let (front_head, front) = split_head(&rendered);
+        // every datasheet has the same styles, so the head is only needed once
+        if head.is_empty() {
+            head = front_head.to_string();
+        }
+        fronts.push(front.to_string());
+        if profile.duplex_backs {
+            backs.push(template.render("card_back", &context)?);
+        }
+    }
+    let mut rendered = profile.impose(&head, fronts, backs);
+    if let Some(reference) = reference {
+        rendered = rendered.replace("</body></html>", &format!("{}</body></html>", reference));
+    }
 
-    let browser = Browser::new(LaunchOptions::default()).unwrap();
+
+    let browser = Browser::new(LaunchOptions::default())?;
     let tab = browser.new_tab()?;
     tab.navigate_to(format!("data:text/html;charset=utf-8,{}", rendered).as_str())?;
-    let bytes = tab.print_to_pdf(Some(PrintToPdfOptions {
-        print_background: Some(true),
-        ..Default::default()
-    })).unwrap();
+    let bytes = tab.print_to_pdf(Some(profile.pdf_options()))?;
     
     write(path, bytes)?;
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Splits a rendered template into the contents of its head and the markup after it
+fn split_head(rendered: &str) -> (&str, &str) {
+    let Some((before, after)) = rendered.split_once("</head>") else {return ("", rendered)};
+    let head = before.split_once("<head>").map(|(_, head)| head).unwrap_or(before);
+    let body = after.trim_start();
+    let body = body.strip_prefix("<body>").unwrap_or(body);
+    let body = body.trim_end();
+    let body = body.strip_suffix("</html>").unwrap_or(body).trim_end();
+    (head, body.strip_suffix("</body>").unwrap_or(body))
+}
@@ -0,0 +1,283 @@
+use egui::{ComboBox, DragValue, Ui};
+use headless_chrome::types::PrintToPdfOptions;
+use serde::{Deserialize, Serialize};
+
+const MM_PER_INCH: f64 = 25.4;
+
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub enum PaperSize {
+    A4,
+    A5,
+    Letter,
+    Legal,
+    PokerCard,
+    TarotCard,
+    Custom(f64, f64) // width, height in mm
+}
+
+impl PaperSize {
+    pub fn to_string(&self) -> &str {
+        match self {
+            PaperSize::A4 => "A4",
+            PaperSize::A5 => "A5",
+            PaperSize::Letter => "US Letter",
+            PaperSize::Legal => "US Legal",
+            PaperSize::PokerCard => "Poker Card",
+            PaperSize::TarotCard => "Tarot Card",
+            PaperSize::Custom(_, _) => "Custom"
+        }
+    }
+
+    /// width and height in mm, portrait
+    pub fn dimensions(&self) -> (f64, f64) {
+        match self {
+            PaperSize::A4 => (210.0, 297.0),
+            PaperSize::A5 => (148.0, 210.0),
+            PaperSize::Letter => (215.9, 279.4),
+            PaperSize::Legal => (215.9, 355.6),
+            PaperSize::PokerCard => (63.5, 88.9),
+            PaperSize::TarotCard => (70.0, 120.0),
+            PaperSize::Custom(width, height) => (*width, *height)
+        }
+    }
+
+    pub fn combo_box(&mut self, ui: &mut Ui, id: usize) {
+        ComboBox::from_id_salt(id)
+            .selected_text(self.to_string())
+            .show_ui(ui, |ui| {
+                ui.selectable_value(self, PaperSize::A4, "A4");
+                ui.selectable_value(self, PaperSize::A5, "A5");
+                ui.selectable_value(self, PaperSize::Letter, "US Letter");
+                ui.selectable_value(self, PaperSize::Legal, "US Legal");
+                ui.selectable_value(self, PaperSize::PokerCard, "Poker Card");
+                ui.selectable_value(self, PaperSize::TarotCard, "Tarot Card");
+                let (width, height) = self.dimensions();
+                ui.selectable_value(self, PaperSize::Custom(width, height), "Custom");
+            });
+    }
+}
+
+
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct PrintProfile {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default = "default_paper")]
+    pub paper: PaperSize,
+    #[serde(default)]
+    pub landscape: bool,
+    #[serde(default)]
+    pub margins: (f64, f64, f64, f64), // top, bottom, left, right in mm
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+    #[serde(default)]
+    pub bleed: f64, // mm added around each datasheet
+    #[serde(default)]
+    pub crop_marks: bool,
+    #[serde(default = "default_layout")]
+    pub layout: (u32, u32), // columns, rows of datasheets per sheet
+    #[serde(default)]
+    pub duplex_backs: bool,
+}
+
+fn default_paper() -> PaperSize {PaperSize::A4}
+fn default_scale() -> f64 {1.0}
+fn default_layout() -> (u32, u32) {(1, 1)}
+
+impl Default for PrintProfile {
+    fn default() -> Self {
+        Self {
+            name: "A4".to_string(),
+            paper: PaperSize::A4,
+            landscape: true,
+            margins: (10.0, 10.0, 10.0, 10.0),
+            scale: 1.0,
+            bleed: 0.0,
+            crop_marks: false,
+            layout: (1, 1),
+            duplex_backs: false
+        }
+    }
+}
+
+pub fn default_print_profiles() -> Vec<PrintProfile> {
+    vec![
+        PrintProfile::default(),
+        PrintProfile {
+            name: "US Letter".to_string(),
+            paper: PaperSize::Letter,
+            margins: (12.7, 12.7, 12.7, 12.7),
+            ..Default::default()
+        },
+        PrintProfile {
+            name: "Poker Cards (3x3 on A4)".to_string(),
+            paper: PaperSize::A4,
+            landscape: false,
+            margins: (5.0, 5.0, 5.0, 5.0),
+            scale: 0.27,
+            bleed: 3.0,
+            crop_marks: true,
+            layout: (3, 3),
+            duplex_backs: true
+        },
+        PrintProfile {
+            name: "Tarot Cards (3x2 on A4)".to_string(),
+            paper: PaperSize::A4,
+            landscape: true,
+            margins: (5.0, 5.0, 5.0, 5.0),
+            scale: 0.35,
+            bleed: 3.0,
+            crop_marks: true,
+            layout: (3, 2),
+            duplex_backs: true
+        },
+    ]
+}
+
+impl PrintProfile {
+
+    pub fn per_sheet(&self) -> usize {
+        (self.layout.0.max(1) * self.layout.1.max(1)) as usize
+    }
+
+    /// page size in mm, with orientation applied
+    pub fn page_size(&self) -> (f64, f64) {
+        let (width, height) = self.paper.dimensions();
+        if self.landscape {(height, width)} else {(width, height)}
+    }
+
+    pub fn pdf_options(&self) -> PrintToPdfOptions {
+        let (width, height) = self.page_size();
+        PrintToPdfOptions {
+            print_background: Some(true),
+            // orientation is already applied to the page size
+            landscape: Some(false),
+            scale: Some(self.scale.clamp(0.1, 2.0)),
+            paper_width: Some(width / MM_PER_INCH),
+            paper_height: Some(height / MM_PER_INCH),
+            margin_top: Some(self.margins.0 / MM_PER_INCH),
+            margin_bottom: Some(self.margins.1 / MM_PER_INCH),
+            margin_left: Some(self.margins.2 / MM_PER_INCH),
+            margin_right: Some(self.margins.3 / MM_PER_INCH),
+            ..Default::default()
+        }
+    }
+
+    /// Lays rendered datasheets out onto sheets, adding bleed, crop marks and back sides where needed.
+    /// `head` goes into the single document head, so the cards only hold body content
+    pub fn impose(&self, head: &str, fronts: Vec<String>, backs: Vec<String>) -> String {
+        let columns = self.layout.0.max(1) as usize;
+        let per_sheet = self.per_sheet();
+
+        let mut html = format!(
+            "<!DOCTYPE html><html><head><meta charset=\"utf-8\">{}<style>{}</style></head><body>",
+            head,
+            self.imposition_style(columns)
+        );
+
+        for (sheet, front_chunk) in fronts.chunks(per_sheet).enumerate() {
+            html.push_str("<div class=\"Sheet\">");
+            for front in front_chunk {
+                html.push_str(&self.cell(front));
+            }
+            html.push_str("</div>");
+
+            if self.duplex_backs {
+                // backs are mirrored along the long edge so they line up with the fronts
+                let back_chunk = &backs[(sheet * per_sheet).min(backs.len())..((sheet + 1) * per_sheet).min(backs.len())];
+                html.push_str("<div class=\"Sheet\">");
+                for row in back_chunk.chunks(columns) {
+                    for _ in row.len()..columns {
+                        html.push_str("<div class=\"Cell\"></div>");
+                    }
+                    for back in row.iter().rev() {
+                        html.push_str(&self.cell(back));
+                    }
+                }
+                html.push_str("</div>");
+            }
+        }
+
+        html.push_str("</body></html>");
+        html
+    }
+
+    fn cell(&self, content: &str) -> String {
+        let marks = if self.crop_marks {
+            "<div class=\"Crop TL\"></div><div class=\"Crop TR\"></div><div class=\"Crop BL\"></div><div class=\"Crop BR\"></div>"
+        } else {""};
+        format!("<div class=\"Cell\">{}<div class=\"Card\">{}</div></div>", marks, content)
+    }
+
+    fn imposition_style(&self, columns: usize) -> String {
+        let bleed = self.bleed.max(0.0);
+        let mark = if self.crop_marks {5.0} else {0.0};
+        format!(
+            ".Sheet {{ display: grid; grid-template-columns: repeat({columns}, max-content); gap: {gap}mm; page-break-after: always; }}
+            .Sheet:last-child {{ page-break-after: auto; }}
+            .Cell {{ position: relative; padding: {pad}mm; }}
+            .Card {{ padding: {bleed}mm; }}
+            .Crop {{ position: absolute; width: {mark}mm; height: {mark}mm; border-color: black; border-style: solid; border-width: 0; }}
+            .TL {{ top: 0; left: 0; border-right-width: 0.2mm; border-bottom-width: 0.2mm; }}
+            .TR {{ top: 0; right: 0; border-left-width: 0.2mm; border-bottom-width: 0.2mm; }}
+            .BL {{ bottom: 0; left: 0; border-right-width: 0.2mm; border-top-width: 0.2mm; }}
+            .BR {{ bottom: 0; right: 0; border-left-width: 0.2mm; border-top-width: 0.2mm; }}",
+            columns = columns,
+            gap = if self.crop_marks {0.0} else {2.0},
+            pad = mark,
+            bleed = bleed,
+            mark = mark
+        )
+    }
+
+    pub fn edit(&mut self, ui: &mut Ui, id: usize) {
+        ui.horizontal(|ui| {
+            ui.label("Name:");
+            ui.text_edit_singleline(&mut self.name);
+        });
+        ui.horizontal(|ui| {
+            ui.label("Paper:");
+            self.paper.combo_box(ui, id);
+            if let PaperSize::Custom(width, height) = &mut self.paper {
+                ui.add(DragValue::new(width).range(10.0..=1000.0).suffix("mm"));
+                ui.label("x");
+                ui.add(DragValue::new(height).range(10.0..=1000.0).suffix("mm"));
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Landscape:");
+            ui.checkbox(&mut self.landscape, "");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Margins (T/B/L/R):");
+            ui.add(DragValue::new(&mut self.margins.0).range(0.0..=100.0).suffix("mm"));
+            ui.add(DragValue::new(&mut self.margins.1).range(0.0..=100.0).suffix("mm"));
+            ui.add(DragValue::new(&mut self.margins.2).range(0.0..=100.0).suffix("mm"));
+            ui.add(DragValue::new(&mut self.margins.3).range(0.0..=100.0).suffix("mm"));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Scale:");
+            ui.add(DragValue::new(&mut self.scale).range(0.1..=2.0).speed(0.01));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Bleed:");
+            ui.add(DragValue::new(&mut self.bleed).range(0.0..=20.0).suffix("mm"));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Crop Marks:");
+            ui.checkbox(&mut self.crop_marks, "");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Datasheets per Sheet:");
+            ui.add(DragValue::new(&mut self.layout.0).range(1..=10));
+            ui.label("x");
+            ui.add(DragValue::new(&mut self.layout.1).range(1..=10));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Duplex Back Sides:");
+            ui.checkbox(&mut self.duplex_backs, "");
+        });
+    }
+}
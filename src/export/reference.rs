@@ -0,0 +1,38 @@
+use crate::data::GlossaryEntry;
+
+
+
+/// A page listing the rules text of every glossary entry a set of datasheets uses
+pub fn reference_html(entries: &Vec<&GlossaryEntry>) -> String {
+    let mut html = "<div style=\"page-break-before: always; font-family: Roboto, sans-serif; width: 800px;\"><h1 style=\"font-family: ConduitITCStd, sans-serif;\">Rules Reference</h1>".to_string();
+    for entry in entries {
+        html.push_str(&format!(
+            "<p><b>{}</b> <i>({})</i><br>{}</p>",
+            escape(&entry.name.to_uppercase()), entry.kind.to_string(), escape(&entry.description).replace("\n", "<br>")
+        ));
+    }
+    html.push_str("</div>");
+    html
+}
+
+pub fn reference_markdown(entries: &Vec<&GlossaryEntry>) -> String {
+    if entries.is_empty() {return "".to_string();}
+    let mut result = "\n### Rules Reference\n\n".to_string();
+    for entry in entries {
+        result.push_str(&format!("- **{}:** {}\n", entry.name, entry.description.replace("\n", " ")));
+    }
+    result
+}
+
+pub fn reference_text(entries: &Vec<&GlossaryEntry>) -> String {
+    if entries.is_empty() {return "".to_string();}
+    let mut result = "\nRULES REFERENCE\n".to_string();
+    for entry in entries {
+        result.push_str(&format!("  {}: {}\n", entry.name.to_uppercase(), entry.description.replace("\n", " ")));
+    }
+    result
+}
+
+fn escape(content: &str) -> String {
+    content.replace("&", "&amp;").replace("<", "&lt;").replace(">", "&gt;")
+}
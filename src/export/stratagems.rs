@@ -0,0 +1,36 @@
+use std::{fs::write, path::PathBuf};
+
+use anyhow::Result;
+use headless_chrome::{Browser, LaunchOptions};
+
+use crate::data::Stratagem;
+
+use super::{embedded_fonts, print_profile::PrintProfile, ExportTemplates};
+
+
+
+/// Writes a card for every stratagem as html, or lays them out with the print profile and prints them to pdf if the path ends in .pdf
+pub fn export_stratagem_cards(stratagems: &[&Stratagem], templates: &ExportTemplates, profile: &PrintProfile, path: PathBuf) -> Result<()> {
+    let mut cards = Vec::new();
+    for stratagem in stratagems {
+        cards.push(templates.html.render("stratagem", &stratagem.get_context())?);
+    }
+
+    if path.extension().is_some_and(|extension| extension == "pdf") {
+        // stratagem cards have no backs, so the profile's duplex setting would only add blank sheets
+        let profile = PrintProfile {duplex_backs: false, ..profile.clone()};
+        let rendered = profile.impose(&embedded_fonts(), cards, Vec::new());
+        let browser = Browser::new(LaunchOptions::default())?;
+        let tab = browser.new_tab()?;
+        tab.navigate_to(format!("data:text/html;charset=utf-8,{}", rendered).as_str())?;
+        write(path, tab.print_to_pdf(Some(profile.pdf_options()))?)?;
+    } else {
+        let mut html = format!("<!DOCTYPE html><html><head><meta charset=\"utf-8\">{}</head><body><div style=\"display: flex; flex-wrap: wrap; gap: 10px;\">", embedded_fonts());
+        for card in cards {
+            html.push_str(&card);
+        }
+        html.push_str("</div></body></html>");
+        write(path, html)?;
+    }
+    Ok(())
+}
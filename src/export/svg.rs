@@ -0,0 +1,244 @@
+use std::{fs::write, path::PathBuf};
+
+use egui::Color32;
+
+use crate::{app::DatasheetAppSettings, data::{CrusadeUpgrade, ScarEffect, Unit, Weapon}};
+
+use super::embedded_fonts;
+
+const WIDTH: f64 = 850.0;
+const MAIN_WIDTH: f64 = 500.0;
+const LINE_HEIGHT: f64 = 18.0;
+// characters on a sidebar line
+const PARAGRAPH_WIDTH: usize = 48;
+// weapon table columns, matches the 7fr 2fr 1fr 1fr 1fr 1fr 1fr grid in the html template
+const WEAPON_COLUMNS: [f64; 7] = [0.0, 250.0, 321.4, 357.1, 392.9, 428.6, 464.3];
+
+
+
+pub fn export_to_svg(unit: &Unit, settings: &DatasheetAppSettings, path: PathBuf) -> Result<(), std::io::Error> {
+    let bar_colour = colour_to_hex(settings.bar_colour);
+    let keyword_colour = colour_to_hex(settings.keyword_colour);
+    let mut body = String::new();
+
+    // name and stats
+    body.push_str(&text(0.0, 40.0, 40.0, "ConduitITCStd", "black", true, &unit.name));
+
+    let mut x = 0.0;
+    for (name, original, value) in unit.stat_values() {
+        let changed = original != value;
+        let box_width = if changed {100.0} else if value.len() > 3 {75.0} else {50.0};
+        body.push_str(&text(x + box_width / 2.0 - 10.0, 70.0, 20.0, "ConduitITCStd", "black", true, &name.to_uppercase()));
+        body.push_str(&format!("<rect x=\"{}\" y=\"76\" width=\"{}\" height=\"50\" rx=\"5\" fill=\"#404040\" stroke=\"gray\"/>", x, box_width));
+        body.push_str(&struck_text(x + 8.0, 110.0, 26.0, "Roboto", "white", &original, &value));
+        x += box_width + 7.0;
+    }
+
+    // weapons
+    let ranged = if unit.crusade_unit {&unit.crusade_weapons.0} else {&unit.ranged_weapons};
+    let melee = if unit.crusade_unit {&unit.crusade_weapons.1} else {&unit.melee_weapons};
+    let mut main_y = 140.0;
+    main_y = weapon_table(&mut body, main_y, ("RANGED WEAPONS", "BS"), unit, ranged, &bar_colour, &keyword_colour);
+    main_y = weapon_table(&mut body, main_y, ("MELEE WEAPONS", "WS"), unit, melee, &bar_colour, &keyword_colour);
+
+    // abilities
+    let mut side_y = 140.0;
+    if let Some(invuln) = unit.stats.invuln {
+        body.push_str(&header(MAIN_WIDTH, side_y, WIDTH - MAIN_WIDTH, "#90ee90", &format!("INVULNERABLE {}+ SAVE", invuln)));
+        side_y += 24.0;
+    }
+    body.push_str(&header(MAIN_WIDTH, side_y, WIDTH - MAIN_WIDTH, &bar_colour, "ABILITIES"));
+    side_y += 24.0;
+
+    let core: Vec<String> = unit.core_abilities.iter().map(|ability| ability.to_render_string()).filter(|ability| !ability.is_empty()).collect();
+    if !core.is_empty() {
+        side_y = paragraph(&mut body, side_y, &format!("CORE: {}", core.join(", ")), None);
+    }
+    if let Some(ability) = &unit.faction_ability {
+        side_y = paragraph(&mut body, side_y, &format!("FACTION: {}", ability), None);
+    }
+    for ability in unit.unique_abilities.iter() {
+        side_y = paragraph(&mut body, side_y, &ability.description, Some(&ability.name.to_uppercase()));
+    }
+    if let Some(assigned) = &unit.enhancement {
+        side_y = paragraph(&mut body, side_y, &assigned.enhancement.description, Some(&format!("ENHANCEMENT - {}", assigned.enhancement.name.to_uppercase())));
+    }
+    if unit.crusade_unit {
+        for upgrade in unit.crusade_data.upgrades.iter() {
+            match upgrade {
+                CrusadeUpgrade::BattleTrait(ability) | CrusadeUpgrade::Relic(ability, _) | CrusadeUpgrade::Enhancement(ability, _) => {
+                    side_y = paragraph(&mut body, side_y, &ability.description, Some(&ability.name.to_uppercase()));
+                },
+                CrusadeUpgrade::BattleScar(ability, effects) => {
+                    side_y = paragraph(&mut body, side_y, &format!("{}{}", ability.description, ScarEffect::list(effects)), Some(&ability.name.to_uppercase()));
+                },
+                _ => {}
+            }
+        }
+    }
+    if let Some(damaged) = unit.damaged {
+        body.push_str(&header(MAIN_WIDTH, side_y, WIDTH - MAIN_WIDTH, &bar_colour, &format!("DAMAGED: 1-{} WOUNDS REMAINING", damaged)));
+        side_y += 24.0;
+        side_y = paragraph(&mut body, side_y, &format!("While this model has 1-{} wounds remaining, each time this model makes an attack, subtract 1 from the Hit roll.", damaged), None);
+    }
+    if let Some(leader) = &unit.leader {
+        body.push_str(&header(MAIN_WIDTH, side_y, WIDTH - MAIN_WIDTH, &bar_colour, "LEADER"));
+        side_y += 24.0;
+        side_y = paragraph(&mut body, side_y, &format!("This model can be attached to the following units: {}", leader.join(", ")), None);
+    }
+
+    // keyword bar
+    let footer_y = main_y.max(side_y) + 10.0;
+    body.push_str(&format!("<line x1=\"{0}\" y1=\"140\" x2=\"{0}\" y2=\"{1}\" stroke=\"black\"/>", MAIN_WIDTH, footer_y));
+    body.push_str(&format!("<rect x=\"0\" y=\"{}\" width=\"{}\" height=\"30\" fill=\"#404040\"/>", footer_y, WIDTH));
+    let keywords: Vec<String> = unit.keywords.iter().map(|keyword| keyword.to_uppercase()).collect();
+    body.push_str(&text(4.0, footer_y + 20.0, 14.0, "Roboto", &keyword_colour, false, &format!("KEYWORDS: {}", keywords.join(", "))));
+    body.push_str(&text(MAIN_WIDTH + 4.0, footer_y + 20.0, 14.0, "Roboto", &keyword_colour, false, &format!("FACTION KEYWORDS: {}", unit.faction_keyword.to_uppercase())));
+
+    let fonts = if settings.svg_embed_fonts {
+        embedded_fonts()
+    } else {
+        "<style>@font-face { font-family: ConduitITCStd; src: local(\"Conduit ITC Std\"); } @font-face { font-family: Roboto; src: local(\"Roboto Condensed Bold\"); }</style>".to_string()
+    };
+
+    let height = footer_y + 30.0;
+    let svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\"><defs>{2}</defs><rect width=\"100%\" height=\"100%\" fill=\"white\"/>{3}</svg>",
+        WIDTH, height, fonts, body
+    );
+
+    write(path, svg)
+}
+
+
+fn weapon_table(body: &mut String, mut y: f64, (title, skill): (&str, &str), unit: &Unit, weapons: &[(Weapon, u32)], bar_colour: &str, keyword_colour: &str) -> f64 {
+    if weapons.is_empty() {return y;}
+
+    body.push_str(&format!("<rect x=\"0\" y=\"{}\" width=\"{}\" height=\"20\" fill=\"{}\"/>", y, MAIN_WIDTH, bar_colour));
+    for (column, name) in WEAPON_COLUMNS.iter().zip([title, "RANGE", "A", skill, "S", "AP", "D"]) {
+        body.push_str(&text(column + 2.0, y + 15.0, 15.0, "ConduitITCStd", "black", true, name));
+    }
+    y += 20.0;
+
+    for (weapon, count) in weapons.iter() {
+        let data = weapon.get_render_data();
+        body.push_str(&format!("<line x1=\"0\" y1=\"{0}\" x2=\"{1}\" y2=\"{0}\" stroke=\"black\"/>", y, MAIN_WIDTH));
+        body.push_str(&text(WEAPON_COLUMNS[0] + 2.0, y + 15.0, 14.0, "Roboto", "black", false, &weapon.get_title(*count)));
+        for (column, (original, value)) in WEAPON_COLUMNS[1..].iter().zip(weapon.column_values(unit.original_weapon(weapon)).iter()) {
+            body.push_str(&struck_text(column + 2.0, y + 15.0, 14.0, "Roboto", "black", original, value));
+        }
+        y += 20.0;
+        if data.7 != "[]" {
+            // the keyword colour is a highlight, light colours would be unreadable as text on white
+            body.push_str(&format!("<rect x=\"0\" y=\"{}\" width=\"{}\" height=\"14\" fill=\"{}\"/>", y, MAIN_WIDTH, keyword_colour));
+            body.push_str(&text(2.0, y + 11.0, 11.0, "Roboto", "black", false, &data.7));
+            y += 14.0;
+        }
+    }
+    y + 4.0
+}
+
+fn header(x: f64, y: f64, width: f64, colour: &str, title: &str) -> String {
+    format!(
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"20\" fill=\"{}\"/>{}",
+        x, y, width, colour, text(x + 2.0, y + 15.0, 15.0, "ConduitITCStd", "black", true, title)
+    )
+}
+
+/// wraps text into the sidebar, with an optional bold lead-in
+fn paragraph(body: &mut String, mut y: f64, content: &str, name: Option<&str>) -> f64 {
+    // the name is wrapped on its own, its last line is shared with the start of the content
+    let mut lead = name.map(|name| wrap(&format!("{}:", name), PARAGRAPH_WIDTH, 0)).unwrap_or_default();
+    let shared = lead.pop();
+    for line in lead.iter() {
+        y += LINE_HEIGHT;
+        body.push_str(&text(MAIN_WIDTH + 4.0, y, 14.0, "Roboto", "black", true, line));
+    }
+
+    let indent = shared.as_ref().map(|shared| shared.len() + 1).unwrap_or(0);
+    let mut lines = wrap(content, PARAGRAPH_WIDTH, indent);
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+    for (i, line) in lines.iter().enumerate() {
+        y += LINE_HEIGHT;
+        match &shared {
+            Some(shared) if i == 0 => body.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" font-size=\"14\" font-family=\"Roboto\"><tspan font-weight=\"bold\">{}</tspan> {}</text>",
+                MAIN_WIDTH + 4.0, y, escape(shared), escape(line)
+            )),
+            _ => body.push_str(&text(MAIN_WIDTH + 4.0, y, 14.0, "Roboto", "black", false, line)),
+        }
+    }
+    y + 6.0
+}
+
+/// Splits content into lines of at most `width` characters, the first line starting `indent` characters in
+fn wrap(content: &str, width: usize, indent: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in content.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let taken = if lines.is_empty() {indent} else {0};
+            if (!line.is_empty() || taken > 0) && taken + line.len() + word.len() + 1 > width {
+                lines.push(line);
+                line = String::new();
+            }
+            if !line.is_empty() {line.push(' ');}
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+fn text(x: f64, y: f64, size: f64, font: &str, colour: &str, bold: bool, content: &str) -> String {
+    format!(
+        "<text x=\"{}\" y=\"{}\" font-size=\"{}\" font-family=\"{}\" fill=\"{}\"{}>{}</text>",
+        x, y, size, font, colour, if bold {" font-weight=\"bold\""} else {""}, escape(content)
+    )
+}
+
+/// A value with the original struck through before it when a weapon mod or battle scar changed it
+fn struck_text(x: f64, y: f64, size: f64, font: &str, colour: &str, original: &str, shown: &str) -> String {
+    if original == shown {
+        return text(x, y, size, font, colour, false, shown);
+    }
+    format!(
+        "<text x=\"{}\" y=\"{}\" font-size=\"{}\" font-family=\"{}\" fill=\"{}\"><tspan text-decoration=\"line-through\" font-size=\"{}\">{}</tspan> {}</text>",
+        x, y, size, font, colour, size * 0.6, escape(original), escape(shown)
+    )
+}
+
+fn escape(content: &str) -> String {
+    content.replace("&", "&amp;").replace("<", "&lt;").replace(">", "&gt;").replace("\"", "&quot;")
+}
+
+fn colour_to_hex(colour: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", colour.r(), colour.g(), colour.b())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_starts_after_indent() {
+        assert_eq!(wrap("one two three", 10, 4), vec!["one", "two three"]);
+        assert_eq!(wrap("one two", 10, 8), vec!["", "one two"]);
+        assert_eq!(wrap("first\nsecond", 48, 0), vec!["first", "second"]);
+    }
+
+    #[test]
+    fn paragraph_keeps_every_line_of_a_long_name() {
+        let name = "AN ABILITY WITH A NAME FAR TOO LONG TO FIT ON ONE LINE OF THE SIDEBAR";
+        let mut body = String::new();
+        paragraph(&mut body, 0.0, "Some rules text.", Some(name));
+        let shown = body.replace("<tspan font-weight=\"bold\">", "").replace("</tspan>", "");
+        for word in name.split_whitespace().chain(["Some", "rules", "text."]) {
+            assert!(shown.contains(word), "{} is missing", word);
+        }
+        assert_eq!(shown.matches("Some rules text.").count(), 1);
+    }
+}
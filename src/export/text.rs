@@ -0,0 +1,236 @@
+use std::{fs::write, path::PathBuf};
+
+use crate::data::{CrusadeUpgrade, ScarEffect, Unit, Weapon};
+
+use super::struck;
+
+
+
+/// `reference` is appended after the datasheet, and is empty when no rules reference is wanted
+pub fn export_to_markdown(unit: &Unit, reference: String, path: PathBuf) -> Result<(), std::io::Error> {
+    write(path, format!("{}{}", unit_to_markdown(unit), reference))
+}
+
+pub fn export_to_text(unit: &Unit, reference: String, path: PathBuf) -> Result<(), std::io::Error> {
+    write(path, format!("{}{}", unit_to_text(unit), reference))
+}
+
+
+pub fn unit_to_markdown(unit: &Unit) -> String {
+    let mut result = format!("## {}\n\n", unit.name);
+
+    // stats
+    let (names, values) = stat_line(unit, true);
+    let values: Vec<String> = values.iter().map(|value| table_cell(value)).collect();
+    result.push_str(&format!("| {} |\n", names.join(" | ")));
+    result.push_str(&format!("|{}\n", " :-: |".repeat(names.len())));
+    result.push_str(&format!("| {} |\n\n", values.join(" | ")));
+
+    // weapons
+    let (ranged, melee) = weapons(unit);
+    for (title, skill, weapons) in [("Ranged Weapons", "BS", ranged), ("Melee Weapons", "WS", melee)] {
+        if weapons.is_empty() {continue;}
+        result.push_str(&format!("| {} | Range | A | {} | S | AP | D | Keywords |\n", title, skill));
+        result.push_str("| :-- | :-: | :-: | :-: | :-: | :-: | :-: | :-- |\n");
+        for (weapon, count) in weapons.iter() {
+            let data = weapon.get_render_data();
+            let keywords = if data.7 == "[]" {"".to_string()} else {data.7};
+            let columns: Vec<String> = weapon_columns(unit, weapon, true).iter().map(|column| table_cell(column)).collect();
+            result.push_str(&format!("| {} | {} | {} |\n", table_cell(&weapon.get_title(*count)), columns.join(" | "), table_cell(&keywords)));
+        }
+        result.push('\n');
+    }
+
+    // abilities
+    result.push_str("### Abilities\n\n");
+    let core = core_abilities(unit);
+    if !core.is_empty() {
+        result.push_str(&format!("- **Core:** {}\n", core));
+    }
+    if let Some(ability) = &unit.faction_ability {
+        result.push_str(&format!("- **Faction:** {}\n", ability));
+    }
+    for ability in unit.unique_abilities.iter() {
+        result.push_str(&format!("- **{}:** {}\n", ability.name, ability.description.replace("\n", " ")));
+    }
+    if let Some(assigned) = &unit.enhancement {
+        result.push_str(&format!("- **Enhancement - {} ({} pts):** {}\n", assigned.enhancement.name, assigned.enhancement.points, assigned.enhancement.description.replace("\n", " ")));
+    }
+    if let Some(invuln) = unit.stats.invuln {
+        result.push_str(&format!("- **Invulnerable Save:** {}+\n", invuln));
+    }
+    if let Some(damaged) = unit.damaged {
+        result.push_str(&format!("- **Damaged (1-{} wounds remaining):** Each time this model makes an attack, subtract 1 from the Hit roll.\n", damaged));
+    }
+    if let Some(leader) = &unit.leader {
+        result.push_str(&format!("- **Leader:** {}\n", leader.join(", ")));
+    }
+    result.push('\n');
+
+    // keywords
+    result.push_str(&format!("**Keywords:** {}  \n", keywords(unit)));
+    result.push_str(&format!("**Faction Keywords:** {}\n", unit.faction_keyword.to_uppercase()));
+
+    if unit.crusade_unit {
+        result.push_str("\n### Crusade\n\n");
+        result.push_str(&format!("- **Rank:** {}\n", unit.crusade_data.rank.to_string()));
+        result.push_str(&format!("- **Experience:** {}\n", unit.crusade_data.exp));
+        result.push_str(&format!("- **Kills:** {}\n", unit.crusade_data.kills));
+        result.push_str(&format!("- **Crusade Points:** {}\n", unit.crusade_data.crusade_points()));
+        for upgrade in unit.crusade_data.upgrades.iter() {
+            result.push_str(&format!("- {}\n", upgrade_line(upgrade, true)));
+        }
+    }
+
+    result
+}
+
+
+pub fn unit_to_text(unit: &Unit) -> String {
+    let mut result = format!("{}\n{}\n", unit.name.to_uppercase(), "=".repeat(unit.name.len()));
+
+    let (names, values) = stat_line(unit, false);
+    let stats: Vec<String> = names.iter().zip(values.iter()).map(|(name, value)| format!("{} {}", name, value)).collect();
+    result.push_str(&format!("{}\n\n", stats.join(" | ")));
+
+    let (ranged, melee) = weapons(unit);
+    for (title, skill, weapons) in [("RANGED WEAPONS", "BS", ranged), ("MELEE WEAPONS", "WS", melee)] {
+        if weapons.is_empty() {continue;}
+        result.push_str(&format!("{}\n", title));
+        for (weapon, count) in weapons.iter() {
+            let data = weapon.get_render_data();
+            let columns = weapon_columns(unit, weapon, false);
+            result.push_str(&format!("  {}: {} A:{} {}:{} S:{} AP:{} D:{}", weapon.get_title(*count), columns[0], columns[1], skill, columns[2], columns[3], columns[4], columns[5]));
+            if data.7 != "[]" {
+                result.push_str(&format!(" {}", data.7));
+            }
+            result.push('\n');
+        }
+        result.push('\n');
+    }
+
+    result.push_str("ABILITIES\n");
+    let core = core_abilities(unit);
+    if !core.is_empty() {
+        result.push_str(&format!("  CORE: {}\n", core));
+    }
+    if let Some(ability) = &unit.faction_ability {
+        result.push_str(&format!("  FACTION: {}\n", ability));
+    }
+    for ability in unit.unique_abilities.iter() {
+        result.push_str(&format!("  {}: {}\n", ability.name.to_uppercase(), ability.description.replace("\n", " ")));
+    }
+    if let Some(assigned) = &unit.enhancement {
+        result.push_str(&format!("  ENHANCEMENT - {} ({} PTS): {}\n", assigned.enhancement.name.to_uppercase(), assigned.enhancement.points, assigned.enhancement.description.replace("\n", " ")));
+    }
+    if let Some(invuln) = unit.stats.invuln {
+        result.push_str(&format!("  INVULNERABLE SAVE: {}+\n", invuln));
+    }
+    if let Some(damaged) = unit.damaged {
+        result.push_str(&format!("  DAMAGED (1-{} WOUNDS REMAINING): Each time this model makes an attack, subtract 1 from the Hit roll.\n", damaged));
+    }
+    if let Some(leader) = &unit.leader {
+        result.push_str(&format!("  LEADER: {}\n", leader.join(", ")));
+    }
+    result.push('\n');
+
+    result.push_str(&format!("KEYWORDS: {}\n", keywords(unit)));
+    result.push_str(&format!("FACTION KEYWORDS: {}\n", unit.faction_keyword.to_uppercase()));
+
+    if unit.crusade_unit {
+        result.push_str(&format!("\nCRUSADE: {} ({} exp, {} kills, {} crusade points)\n", unit.crusade_data.rank.to_string(), unit.crusade_data.exp, unit.crusade_data.kills, unit.crusade_data.crusade_points()));
+        for upgrade in unit.crusade_data.upgrades.iter() {
+            result.push_str(&format!("  {}\n", upgrade_line(upgrade, false)));
+        }
+    }
+
+    result
+}
+
+
+/// Escapes the pipes that would otherwise end a markdown table cell
+fn table_cell(content: &str) -> String {
+    content.replace("|", "\\|")
+}
+
+/// Values changed by battle scars are struck through in markdown, and followed by the original in plain text
+fn changed(original: &str, shown: &str, markdown: bool) -> String {
+    if markdown {
+        struck(original, shown, "~~", "~~")
+    } else if original == shown {
+        shown.to_string()
+    } else {
+        format!("{} (was {})", shown, original)
+    }
+}
+
+fn stat_line(unit: &Unit, markdown: bool) -> (Vec<&str>, Vec<String>) {
+    unit.stat_values().into_iter().map(|(name, original, shown)| (name, changed(&original, &shown, markdown))).unzip()
+}
+
+fn weapon_columns(unit: &Unit, weapon: &Weapon, markdown: bool) -> Vec<String> {
+    weapon.column_values(unit.original_weapon(weapon)).iter().map(|(original, shown)| changed(original, shown, markdown)).collect()
+}
+
+/// A list of weapons and how many of each the unit has
+type WeaponCounts<'a> = &'a [(Weapon, u32)];
+
+fn weapons(unit: &Unit) -> (WeaponCounts<'_>, WeaponCounts<'_>) {
+    if unit.crusade_unit {
+        (&unit.crusade_weapons.0, &unit.crusade_weapons.1)
+    } else {
+        (&unit.ranged_weapons, &unit.melee_weapons)
+    }
+}
+
+fn core_abilities(unit: &Unit) -> String {
+    let abilities: Vec<String> = unit.core_abilities.iter()
+        .map(|ability| ability.to_render_string())
+        .filter(|ability| !ability.is_empty())
+        .collect();
+    abilities.join(", ")
+}
+
+fn keywords(unit: &Unit) -> String {
+    let keywords: Vec<String> = unit.keywords.iter().map(|keyword| keyword.to_uppercase()).collect();
+    keywords.join(", ")
+}
+
+fn upgrade_line(upgrade: &CrusadeUpgrade, markdown: bool) -> String {
+    let (name, description) = match upgrade {
+        CrusadeUpgrade::WeaponMod(weapon_mod) => {
+            let target = weapon_mod.target.as_ref().map(|target| format!(" on {}", target.name)).unwrap_or("".to_string());
+            (weapon_mod.name.clone(), format!("{}, {}{}", weapon_mod.change_one.to_string(), weapon_mod.change_two.to_string(), target))
+        },
+        CrusadeUpgrade::Relic(ability, tier) => {
+            (format!("{} ({})", ability.name, tier.to_string()), ability.description.replace("\n", " "))
+        },
+        CrusadeUpgrade::BattleScar(ability, effects) => {
+            (ability.name.clone(), format!("{}{}", ability.description.replace("\n", " "), ScarEffect::list(effects)))
+        },
+        CrusadeUpgrade::BattleTrait(ability) | CrusadeUpgrade::Enhancement(ability, _) => {
+            (ability.name.clone(), ability.description.replace("\n", " "))
+        }
+    };
+    if markdown {
+        format!("**{} - {}:** {}", upgrade.to_string(), name, description)
+    } else {
+        format!("{} - {}: {}", upgrade.to_string().to_uppercase(), name, description)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_escapes_pipes_in_table_cells() {
+        let weapon: Weapon = ron::from_str("(name: \"Bolt Rifle | Mk II\", range: Ranged(24), skill: 3)").unwrap();
+        let unit = Unit {ranged_weapons: vec![(weapon, 1)], ..Default::default()};
+        let markdown = unit_to_markdown(&unit);
+        let row = markdown.lines().find(|line| line.contains("Bolt Rifle")).unwrap();
+        assert!(row.contains("Bolt Rifle \\| Mk II"));
+        assert_eq!(row.matches(" | ").count(), 7);
+    }
+}
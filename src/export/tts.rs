@@ -0,0 +1,128 @@
+use std::{fs::write, path::PathBuf};
+
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use crate::data::{CrusadeUpgrade, ScarEffect, Unit};
+
+use super::struck;
+
+// colours used by the common Tabletop Simulator datasheet scripts
+const STAT_COLOUR: &str = "56f442";
+const WEAPON_COLOUR: &str = "e85545";
+const ABILITY_COLOUR: &str = "dc61ed";
+const KEYWORD_COLOUR: &str = "c6c930";
+// spacing between models when a list of units is laid out on the table
+const SPACING: f64 = 3.0;
+
+
+
+/// The description block Tabletop Simulator shows for a model, using its [rrggbb]...[-] colour tags
+pub fn unit_to_tts(unit: &Unit) -> String {
+    let mut result = String::new();
+
+    let (names, values): (Vec<String>, Vec<String>) = unit.stat_values().into_iter()
+        .map(|(name, original, shown)| (name.to_uppercase(), struck(&original, &shown, "[s]", "[/s]")))
+        .unzip();
+    let names: Vec<String> = names.iter().zip(values.iter()).map(|(name, value)| format!("{:<width$}", name, width = value.len().max(2))).collect();
+    let values: Vec<String> = values.iter().map(|value| format!("{:<2}", value)).collect();
+    result.push_str(&format!("[{}]{}[-]\n{}\n", STAT_COLOUR, names.join(" "), values.join(" ")));
+
+    let ranged = if unit.crusade_unit {&unit.crusade_weapons.0} else {&unit.ranged_weapons};
+    let melee = if unit.crusade_unit {&unit.crusade_weapons.1} else {&unit.melee_weapons};
+    for (title, skill, weapons) in [("Ranged Weapons", "BS", ranged), ("Melee Weapons", "WS", melee)] {
+        if weapons.is_empty() {continue;}
+        result.push_str(&format!("\n[{}]{}[-]\n", WEAPON_COLOUR, title));
+        for (weapon, count) in weapons.iter() {
+            let data = weapon.get_render_data();
+            let columns: Vec<String> = weapon.column_values(unit.original_weapon(weapon)).iter().map(|(original, shown)| struck(original, shown, "[s]", "[/s]")).collect();
+            result.push_str(&format!("[{}]{}[-] {} A{} {}{} S{} AP{} D{}", KEYWORD_COLOUR, weapon.get_title(*count), columns[0], columns[1], skill, columns[2], columns[3], columns[4], columns[5]));
+            if data.7 != "[]" {
+                result.push_str(&format!(" [{}]{}[-]", KEYWORD_COLOUR, data.7.to_lowercase()));
+            }
+            result.push('\n');
+        }
+    }
+
+    result.push_str(&format!("\n[{}]Abilities[-]\n", ABILITY_COLOUR));
+    let core: Vec<String> = unit.core_abilities.iter().map(|ability| ability.to_render_string()).filter(|ability| !ability.is_empty()).collect();
+    if !core.is_empty() {
+        result.push_str(&format!("[{}]Core:[-] {}\n", ABILITY_COLOUR, core.join(", ")));
+    }
+    if let Some(ability) = &unit.faction_ability {
+        result.push_str(&format!("[{}]Faction:[-] {}\n", ABILITY_COLOUR, ability));
+    }
+    for ability in unit.unique_abilities.iter() {
+        result.push_str(&format!("[{}]{}:[-] {}\n", ABILITY_COLOUR, ability.name, ability.description.replace("\n", " ")));
+    }
+    if let Some(assigned) = &unit.enhancement {
+        result.push_str(&format!("[{}]Enhancement - {}:[-] {}\n", ABILITY_COLOUR, assigned.enhancement.name, assigned.enhancement.description.replace("\n", " ")));
+    }
+    if let Some(damaged) = unit.damaged {
+        result.push_str(&format!("[{}]Damaged 1-{}:[-] subtract 1 from the Hit roll\n", ABILITY_COLOUR, damaged));
+    }
+    if unit.crusade_unit {
+        for upgrade in unit.crusade_data.upgrades.iter() {
+            match upgrade {
+                CrusadeUpgrade::BattleTrait(ability) | CrusadeUpgrade::Relic(ability, _) | CrusadeUpgrade::Enhancement(ability, _) => {
+                    result.push_str(&format!("[{}]{}:[-] {}\n", ABILITY_COLOUR, ability.name, ability.description.replace("\n", " ")));
+                },
+                CrusadeUpgrade::BattleScar(ability, effects) => {
+                    result.push_str(&format!("[{}]{}:[-] {}{}\n", ABILITY_COLOUR, ability.name, ability.description.replace("\n", " "), ScarEffect::list(effects)));
+                },
+                _ => {}
+            }
+        }
+    }
+
+    let keywords: Vec<String> = unit.keywords.iter().map(|keyword| keyword.to_uppercase()).collect();
+    result.push_str(&format!("\n[{}]{}[-]\n[{}]{}[-]", KEYWORD_COLOUR, keywords.join(", "), KEYWORD_COLOUR, unit.faction_keyword.to_uppercase()));
+
+    result
+}
+
+
+fn unit_object(unit: &Unit, x: f64) -> Value {
+    let nickname = if unit.crusade_unit {
+        format!("{} [{}]", unit.name, unit.crusade_data.rank.to_string())
+    } else {
+        unit.name.clone()
+    };
+    json!({
+        "Name": "BlockSquare",
+        "Transform": {
+            "posX": x, "posY": 1.0, "posZ": 0.0,
+            "rotX": 0.0, "rotY": 180.0, "rotZ": 0.0,
+            "scaleX": 1.0, "scaleY": 1.0, "scaleZ": 1.0
+        },
+        "Nickname": nickname,
+        "Description": unit_to_tts(unit),
+        "ColorDiffuse": {"r": 0.5, "g": 0.5, "b": 0.5},
+        "Locked": false,
+        "Grid": true,
+        "Snap": true,
+        "Tooltip": true
+    })
+}
+
+/// A saved object file that can be dropped into Tabletop Simulator's saved objects folder
+pub fn export_to_tts(units: &[&Unit], name: &str, path: PathBuf) -> Result<()> {
+    let objects: Vec<Value> = units.iter().enumerate().map(|(i, unit)| unit_object(unit, i as f64 * SPACING)).collect();
+    let save = json!({
+        "SaveName": name,
+        "GameMode": "",
+        "Date": "",
+        "Table": "",
+        "Sky": "",
+        "Note": "",
+        "Rules": "",
+        "XmlUI": "",
+        "LuaScript": "",
+        "LuaScriptState": "",
+        "ObjectStates": objects,
+        "TabStates": {},
+        "VersionNumber": ""
+    });
+    write(path, serde_json::to_string_pretty(&save)?)?;
+    Ok(())
+}
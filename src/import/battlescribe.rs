@@ -0,0 +1,384 @@
+use std::{collections::HashMap, fs::{read_to_string, File}, io::Read, path::PathBuf};
+
+use anyhow::{anyhow, Result};
+use roxmltree::{Document, Node};
+
+use crate::data::{Ability, CoreAbility, Range, Unit, UnitStats};
+
+use super::{parse_number, parse_weapon_profile, sanitise_filename};
+
+// linked entries can reference each other, so stop following them past this depth
+const MAX_LINK_DEPTH: usize = 8;
+
+
+
+/// Reads the xml of a BattleScribe file, unzipping it first for .catz and .rosz files
+pub fn read_xml(path: &PathBuf) -> Result<String> {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("catz") | Some("rosz") => {
+            let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+            let mut file = archive.by_index(0)?;
+            let mut xml = String::new();
+            file.read_to_string(&mut xml)?;
+            Ok(xml)
+        },
+        _ => Ok(read_to_string(path)?)
+    }
+}
+
+
+/// The name of a catalogue, its (filename, unit) pairs and any warnings from converting them
+pub type Catalogue = (String, Vec<(String, Unit)>, Vec<String>);
+
+/// Converts the units of a catalogue
+pub fn read_catalogue(path: &PathBuf) -> Result<Catalogue> {
+    let xml = read_xml(path)?;
+    let document = Document::parse(&xml)?;
+    let root = document.root_element();
+    if root.tag_name().name() != "catalogue" {
+        return Err(anyhow!("Not a catalogue file"));
+    }
+    let name = root.attribute("name").unwrap_or("Catalogue").to_string();
+
+    let index: HashMap<&str, Node> = document.descendants()
+        .filter_map(|node| node.attribute("id").map(|id| (id, node)))
+        .collect();
+
+    let mut units: Vec<(String, Unit)> = Vec::new();
+    let mut warnings = Vec::new();
+    for list in children(root, &["sharedSelectionEntries", "selectionEntries"]) {
+        for entry in children(list, &["selectionEntry"]) {
+            if !matches!(entry.attribute("type"), Some("unit") | Some("model")) {continue;}
+            if let Some(unit) = entry_to_unit(entry, &index, &mut warnings) {
+                if !units.iter().any(|(_, existing)| existing.name == unit.name) {
+                    units.push((sanitise_filename(&unit.name), unit));
+                }
+            }
+        }
+    }
+
+    Ok((name, units, warnings))
+}
+
+
+/// Reads the unit selections of a roster, returning the roster name and the name of every selected unit
+pub fn read_roster(path: &PathBuf) -> Result<(String, Vec<String>)> {
+    let xml = read_xml(path)?;
+    let document = Document::parse(&xml)?;
+    let root = document.root_element();
+    if root.tag_name().name() != "roster" {
+        return Err(anyhow!("Not a roster file"));
+    }
+    let name = root.attribute("name").unwrap_or("Roster").to_string();
+
+    let mut selections = Vec::new();
+    for force in root.descendants().filter(|node| node.tag_name().name() == "force") {
+        for list in children(force, &["selections"]) {
+            for selection in children(list, &["selection"]) {
+                if matches!(selection.attribute("type"), Some("unit") | Some("model")) {
+                    let count = selection.attribute("number").and_then(|number| number.parse().ok()).unwrap_or(1);
+                    for _ in 0..count {
+                        selections.push(selection.attribute("name").unwrap_or("").to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    Ok((name, selections))
+}
+
+
+#[derive(Default)]
+struct EntryContents<'a, 'input> {
+    profiles: Vec<Node<'a, 'input>>,
+    rules: Vec<String>,
+    models: Vec<(u32, String)>,
+    keywords: Vec<String>,
+    points: Option<u32>,
+}
+
+fn children<'a, 'input>(node: Node<'a, 'input>, names: &'a [&str]) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children().filter(move |child| names.contains(&child.tag_name().name()))
+}
+
+/// gathers everything an entry contains, following links into shared entries and profiles
+fn collect<'a, 'input>(entry: Node<'a, 'input>, index: &HashMap<&str, Node<'a, 'input>>, depth: usize, contents: &mut EntryContents<'a, 'input>) {
+    if depth > MAX_LINK_DEPTH {return;}
+
+    for list in entry.children().filter(|child| child.is_element()) {
+        for item in list.children().filter(|child| child.is_element()) {
+            match (list.tag_name().name(), item.tag_name().name()) {
+                ("profiles", "profile") => contents.profiles.push(item),
+                ("rules", "rule") => contents.rules.push(item.attribute("name").unwrap_or("").to_string()),
+                ("infoLinks", "infoLink") => {
+                    let target = item.attribute("targetId").and_then(|id| index.get(id));
+                    match (item.attribute("type"), target) {
+                        (Some("profile"), Some(profile)) => contents.profiles.push(*profile),
+                        (Some("rule"), target) => {
+                            // rules usually live in the game system, so fall back on the link name
+                            let name = target.and_then(|rule| rule.attribute("name")).or(item.attribute("name"));
+                            contents.rules.push(name.unwrap_or("").to_string());
+                        },
+                        _ => {}
+                    }
+                },
+                ("categoryLinks", "categoryLink") if depth == 0 => {
+                    contents.keywords.push(item.attribute("name").unwrap_or("").to_string());
+                },
+                ("costs", "cost") if depth == 0 && item.attribute("name").is_some_and(|name| name.eq_ignore_ascii_case("pts")) => {
+                    contents.points = item.attribute("value").and_then(|value| value.parse::<f64>().ok()).map(|value| value as u32);
+                },
+                ("selectionEntries", "selectionEntry") | ("selectionEntryGroups", "selectionEntryGroup") => {
+                    if item.attribute("type") == Some("model") && depth <= 1 {
+                        contents.models.push((min_count(item), item.attribute("name").unwrap_or("").to_string()));
+                    }
+                    collect(item, index, depth + 1, contents);
+                },
+                ("entryLinks", "entryLink") => {
+                    if let Some(target) = item.attribute("targetId").and_then(|id| index.get(id)) {
+                        if target.attribute("type") == Some("model") && depth <= 1 {
+                            contents.models.push((min_count(item), target.attribute("name").unwrap_or("").to_string()));
+                        }
+                        collect(*target, index, depth + 1, contents);
+                    }
+                },
+                _ => {}
+            }
+        }
+    }
+}
+
+fn min_count(entry: Node) -> u32 {
+    entry.descendants()
+        .filter(|node| node.tag_name().name() == "constraint" && node.attribute("type") == Some("min"))
+        .filter_map(|constraint| constraint.attribute("value").and_then(|value| value.parse::<f64>().ok()))
+        .map(|value| value as u32)
+        .next()
+        .unwrap_or(1)
+}
+
+fn characteristic<'a>(profile: Node<'a, '_>, names: &[&str]) -> &'a str {
+    profile.descendants()
+        .find(|node| node.tag_name().name() == "characteristic" && node.attribute("name").is_some_and(|name| names.iter().any(|n| n.eq_ignore_ascii_case(name))))
+        .and_then(|node| node.text())
+        .map(|text| text.trim())
+        .unwrap_or("")
+}
+
+fn parse_stats(profile: Node) -> Result<UnitStats, String> {
+    Ok(UnitStats {
+        movement: parse_number(characteristic(profile, &["M"]), "movement").unwrap_or(0),
+        toughness: parse_number(characteristic(profile, &["T"]), "toughness")?,
+        save: parse_number(characteristic(profile, &["SV"]), "save")?,
+        invuln: None,
+        wounds: parse_number(characteristic(profile, &["W"]), "wounds")?,
+        leadership: parse_number(characteristic(profile, &["LD"]), "leadership")?,
+        oc: parse_number(characteristic(profile, &["OC"]), "objective control")?,
+    })
+}
+
+
+fn entry_to_unit(entry: Node, index: &HashMap<&str, Node>, warnings: &mut Vec<String>) -> Option<Unit> {
+    let mut contents = EntryContents::default();
+    collect(entry, index, 0, &mut contents);
+
+    let stat_profiles: Vec<Node> = contents.profiles.iter().filter(|profile| profile.attribute("typeName") == Some("Unit")).copied().collect();
+    if stat_profiles.is_empty() {return None;}
+
+    let name = entry.attribute("name").unwrap_or("").to_string();
+    let mut unit = Unit {
+        name: name.clone(),
+        ..Default::default()
+    };
+
+    // stats, with any further model profiles becoming extra statlines
+    let mut seen = Vec::new();
+    for profile in stat_profiles {
+        let profile_name = profile.attribute("name").unwrap_or("").to_string();
+        if seen.contains(&profile_name) {continue;}
+        match parse_stats(profile) {
+            Ok(stats) if seen.is_empty() => unit.stats = stats,
+            Ok(stats) => unit.extra_statlines.1.push((profile_name.clone(), stats)),
+            Err(e) => warnings.push(format!("{}: {}", name, e))
+        }
+        seen.push(profile_name);
+    }
+
+    // weapons
+    let mut weapon_names = Vec::new();
+    for profile in contents.profiles.iter() {
+        let type_name = profile.attribute("typeName").unwrap_or("");
+        if type_name != "Ranged Weapons" && type_name != "Melee Weapons" {continue;}
+        let weapon_name = profile.attribute("name").unwrap_or("").trim_start_matches("➤ ").to_string();
+        if weapon_names.contains(&weapon_name) {continue;}
+        weapon_names.push(weapon_name.clone());
+
+        let range = if type_name == "Melee Weapons" {"Melee"} else {characteristic(*profile, &["Range"])};
+        let weapon = parse_weapon_profile(&weapon_name, [
+            range,
+            characteristic(*profile, &["A"]),
+            characteristic(*profile, &["BS", "WS"]),
+            characteristic(*profile, &["S"]),
+            characteristic(*profile, &["AP"]),
+            characteristic(*profile, &["D"]),
+            characteristic(*profile, &["Keywords"])
+        ]);
+        match weapon {
+            Ok(weapon) => match weapon.range {
+                Range::Melee => unit.melee_weapons.push((weapon, 1)),
+                Range::Ranged(_) => unit.ranged_weapons.push((weapon, 1)),
+            },
+            Err(e) => warnings.push(format!("{}, {}: {}", name, weapon_name, e))
+        }
+    }
+
+    // abilities
+    let mut core_abilities = Vec::new();
+    let mut faction_abilities: Vec<String> = Vec::new();
+    for rule in contents.rules.iter() {
+        match CoreAbility::from_string(rule) {
+            Some(ability) => if !core_abilities.contains(&ability) {core_abilities.push(ability)},
+            None => if !rule.is_empty() && !faction_abilities.contains(rule) {faction_abilities.push(rule.clone())}
+        }
+    }
+    for profile in contents.profiles.iter().filter(|profile| profile.attribute("typeName") == Some("Abilities")) {
+        let ability_name = profile.attribute("name").unwrap_or("").to_string();
+        let description = characteristic(*profile, &["Description"]).to_string();
+
+        if let Some(ability) = CoreAbility::from_string(&ability_name) {
+            if !core_abilities.contains(&ability) {core_abilities.push(ability);}
+        } else if ability_name.eq_ignore_ascii_case("Invulnerable Save") {
+            unit.stats.invuln = parse_number(description.split_whitespace().find(|word| word.contains('+')).unwrap_or(""), "invulnerable save").ok();
+        } else if ability_name.to_uppercase().starts_with("DAMAGED:") {
+            unit.damaged = ability_name.split('-').nth(1).and_then(|wounds| parse_number(wounds.split_whitespace().next().unwrap_or(""), "damaged").ok());
+        } else if ability_name.eq_ignore_ascii_case("Leader") {
+            let attached: Vec<String> = description.split('■').skip(1).map(|line| line.trim().to_string()).filter(|line| !line.is_empty()).collect();
+            unit.leader = Some(attached);
+            if !core_abilities.contains(&CoreAbility::Leader) {core_abilities.push(CoreAbility::Leader);}
+        } else if !unit.unique_abilities.iter().any(|ability| ability.name == ability_name) {
+            unit.unique_abilities.push(Ability {name: ability_name, description});
+        }
+    }
+    if !core_abilities.is_empty() {
+        unit.core_abilities = core_abilities;
+    }
+    if !faction_abilities.is_empty() {
+        unit.faction_ability = Some(faction_abilities.join(", "));
+    }
+
+    // keywords and composition
+    for keyword in contents.keywords {
+        match keyword.strip_prefix("Faction: ") {
+            Some(faction) => unit.faction_keyword = faction.to_string(),
+            None => if !keyword.is_empty() {unit.keywords.push(keyword.to_uppercase())}
+        }
+    }
+    unit.unit_comp.points = contents.points;
+    for (count, model) in contents.models {
+        unit.unit_comp.add_model(count, model);
+    }
+
+    Some(unit)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CATALOGUE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<catalogue name="Space Marines">
+  <sharedProfiles>
+    <profile id="bolt-pistol" name="Bolt pistol" typeName="Ranged Weapons">
+      <characteristics>
+        <characteristic name="Range">12"</characteristic><characteristic name="A">1</characteristic><characteristic name="BS">3+</characteristic>
+        <characteristic name="S">4</characteristic><characteristic name="AP">0</characteristic><characteristic name="D">1</characteristic><characteristic name="Keywords">Pistol</characteristic>
+      </characteristics>
+    </profile>
+  </sharedProfiles>
+  <sharedRules>
+    <rule id="deep-strike" name="Deep Strike"/>
+  </sharedRules>
+  <sharedSelectionEntries>
+    <selectionEntry id="scouts" name="Scout Squad" type="unit">
+      <profiles>
+        <profile name="Scout" typeName="Unit">
+          <characteristics>
+            <characteristic name="M">6"</characteristic><characteristic name="T">4</characteristic><characteristic name="SV">4+</characteristic>
+            <characteristic name="W">2</characteristic><characteristic name="LD">6+</characteristic><characteristic name="OC">1</characteristic>
+          </characteristics>
+        </profile>
+        <profile name="➤ Combat knife" typeName="Melee Weapons">
+          <characteristics>
+            <characteristic name="Range">Melee</characteristic><characteristic name="A">3</characteristic><characteristic name="WS">3+</characteristic>
+            <characteristic name="S">4</characteristic><characteristic name="AP">0</characteristic><characteristic name="D">1</characteristic><characteristic name="Keywords">-</characteristic>
+          </characteristics>
+        </profile>
+        <profile name="Broken gun" typeName="Ranged Weapons">
+          <characteristics><characteristic name="Range">24"</characteristic><characteristic name="A">X</characteristic></characteristics>
+        </profile>
+        <profile name="Invulnerable Save" typeName="Abilities">
+          <characteristics><characteristic name="Description">This unit has a 5+ invulnerable save.</characteristic></characteristics>
+        </profile>
+      </profiles>
+      <infoLinks>
+        <infoLink targetId="deep-strike" type="rule"/>
+        <infoLink targetId="bolt-pistol" type="profile"/>
+      </infoLinks>
+      <categoryLinks>
+        <categoryLink name="Faction: Adeptus Astartes"/>
+        <categoryLink name="Infantry"/>
+      </categoryLinks>
+      <costs><cost name="pts" value="70.0"/></costs>
+      <selectionEntries>
+        <selectionEntry name="Scout Sergeant" type="model"/>
+        <selectionEntry name="Scout" type="model">
+          <constraints><constraint type="min" value="4.0"/></constraints>
+        </selectionEntry>
+      </selectionEntries>
+    </selectionEntry>
+    <selectionEntry id="crate" name="Ammo Crate" type="upgrade"/>
+  </sharedSelectionEntries>
+</catalogue>"#;
+
+    fn write_temp(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn catalogue_units_are_converted() {
+        let path = write_temp("scouts.cat", CATALOGUE);
+        let (name, units, warnings) = read_catalogue(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(name, "Space Marines");
+        assert_eq!(warnings, vec!["Scout Squad, Broken gun: invalid attacks 'X'"]);
+        let [(filename, unit)] = units.as_slice() else {panic!("{:?}", units)};
+        assert_eq!(filename, "Scout Squad");
+        assert_eq!((unit.stats.movement, unit.stats.save, unit.stats.leadership, unit.stats.invuln), (6, 4, 6, Some(5)));
+        assert_eq!(unit.core_abilities, vec![CoreAbility::DeepStrike]);
+        assert_eq!(unit.faction_keyword, "Adeptus Astartes");
+        assert_eq!(unit.keywords, vec!["INFANTRY"]);
+        assert_eq!(unit.unit_comp.points, Some(70));
+        let weapons: Vec<&str> = unit.ranged_weapons.iter().chain(unit.melee_weapons.iter()).map(|(weapon, _)| weapon.name.as_str()).collect();
+        assert_eq!(weapons, vec!["Bolt pistol", "Combat knife"]);
+    }
+
+    #[test]
+    fn roster_selections_repeat_by_number() {
+        let path = write_temp("roster.ros", r#"<roster name="Patrol"><forces><force><selections>
+            <selection name="Scout Squad" type="unit" number="2"/>
+            <selection name="Ammo Crate" type="upgrade"/>
+            <selection name="Captain" type="model"/>
+        </selections></force></forces></roster>"#);
+        let roster = read_roster(&path);
+        let catalogue = read_catalogue(&path);
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(roster.unwrap(), ("Patrol".to_string(), vec!["Scout Squad".to_string(), "Scout Squad".to_string(), "Captain".to_string()]));
+        assert!(catalogue.is_err());
+    }
+}
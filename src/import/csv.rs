@@ -0,0 +1,293 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::data::{Range, Unit, Weapon};
+
+use super::{parse_number, parse_weapon_profile};
+
+
+
+pub struct CsvSheet {
+    pub path: PathBuf,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl CsvSheet {
+    pub fn read(path: PathBuf) -> Result<Self> {
+        let mut reader = csv::ReaderBuilder::new().flexible(true).trim(csv::Trim::All).from_path(&path)?;
+        let headers = reader.headers()?.iter().map(|header| header.to_string()).collect();
+        let mut rows = Vec::new();
+        for record in reader.records() {
+            rows.push(record?.iter().map(|cell| cell.to_string()).collect());
+        }
+        Ok(Self {path, headers, rows})
+    }
+
+    /// guesses which column holds a field from its header
+    fn guess(&self, names: &[&str]) -> Option<usize> {
+        self.headers.iter().position(|header| names.contains(&header.to_lowercase().as_str()))
+    }
+}
+
+
+#[derive(Default)]
+pub struct UnitColumns {
+    pub name: Option<usize>,
+    pub movement: Option<usize>,
+    pub toughness: Option<usize>,
+    pub save: Option<usize>,
+    pub invuln: Option<usize>,
+    pub wounds: Option<usize>,
+    pub leadership: Option<usize>,
+    pub oc: Option<usize>,
+    pub keywords: Option<usize>,
+    pub faction_keyword: Option<usize>,
+}
+
+impl UnitColumns {
+    pub fn guess(sheet: &CsvSheet) -> Self {
+        Self {
+            name: sheet.guess(&["name", "unit", "unit name"]),
+            movement: sheet.guess(&["m", "move", "movement"]),
+            toughness: sheet.guess(&["t", "toughness"]),
+            save: sheet.guess(&["sv", "save"]),
+            invuln: sheet.guess(&["inv", "invuln", "invulnerable save"]),
+            wounds: sheet.guess(&["w", "wounds"]),
+            leadership: sheet.guess(&["ld", "leadership"]),
+            oc: sheet.guess(&["oc", "objective control"]),
+            keywords: sheet.guess(&["keywords"]),
+            faction_keyword: sheet.guess(&["faction", "faction keyword", "faction keywords"]),
+        }
+    }
+
+    pub fn fields_mut(&mut self) -> [(&str, &mut Option<usize>); 10] {
+        [
+            ("Name", &mut self.name),
+            ("Movement", &mut self.movement),
+            ("Toughness", &mut self.toughness),
+            ("Save", &mut self.save),
+            ("Invulnerable Save", &mut self.invuln),
+            ("Wounds", &mut self.wounds),
+            ("Leadership", &mut self.leadership),
+            ("Objective Control", &mut self.oc),
+            ("Keywords", &mut self.keywords),
+            ("Faction Keyword", &mut self.faction_keyword),
+        ]
+    }
+}
+
+
+#[derive(Default)]
+pub struct WeaponColumns {
+    pub unit: Option<usize>,
+    pub name: Option<usize>,
+    pub count: Option<usize>,
+    pub range: Option<usize>,
+    pub attacks: Option<usize>,
+    pub skill: Option<usize>,
+    pub strength: Option<usize>,
+    pub ap: Option<usize>,
+    pub damage: Option<usize>,
+    pub keywords: Option<usize>,
+}
+
+impl WeaponColumns {
+    pub fn guess(sheet: &CsvSheet) -> Self {
+        Self {
+            unit: sheet.guess(&["unit", "unit name"]),
+            name: sheet.guess(&["weapon", "name", "weapon name"]),
+            count: sheet.guess(&["count", "number", "#"]),
+            range: sheet.guess(&["range"]),
+            attacks: sheet.guess(&["a", "attacks"]),
+            skill: sheet.guess(&["bs", "ws", "skill", "bs/ws"]),
+            strength: sheet.guess(&["s", "strength"]),
+            ap: sheet.guess(&["ap"]),
+            damage: sheet.guess(&["d", "damage"]),
+            keywords: sheet.guess(&["keywords", "abilities"]),
+        }
+    }
+
+    pub fn fields_mut(&mut self) -> [(&str, &mut Option<usize>); 10] {
+        [
+            ("Unit Name", &mut self.unit),
+            ("Weapon Name", &mut self.name),
+            ("Count", &mut self.count),
+            ("Range", &mut self.range),
+            ("Attacks", &mut self.attacks),
+            ("Skill", &mut self.skill),
+            ("Strength", &mut self.strength),
+            ("AP", &mut self.ap),
+            ("Damage", &mut self.damage),
+            ("Keywords", &mut self.keywords),
+        ]
+    }
+}
+
+
+pub struct CsvImport {
+    pub folder: usize,
+    pub units: Option<(CsvSheet, UnitColumns)>,
+    pub weapons: Option<(CsvSheet, WeaponColumns)>,
+}
+
+impl CsvImport {
+    pub fn new(folder: usize) -> Self {
+        Self {
+            folder,
+            units: None,
+            weapons: None
+        }
+    }
+
+    /// Builds units from the mapped sheets, returning them alongside a description of every row that failed
+    pub fn build_units(&self) -> (Vec<Unit>, Vec<String>) {
+        let mut units = Vec::new();
+        let mut errors = Vec::new();
+
+        let Some((sheet, columns)) = &self.units else {
+            errors.push("No units sheet selected".to_string());
+            return (units, errors);
+        };
+
+        for (i, row) in sheet.rows.iter().enumerate() {
+            match parse_unit(row, columns) {
+                Ok(unit) => units.push(unit),
+                Err(e) => errors.push(format!("Units row {}: {}", i + 2, e))
+            }
+        }
+
+        if let Some((sheet, columns)) = &self.weapons {
+            for (i, row) in sheet.rows.iter().enumerate() {
+                let unit_name = cell(row, columns.unit);
+                let mut matches = units.iter_mut().filter(|unit| unit.name.eq_ignore_ascii_case(unit_name));
+                let Some(unit) = matches.next() else {
+                    errors.push(format!("Weapons row {}: no unit named '{}'", i + 2, unit_name));
+                    continue;
+                };
+                if matches.next().is_some() {
+                    errors.push(format!("Weapons row {}: more than one unit is named '{}'", i + 2, unit_name));
+                    continue;
+                }
+                match parse_weapon(row, columns) {
+                    Ok((weapon, count)) => {
+                        match weapon.range {
+                            Range::Melee => unit.melee_weapons.push((weapon, count)),
+                            Range::Ranged(_) => unit.ranged_weapons.push((weapon, count)),
+                        }
+                    },
+                    Err(e) => errors.push(format!("Weapons row {}: {}", i + 2, e))
+                }
+            }
+        }
+
+        (units, errors)
+    }
+}
+
+
+fn cell(row: &[String], column: Option<usize>) -> &str {
+    column.and_then(|i| row.get(i)).map(|cell| cell.as_str()).unwrap_or("")
+}
+
+fn number(row: &[String], column: Option<usize>, field: &str) -> Result<u32, String> {
+    parse_number(cell(row, column), field)
+}
+
+fn parse_unit(row: &[String], columns: &UnitColumns) -> Result<Unit, String> {
+    let name = cell(row, columns.name);
+    if name.is_empty() {
+        return Err("missing name".to_string());
+    }
+
+    let mut unit = Unit {
+        name: name.to_string(),
+        ..Default::default()
+    };
+    unit.stats.movement = number(row, columns.movement, "movement")?;
+    unit.stats.toughness = number(row, columns.toughness, "toughness")?;
+    unit.stats.save = number(row, columns.save, "save")?;
+    // an empty cell means no invulnerable save, anything else has to be a save
+    unit.stats.invuln = match cell(row, columns.invuln) {
+        "" | "-" => None,
+        _ => Some(number(row, columns.invuln, "invulnerable save")?)
+    };
+    unit.stats.wounds = number(row, columns.wounds, "wounds")?;
+    unit.stats.leadership = number(row, columns.leadership, "leadership")?;
+    unit.stats.oc = number(row, columns.oc, "objective control")?;
+    unit.keywords = cell(row, columns.keywords).split(',').map(|keyword| keyword.trim().to_uppercase()).filter(|keyword| !keyword.is_empty()).collect();
+    unit.faction_keyword = cell(row, columns.faction_keyword).to_string();
+    Ok(unit)
+}
+
+fn parse_weapon(row: &[String], columns: &WeaponColumns) -> Result<(Weapon, u32), String> {
+    let weapon = parse_weapon_profile(cell(row, columns.name), [
+        cell(row, columns.range),
+        cell(row, columns.attacks),
+        cell(row, columns.skill),
+        cell(row, columns.strength),
+        cell(row, columns.ap),
+        cell(row, columns.damage),
+        cell(row, columns.keywords)
+    ])?;
+    let count = if columns.count.is_some() {number(row, columns.count, "count")?} else {1};
+    Ok((weapon, count))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sheet(headers: &[&str], rows: &[&[&str]]) -> CsvSheet {
+        CsvSheet {
+            path: PathBuf::new(),
+            headers: headers.iter().map(|header| header.to_string()).collect(),
+            rows: rows.iter().map(|row| row.iter().map(|cell| cell.to_string()).collect()).collect(),
+        }
+    }
+
+    fn import(units: CsvSheet, weapons: CsvSheet) -> CsvImport {
+        let unit_columns = UnitColumns::guess(&units);
+        let weapon_columns = WeaponColumns::guess(&weapons);
+        CsvImport {folder: 0, units: Some((units, unit_columns)), weapons: Some((weapons, weapon_columns))}
+    }
+
+    const UNIT_HEADERS: [&str; 8] = ["Name", "M", "T", "Sv", "Inv", "W", "Ld", "OC"];
+    const WEAPON_HEADERS: [&str; 9] = ["Unit", "Weapon", "Range", "A", "BS", "S", "AP", "D", "Keywords"];
+
+    #[test]
+    fn weapons_go_to_their_unit() {
+        let units = sheet(&UNIT_HEADERS, &[&["Intercessors", "6\"", "4", "3+", "", "2", "6+", "2"]]);
+        let weapons = sheet(&WEAPON_HEADERS, &[&["intercessors", "Bolt rifle", "24\"", "2", "3+", "4", "-1", "1", ""]]);
+        let (units, errors) = import(units, weapons).build_units();
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(units[0].stats.movement, 6);
+        assert_eq!(units[0].stats.invuln, None);
+        assert_eq!(units[0].ranged_weapons.len(), 1);
+    }
+
+    #[test]
+    fn bad_cells_and_shared_names_are_reported() {
+        let units = sheet(&UNIT_HEADERS, &[
+            &["Scouts", "6\"", "4", "4+", "", "1", "6+", "1"],
+            &["Scouts", "6\"", "4", "4+", "", "1", "6+", "1"],
+            &["Broken", "6-8", "4", "4+", "", "1", "6+", "1"],
+            &["Shielded", "6\"", "4", "4+", "4++ vs ranged", "1", "6+", "1"],
+        ]);
+        let weapons = sheet(&WEAPON_HEADERS, &[
+            &["Scouts", "Bolt pistol", "12\"", "1", "3+", "4", "0", "1", ""],
+            &["Nobody", "Bolt pistol", "12\"", "1", "3+", "4", "0", "1", ""],
+        ]);
+        let (units, errors) = import(units, weapons).build_units();
+        assert_eq!(units.len(), 2);
+        assert!(units.iter().all(|unit| unit.ranged_weapons.is_empty()));
+        assert_eq!(errors, vec![
+            "Units row 4: invalid movement '6-8'",
+            "Units row 5: invalid invulnerable save '4++ vs ranged'",
+            "Weapons row 2: more than one unit is named 'Scouts'",
+            "Weapons row 3: no unit named 'Nobody'",
+        ]);
+    }
+}
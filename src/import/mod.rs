@@ -0,0 +1,270 @@
+use std::{fs::read_to_string, path::{Path, PathBuf}};
+
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+
+use crate::{data::{Range, Unit, VariableValue, Weapon, WeaponAbility}, export::FolderFile};
+
+mod battlescribe;
+mod csv;
+
+pub use battlescribe::{read_catalogue, read_roster};
+pub use csv::{CsvImport, CsvSheet, UnitColumns, WeaponColumns};
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum ImportType {
+    Ron,
+    Json,
+    Yaml,
+}
+
+impl ImportType {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "ron" => Some(ImportType::Ron),
+            "json" => Some(ImportType::Json),
+            "yaml" | "yml" => Some(ImportType::Yaml),
+            _ => None
+        }
+    }
+
+    fn parse<T: DeserializeOwned>(&self, s: &str) -> Result<T> {
+        Ok(match self {
+            ImportType::Ron => ron::from_str(s)?,
+            ImportType::Json => {
+                let mut value: serde_json::Value = serde_json::from_str(s)?;
+                upgrade_json_upgrades(&mut value);
+                serde_json::from_value(value)?
+            },
+            ImportType::Yaml => {
+                let mut value: serde_yaml::Value = serde_yaml::from_str(s)?;
+                upgrade_yaml_upgrades(&mut value);
+                serde_yaml::from_value(value)?
+            },
+        })
+    }
+}
+
+
+/// Crusade upgrades that gained a second field, and were exported with just their ability before then
+const WIDENED_UPGRADES: [&str; 3] = ["Relic", "Enhancement", "BattleScar"];
+
+/// Wraps the ability of upgrades exported before relic tiers, enhancement points and scar effects into the current tuple shape.
+/// RON reads the old shape as it is, since the new fields have defaults
+fn upgrade_json_upgrades(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            if map.len() == 1 {
+                if let Some((key, inner)) = map.iter_mut().next() {
+                    if WIDENED_UPGRADES.contains(&key.as_str()) && inner.is_object() {
+                        *inner = serde_json::Value::Array(vec![inner.take()]);
+                        return;
+                    }
+                }
+            }
+            map.values_mut().for_each(upgrade_json_upgrades);
+        },
+        serde_json::Value::Array(values) => values.iter_mut().for_each(upgrade_json_upgrades),
+        _ => {}
+    }
+}
+
+/// The yaml version of `upgrade_json_upgrades`, where variants are written as tags
+fn upgrade_yaml_upgrades(value: &mut serde_yaml::Value) {
+    match value {
+        serde_yaml::Value::Tagged(tagged) => {
+            if WIDENED_UPGRADES.iter().any(|name| tagged.tag == name) && tagged.value.is_mapping() {
+                tagged.value = serde_yaml::Value::Sequence(vec![tagged.value.clone()]);
+            } else {
+                upgrade_yaml_upgrades(&mut tagged.value);
+            }
+        },
+        serde_yaml::Value::Mapping(map) => map.values_mut().for_each(upgrade_yaml_upgrades),
+        serde_yaml::Value::Sequence(values) => values.iter_mut().for_each(upgrade_yaml_upgrades),
+        _ => {}
+    }
+}
+
+
+/// Makes a unit or folder name safe to use as a single file or folder name inside the working folder
+pub fn sanitise_filename(name: &str) -> String {
+    let cleaned: String = name.chars()
+        .map(|c| if c.is_control() || "/\\:*?\"<>|".contains(c) {'_'} else {c})
+        .collect();
+    // leading dots would make the name hidden, or point outside the folder
+    let cleaned = cleaned.trim().trim_matches('.').trim();
+    if cleaned.is_empty() {"unnamed".to_string()} else {cleaned.to_string()}
+}
+
+
+/// Reads either a single unit or a whole exported subfolder, returning (filename, unit) pairs
+pub fn read_units(path: &PathBuf) -> Result<Vec<(String, Unit)>> {
+    let import_type = ImportType::from_path(path).ok_or(anyhow!("Unsupported file type"))?;
+    let s = read_to_string(path)?;
+
+    if let Ok(folder) = import_type.parse::<FolderFile>(&s) {
+        if !folder.units.is_empty() {
+            return Ok(folder.units.into_iter().collect());
+        }
+    }
+
+    let unit: Unit = import_type.parse(&s)?;
+    let filename = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or(&unit.name).to_string();
+    Ok(vec![(filename, unit)])
+}
+
+
+/// reads a number, ignoring decorations like 6" or 3+. Anything else, like 12-24 or D6+1, is an error
+fn parse_number(text: &str, field: &str) -> Result<u32, String> {
+    text.trim()
+        .trim_end_matches(['"', '+', '\''])
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid {} '{}'", field, text))
+}
+
+/// reads an armour penetration value, written as -1 or 1, stored as its size
+fn parse_ap(text: &str) -> Result<i32, String> {
+    let trimmed = text.trim();
+    parse_number(trimmed.strip_prefix('-').unwrap_or(trimmed), "ap").map(|ap| ap as i32)
+}
+
+/// Builds a weapon from the text of each column of its profile, as found in spreadsheets and catalogues.
+/// The columns are in datasheet order: range, attacks, skill, strength, ap, damage and keywords
+fn parse_weapon_profile(name: &str, profile: [&str; 7]) -> Result<Weapon, String> {
+    let [range, attacks, skill, strength, ap, damage, keywords] = profile;
+    if name.is_empty() {
+        return Err("missing weapon name".to_string());
+    }
+
+    let range = if range.eq_ignore_ascii_case("melee") {
+        Range::Melee
+    } else {
+        match parse_number(range, "range")? {
+            0 => Range::Melee,
+            x => Range::Ranged(x)
+        }
+    };
+
+    let attacks_text = attacks.to_uppercase();
+    let attacks = VariableValue::from_string(&attacks_text).map_err(|_| format!("invalid attacks '{}'", attacks_text))?;
+    let damage_text = damage.to_uppercase();
+    let damage = VariableValue::from_string(&damage_text).map_err(|_| format!("invalid damage '{}'", damage_text))?;
+
+    let keywords: Vec<WeaponAbility> = keywords
+        .trim_matches(|c| c == '[' || c == ']' || c == '-')
+        .split(',')
+        .map(WeaponAbility::from_string)
+        .filter(|keyword| keyword != &WeaponAbility::None)
+        .collect();
+
+    // torrent weapons have no skill value
+    let skill = if keywords.contains(&WeaponAbility::Torrent) {
+        parse_number(skill, "skill").unwrap_or(0)
+    } else {
+        parse_number(skill, "skill")?
+    };
+
+    Ok(Weapon {
+        name: name.to_string(),
+        range,
+        attacks,
+        skill,
+        strength: parse_number(strength, "strength")?,
+        ap: parse_ap(ap)?,
+        damage,
+        keywords,
+        charge: Default::default(),
+        library: None,
+        uid: 0
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::CrusadeUpgrade;
+
+    fn upgrade_names(unit: &Unit) -> Vec<String> {
+        unit.crusade_data.upgrades.iter().map(|upgrade| format!("{} {}", upgrade.to_string(), upgrade.name())).collect()
+    }
+
+    #[test]
+    fn numbers_ignore_decorations_only() {
+        assert_eq!(parse_number("6\"", "range"), Ok(6));
+        assert_eq!(parse_number(" 3+ ", "skill"), Ok(3));
+        assert_eq!(parse_number("12", "strength"), Ok(12));
+        assert!(parse_number("12-24", "range").is_err());
+        assert!(parse_number("D6+1", "attacks").is_err());
+        assert!(parse_number("", "wounds").is_err());
+    }
+
+    #[test]
+    fn ap_keeps_its_size() {
+        assert_eq!(parse_ap("-2"), Ok(2));
+        assert_eq!(parse_ap("0"), Ok(0));
+        assert_eq!(parse_ap("1"), Ok(1));
+        assert!(parse_ap("-X").is_err());
+    }
+
+    #[test]
+    fn weapon_profiles_parse_or_fail() {
+        let weapon = parse_weapon_profile("Bolt rifle", ["24\"", "2", "3+", "4", "-1", "1", "Assault, Heavy"]).unwrap();
+        assert_eq!(weapon.range, Range::Ranged(24));
+        assert_eq!((weapon.skill, weapon.strength, weapon.ap), (3, 4, 1));
+        assert_eq!(weapon.keywords, vec![WeaponAbility::Assault, WeaponAbility::Heavy]);
+
+        let melee = parse_weapon_profile("Chainsword", ["Melee", "D6+1", "3+", "4", "0", "1", ""]).unwrap();
+        assert_eq!(melee.range, Range::Melee);
+        assert_eq!(melee.attacks.to_string(), "D6+1");
+
+        let torrent = parse_weapon_profile("Flamer", ["12\"", "D6", "N/A", "4", "0", "1", "Torrent"]).unwrap();
+        assert_eq!(torrent.skill, 0);
+
+        assert!(parse_weapon_profile("", ["24", "1", "3+", "4", "0", "1", ""]).is_err());
+        assert!(parse_weapon_profile("Bolter", ["12-24", "1", "3+", "4", "0", "1", ""]).is_err());
+        assert!(parse_weapon_profile("Bolter", ["24", "1", "3+", "4", "-X", "1", ""]).is_err());
+        assert!(parse_weapon_profile("Bolter", ["24", "1", "3+", "4", "0", "D", ""]).is_err());
+    }
+
+    #[test]
+    fn sanitised_names_stay_in_the_folder() {
+        assert_eq!(sanitise_filename("Captain w/ Jump Pack"), "Captain w_ Jump Pack");
+        assert_eq!(sanitise_filename("../../x"), "_.._x");
+        assert_eq!(sanitise_filename(".."), "unnamed");
+        assert_eq!(sanitise_filename("a\\b:c"), "a_b_c");
+    }
+
+    #[test]
+    fn json_upgrades_from_before_tiers_still_import() {
+        let json = r#"{"name": "Old", "crusade_data": {"upgrades": [
+            {"Relic": {"name": "Blade", "description": "Sharp"}},
+            {"Enhancement": {"name": "Aura", "description": "Glows"}},
+            {"BattleScar": {"name": "Limp", "description": "Slow"}},
+            {"BattleTrait": {"name": "Brave", "description": "Bold"}}
+        ]}}"#;
+        let unit: Unit = ImportType::Json.parse(json).unwrap();
+        assert_eq!(upgrade_names(&unit), vec!["Relic Blade", "Enhancement Aura", "Battle Scar Limp", "Battle Trait Brave"]);
+        assert!(matches!(&unit.crusade_data.upgrades[2], CrusadeUpgrade::BattleScar(_, effects) if effects.is_empty()));
+    }
+
+    #[test]
+    fn yaml_upgrades_from_before_tiers_still_import() {
+        let yaml = "name: Old\ncrusade_data:\n  upgrades:\n  - !Relic\n    name: Blade\n    description: Sharp\n  - !BattleScar\n    name: Limp\n    description: Slow\n";
+        let unit: Unit = ImportType::Yaml.parse(yaml).unwrap();
+        assert_eq!(upgrade_names(&unit), vec!["Relic Blade", "Battle Scar Limp"]);
+    }
+
+    #[test]
+    fn current_upgrades_round_trip() {
+        let mut unit = Unit::default();
+        unit.crusade_data.upgrades.push(CrusadeUpgrade::Enhancement(Default::default(), 15));
+        let json = serde_json::to_string(&unit).unwrap();
+        let yaml = serde_yaml::to_string(&unit).unwrap();
+        for parsed in [ImportType::Json.parse::<Unit>(&json).unwrap(), ImportType::Yaml.parse::<Unit>(&yaml).unwrap()] {
+            assert!(matches!(parsed.crusade_data.upgrades[0], CrusadeUpgrade::Enhancement(_, 15)));
+        }
+    }
+}
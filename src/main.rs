@@ -1,14 +1,39 @@
-use app::{string_to_color32, DatasheetApp, DatasheetAppSettings};
+use std::{collections::BTreeMap, fs, path::{Path, PathBuf}};
+
+use app::{string_to_color32, DatasheetApp, DatasheetAppSettings, FOLDER_FILES};
+use data::{Severity, Unit, UnitEditData, WeaponLibrary};
 use egui::{Color32, IconData, ThemePreference};
+use export::default_print_profiles;
 use include_assets::{include_dir, NamedArchive};
 mod data;
 mod app;
 mod export;
+mod import;
 mod helper_funcs;
 
 
 fn main() -> eframe::Result {
 
+    // command line tools, the app opens when no arguments are given
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(|arg| arg.as_str()) {
+        Some("schema") => {
+            match args.get(2).map(|arg| arg.as_str()) {
+                Some("folder") => println!("{}", export::folder_schema()),
+                _ => println!("{}", export::unit_schema()),
+            }
+            return Ok(());
+        },
+        Some("validate") => {
+            let Some(path) = args.get(2) else {
+                eprintln!("usage: datasheet_creator validate <unit file or folder>");
+                std::process::exit(2);
+            };
+            std::process::exit(validate(PathBuf::from(path)));
+        },
+        _ => {}
+    }
+
     let archive = NamedArchive::load(include_dir!("assets"));
     let icon_data = archive.get("Logo_128.png").unwrap();
     let icon = image::load_from_memory(icon_data).unwrap().to_rgba8();
@@ -35,6 +60,12 @@ fn main() -> eframe::Result {
                 let key_col = string_to_color32(storage.get_string("Keyword_Colour").unwrap_or("".to_string())).unwrap_or(Color32::LIGHT_BLUE);
                 let default_faction_ability = storage.get_string("Default_Faction_Ability").unwrap_or("".to_string());
                 let default_faction_keyword = storage.get_string("Default_Faction_Keyword").unwrap_or("".to_string());
+                let print_profiles = ron::from_str(&storage.get_string("Print_Profiles").unwrap_or("".to_string())).unwrap_or(default_print_profiles());
+                let selected_print_profile = storage.get_string("Selected_Print_Profile").unwrap_or("".to_string()).parse().unwrap_or(0);
+                let image_dpi = storage.get_string("Image_DPI").unwrap_or("".to_string()).parse().unwrap_or(300);
+                let svg_embed_fonts = storage.get_string("SVG_Embed_Fonts").unwrap_or("true".to_string()) == "true";
+                let rules_reference = storage.get_string("Rules_Reference").unwrap_or("false".to_string()) == "true";
+                let rank_thresholds = ron::from_str(&storage.get_string("Rank_Thresholds").unwrap_or("".to_string())).unwrap_or_default();
 
                 return Ok(Box::new(DatasheetApp {
                     settings: DatasheetAppSettings {
@@ -43,6 +74,12 @@ fn main() -> eframe::Result {
                         default_faction_ability,
                         default_faction_keyword,
                         dark_mode: theme_pref == ThemePreference::Dark,
+                        print_profiles,
+                        selected_print_profile,
+                        image_dpi,
+                        svg_embed_fonts,
+                        rules_reference,
+                        rank_thresholds,
                     },
                     ..Default::default()
                 }))
@@ -51,4 +88,134 @@ fn main() -> eframe::Result {
             
         })
     )
-}
\ No newline at end of file
+}
+
+
+/// Unit files in a folder and its subfolders, skipping the shared folder files
+fn unit_files(path: PathBuf, files: &mut Vec<PathBuf>) {
+    let Ok(dir) = fs::read_dir(path) else {return};
+    for entry in dir.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            unit_files(path, files);
+        } else if import::ImportType::from_path(&path).is_some() && !FOLDER_FILES.iter().any(|name| path.file_stem().is_some_and(|stem| stem == *name)) {
+            files.push(path);
+        }
+    }
+}
+
+/// The working folder the path belongs to, the nearest folder holding one of the folder files.
+/// Without one, a folder is its own working folder and a unit file belongs to its folder's parent like in the app
+fn working_folder(path: &Path) -> PathBuf {
+    let is_working_folder = |folder: &Path| FOLDER_FILES.iter().any(|name| folder.join(format!("{}.ron", name)).is_file());
+    if let Some(folder) = path.ancestors().filter(|folder| folder.is_dir()).find(|folder| is_working_folder(folder)) {
+        return folder.to_path_buf();
+    }
+    if path.is_dir() {
+        path.to_path_buf()
+    } else {
+        path.parent().and_then(Path::parent).unwrap_or(Path::new(".")).to_path_buf()
+    }
+}
+
+/// Every unit of the working folder by its "folder/filename" reference, with library weapons and variants resolved the way the app resolves them on open
+fn resolved_units(folder: &Path) -> (BTreeMap<(PathBuf, String), Unit>, Vec<String>) {
+    let mut errors = Vec::new();
+    let library: WeaponLibrary = match fs::read_to_string(folder.join("WEAPONS.ron")) {
+        Ok(s) => ron::from_str(&s).unwrap_or_else(|e| {
+            errors.push(format!("{}: Error: failed to read: {}", folder.join("WEAPONS.ron").display(), e));
+            WeaponLibrary::default()
+        }),
+        Err(_) => WeaponLibrary::default(),
+    };
+
+    let mut files = Vec::new();
+    unit_files(folder.to_path_buf(), &mut files);
+    let mut units = BTreeMap::new();
+    let mut references = BTreeMap::new();
+    for file in files {
+        let Ok(read) = import::read_units(&file) else {continue};
+        let parent = file.parent().and_then(Path::file_name).map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        for (filename, mut unit) in read {
+            unit.assign_weapon_uids();
+            if library.resolve_unit(&mut unit, &BTreeMap::new()) {
+                unit = UnitEditData::from((&unit, filename.clone())).into();
+            }
+            references.insert(format!("{}/{}", parent, filename), (file.clone(), filename.clone()));
+            units.insert((file.clone(), filename), unit);
+        }
+    }
+
+    // repeated so variants of variants pick up changes, stopping early on a cycle
+    for _ in 0..8 {
+        let mut changed = false;
+        let keys: Vec<(PathBuf, String)> = units.keys().cloned().collect();
+        for key in keys {
+            let unit = &units[&key];
+            let Some(base_key) = unit.base.as_ref().and_then(|base| references.get(&base.unit)) else {continue};
+            if *base_key == key {
+                continue;
+            }
+            let resolved = unit.resolve_variant(&units[base_key]);
+            if ron::to_string(&resolved).ok() != ron::to_string(unit).ok() {
+                units.insert(key, resolved);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    (units, errors)
+}
+
+/// Prints the problems with every unit at the path, returning 1 if any have errors and 2 if the path can't be read
+fn validate(path: PathBuf) -> i32 {
+    let mut files = Vec::new();
+    if path.is_dir() {
+        unit_files(path.clone(), &mut files);
+    } else if path.is_file() {
+        files.push(path.clone());
+    } else {
+        eprintln!("{} does not exist", path.display());
+        return 2;
+    }
+    files.sort();
+
+    let (resolved, library_errors) = resolved_units(&working_folder(&path));
+    let mut errors = library_errors.len();
+    let mut warnings = 0;
+    for error in library_errors {
+        println!("{}", error);
+    }
+    for file in files {
+        let units = match import::read_units(&file) {
+            Ok(units) => units,
+            Err(err) => {
+                println!("{}: Error: failed to read: {}", file.display(), err);
+                errors += 1;
+                continue;
+            }
+        };
+        for (filename, mut unit) in units {
+            // units outside the working folder can only be checked as they are
+            let unit = match resolved.get(&(file.clone(), filename.clone())) {
+                Some(resolved) => resolved.clone(),
+                None => {
+                    unit.assign_weapon_uids();
+                    unit
+                }
+            };
+            for problem in unit.validate() {
+                match problem.severity {
+                    Severity::Error => errors += 1,
+                    Severity::Warning => warnings += 1,
+                }
+                println!("{} ({}): {}", file.display(), filename, problem.to_string());
+            }
+        }
+    }
+
+    println!("{} errors, {} warnings", errors, warnings);
+    if errors > 0 {1} else {0}
+}